@@ -1,25 +1,24 @@
-use reqwest;
 use secrecy::{ExposeSecret, SecretBox};
-use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Mutex;
-use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Notify;
 use zeroize::Zeroize;
 
-use crate::network_controller::client_builder::build_client;
-
-const ERROR_NO_CONEXION: &str = "No se detecta conexión a internet.";
-const ERROR_PORTAL_NO_DISPONIBLE: &str = "No estas en el wifi UABC o ya estas conectado.";
-const ERROR_CREDENCIALES: &str = "Credenciales invalidas.";
-const ERROR_TIEMPO_ESPERA: &str = "Tiempo de espera agotado.";
-const ERROR_GENERAL: &str = "Ocurrió un error al conectarse a la red UABC.";
-
-const MONITORING_INTERVAL: Duration = Duration::from_secs(60);
-const SUCCESS_INTERVAL: Duration = Duration::from_secs(20);
-const INITIAL_BACKOFF_SECS: u64 = 5;
-const MAX_BACKOFF_SECS: u64 = 5 * 60;
-const BACKOFF_MULTIPLIER: f64 = 2.0;
-const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+use crate::error::{AuthError, AuthResult};
+use crate::history::{self, AttemptOutcome, AuthAttempt};
+use crate::keyring_controller::vault;
+use crate::network_controller::captive_api;
+use crate::network_controller::network_sync::{
+    current_network_profile, current_network_state, current_ssid, subscribe_network_state,
+    NetworkStateEvent,
+};
+use crate::network_controller::profiles::NetworkProfile;
+use crate::network_controller::tofu;
+use crate::portal_controller::driver::{driver_for_network, PortalDriver, PortalPresence};
+use crate::portal_controller::policy;
+use crate::settings;
 
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -57,41 +56,97 @@ impl SecureString {
     }
 }
 
+/// Antes del vencimiento previsto se empieza a vigilar el portal de cerca.
+const SESSION_EXPIRY_MARGIN: Duration = Duration::from_secs(15);
+/// Intervalo de verificación alrededor del vencimiento previsto.
+const SESSION_EXPIRY_POLL: Duration = Duration::from_secs(3);
+
+/// Origen de la duración de sesión usada para predecir el vencimiento.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifetimeSource {
+    /// Tiempo restante reportado por la API del portal (RFC 8908).
+    CaptiveApi,
+    /// Anunciada por el portal en la respuesta del login.
+    Portal,
+    /// Estimada a partir de los cierres de sesión observados.
+    Learned,
+}
+
+impl LifetimeSource {
+    pub fn as_key(self) -> &'static str {
+        match self {
+            LifetimeSource::CaptiveApi => "captive_api",
+            LifetimeSource::Portal => "portal",
+            LifetimeSource::Learned => "learned",
+        }
+    }
+}
+
+/// Sesión abierta por el último login exitoso de esta instancia.
+struct PortalSession {
+    started_at: SystemTime,
+    ssid: Option<String>,
+    lifetime: Option<(Duration, LifetimeSource)>,
+}
+
+/// Señales que despiertan al monitor mientras espera entre verificaciones.
+pub enum MonitorSignal {
+    Network(NetworkStateEvent),
+    Stop,
+}
+
 pub struct Auth {
     email: String,
     password: SecretBox<SecureString>,
-    check_interval: Duration,
-    success_interval: Duration,
     should_stop: Arc<AtomicBool>,
     consecutive_failures: Arc<AtomicU32>,
     current_backoff_secs: Arc<Mutex<u64>>,
+    driver: Option<Arc<dyn PortalDriver>>,
+    signal_tx: UnboundedSender<MonitorSignal>,
+    signal_rx: Mutex<Option<UnboundedReceiver<MonitorSignal>>>,
+    /// Interrumpe el login en curso; las señales se leen solo entre intentos.
+    stop_notify: Notify,
+    session: Mutex<Option<PortalSession>>,
 }
 
 impl Auth {
     pub fn new(email: &str, password: &str) -> Self {
+        let (signal_tx, signal_rx) = mpsc::unbounded_channel();
         Auth {
             email: email.to_string(),
             password: SecretBox::new(Box::new(SecureString::from(password))),
-            check_interval: MONITORING_INTERVAL,
-            success_interval: SUCCESS_INTERVAL,
             should_stop: Arc::new(AtomicBool::new(false)),
             consecutive_failures: Arc::new(AtomicU32::new(0)),
-            current_backoff_secs: Arc::new(Mutex::new(INITIAL_BACKOFF_SECS)),
+            current_backoff_secs: Arc::new(Mutex::new(settings::current().initial_backoff_secs)),
+            driver: None,
+            signal_tx,
+            signal_rx: Mutex::new(Some(signal_rx)),
+            stop_notify: Notify::new(),
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Usa siempre el driver indicado en lugar de elegirlo según el SSID actual.
+    pub fn with_driver(email: &str, password: &str, driver: Arc<dyn PortalDriver>) -> Self {
+        Auth {
+            driver: Some(driver),
+            ..Auth::new(email, password)
         }
     }
 
     fn calculate_backoff(&self) -> Duration {
         let failures = self.consecutive_failures.load(Ordering::SeqCst);
+        let config = settings::current();
         
         if failures == 0 {
-            return self.check_interval;
+            return config.monitoring_interval();
         }
 
         let backoff_secs = match self.current_backoff_secs.lock() {
             Ok(mut guard) => {
                 let current = *guard;
-                let next = ((current as f64) * BACKOFF_MULTIPLIER) as u64;
-                *guard = next.min(MAX_BACKOFF_SECS);
+                let next = ((current as f64) * config.backoff_multiplier) as u64;
+                *guard = next.min(config.max_backoff_secs);
                 current
             }
             Err(poisoned) => {
@@ -100,19 +155,23 @@ impl Auth {
             }
         };
 
-        Duration::from_secs(backoff_secs.min(MAX_BACKOFF_SECS))
+        Duration::from_secs(backoff_secs.min(config.max_backoff_secs))
     }
 
     fn record_success(&self) {
+        self.reset_backoff();
+    }
+
+    fn reset_backoff(&self) {
         self.consecutive_failures.store(0, Ordering::SeqCst);
         if let Ok(mut guard) = self.current_backoff_secs.lock() {
-            *guard = INITIAL_BACKOFF_SECS;
+            *guard = settings::current().initial_backoff_secs;
         }
     }
 
     fn record_2efailure(&self) {
         let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst);
-        if failures >= MAX_CONSECUTIVE_FAILURES {
+        if failures >= settings::current().max_consecutive_failures {
             eprintln!(
                 "[auth] Demasiados fallos consecutivos ({}), considera verificar la conexión",
                 failures + 1
@@ -120,225 +179,347 @@ impl Auth {
         }
     }
 
-    pub fn login(&self) -> Result<bool, Box<dyn std::error::Error>> {
+    pub async fn login(&self) -> AuthResult<bool> {
+        let (result, attempt) = self.timed_login().await;
+        history::record(&attempt);
+        result.map(|_| true)
+    }
+
+    /// Ejecuta un intento y arma su registro para el historial (sin backoff).
+    async fn timed_login(&self) -> (AuthResult<AttemptOutcome>, AuthAttempt) {
+        let timestamp_ms = history::now_ms();
         let start_time = Instant::now();
+        let ssid = current_ssid();
+        let mut timings = LoginTimings::default();
 
-        match check_uabc_connection() {
-            Ok(is_direct_access) => {
-                let _elapsed = start_time.elapsed();
-
-                if is_direct_access {
-                    Ok(true)
-                } else {
-                    let login_start = Instant::now();
-                    match auto_login(&self.email, self.password.expose_secret().expose()) {
-                        Ok(success) => {
-                            let _login_elapsed = login_start.elapsed();
-                            if success {
-                                Ok(true)
-                            } else {
-                                Err(ERROR_CREDENCIALES.into())
-                            }
-                        }
-                        Err(e) => {
-                            if e.to_string().contains("timeout") {
-                                Err(ERROR_TIEMPO_ESPERA.into())
-                            } else if e.to_string().contains("connection") {
-                                Err(ERROR_NO_CONEXION.into())
-                            } else {
-                                Err(ERROR_GENERAL.into())
-                            }
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                if e.to_string().contains("timeout") {
-                    Err(ERROR_TIEMPO_ESPERA.into())
-                } else if e.to_string().contains("connection") {
-                    Err(ERROR_NO_CONEXION.into())
-                } else {
-                    Err(ERROR_PORTAL_NO_DISPONIBLE.into())
-                }
-            }
+        let result = self.run_login(ssid.as_deref(), &mut timings).await;
+        if let Err(e) = &result {
+            policy::report(e, ssid.as_deref());
         }
+
+        let (outcome, error_code, error_message) = match &result {
+            Ok(outcome) => (*outcome, None, None),
+            Err(e) => (
+                AttemptOutcome::Failed,
+                Some(e.code().to_string()),
+                Some(match e.details() {
+                    Some(details) => format!("{} ({})", e, details),
+                    None => e.to_string(),
+                }),
+            ),
+        };
+
+        let attempt = AuthAttempt {
+            timestamp_ms,
+            ssid,
+            outcome,
+            error_code,
+            error_message,
+            detect: timings.detect,
+            submit: timings.submit,
+            verify: timings.verify,
+            total: start_time.elapsed(),
+            backoff: None,
+        };
+
+        (result, attempt)
     }
 
-    pub fn start_monitoring(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.should_stop.store(false, Ordering::SeqCst);
+    async fn run_login(
+        &self,
+        ssid: Option<&str>,
+        timings: &mut LoginTimings,
+    ) -> AuthResult<AttemptOutcome> {
+        let profile = current_network_profile();
+        let driver = match &self.driver {
+            Some(driver) => Arc::clone(driver),
+            None => driver_for_network(profile.as_ref(), ssid)?,
+        };
+        let (username, password) = self.credentials_for(profile.as_ref())?;
 
-        while !self.should_stop.load(Ordering::SeqCst) {
-            match self.login() {
-                Ok(true) => {
-                    self.record_success();
-                    thread::sleep(self.success_interval);
-                }
-                Ok(false) => {
-                    self.record_2efailure();
-                    let backoff = self.calculate_backoff();
-                    eprintln!("[auth] Login fallido, reintentando en {} segundos", backoff.as_secs());
-                    thread::sleep(backoff);
-                }
-                Err(e) => {
-                    self.record_2efailure();
-                    let backoff = self.calculate_backoff();
-                    eprintln!("[auth] Error: {}. Reintentando en {} segundos", e, backoff.as_secs());
-                    thread::sleep(backoff);
-                }
-            }
+        // Si la red anuncia su API de portal cautivo (RFC 8908), su respuesta
+        // decide si hace falta iniciar sesión.
+        if captive_api::query().await.is_some_and(|status| !status.captive) {
+            return Ok(AttemptOutcome::AlreadyAuthenticated);
         }
-        Ok(())
+
+        // La detección es la primera conexión al portal: si el perfil está
+        // aprendiendo su pin, la huella queda pendiente de aprobación.
+        let presence = timed(&mut timings.detect, driver.detect())
+            .await
+            .map_err(|e| tofu::learn_from_error(profile.as_ref(), e))?;
+
+        if presence == PortalPresence::DirectAccess {
+            return Ok(AttemptOutcome::AlreadyAuthenticated);
+        }
+
+        self.observe_logout(ssid);
+
+        let advertised =
+            auto_login(driver.as_ref(), &username, password.expose(), timings).await?;
+        // El tiempo restante de la API es más fiable que el texto de la página
+        let remaining = captive_api::query()
+            .await
+            .and_then(|status| status.session_remaining())
+            .map(|lifetime| (lifetime, LifetimeSource::CaptiveApi));
+        self.start_session(
+            ssid,
+            remaining.or(advertised.map(|lifetime| (lifetime, LifetimeSource::Portal))),
+        );
+
+        Ok(AttemptOutcome::Authenticated)
     }
 
-    pub fn stop_monitoring(&self) {
-        self.should_stop.store(true, Ordering::SeqCst);
+    /// Usuario y contraseña para la red actual: los del perfil de credenciales
+    /// que indique el perfil de red o, si no indica ninguno, los de esta instancia.
+    fn credentials_for(
+        &self,
+        profile: Option<&NetworkProfile>,
+    ) -> AuthResult<(String, SecureString)> {
+        let Some(id) = profile.and_then(|p| p.credential_profile_id.as_deref()) else {
+            return Ok((self.email.clone(), self.password.expose_secret().clone()));
+        };
+
+        let creds = vault::get_profile_credentials(id).map_err(|e| {
+            AuthError::InvalidInput(format!("Credenciales del perfil de red: {}", e))
+        })?;
+        Ok((
+            portal_username(&creds.email).to_string(),
+            SecureString::from(creds.password.as_str()),
+        ))
     }
-}
 
-fn check_uabc_connection() -> Result<bool, Box<dyn std::error::Error>> {
-    let client = build_client(Duration::from_secs(3), false)?;
-
-    match client.get("https://pcw.uabc.mx/").send() {
-        Ok(response) => {
-            let status = response.status();
-
-            if status.is_success() {
-                let body = response.text()?;
-                if body.contains("Universidad Autónoma de Baja California")
-                    && !body.contains("login")
-                {
-                    println!("Pcw exist");
-                    return Ok(true);
-                }
-            }
+    fn lock_session(&self) -> std::sync::MutexGuard<'_, Option<PortalSession>> {
+        match self.session.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn start_session(&self, ssid: Option<&str>, announced: Option<(Duration, LifetimeSource)>) {
+        let lifetime = announced.or_else(|| {
+            history::learned_session_lifetime(ssid)
+                .map(|lifetime| (lifetime, LifetimeSource::Learned))
+        });
+
+        *self.lock_session() = Some(PortalSession {
+            started_at: SystemTime::now(),
+            ssid: ssid.map(str::to_string),
+            lifetime,
+        });
+    }
 
-            Ok(false)
+    /// El portal volvió a pedir login: si la sesión anterior siguió en la misma
+    /// red desde que se abrió, su duración sirve para aprender el vencimiento.
+    fn observe_logout(&self, ssid: Option<&str>) {
+        let Some(session) = self.lock_session().take() else {
+            return;
+        };
+        if session.ssid.as_deref() != ssid {
+            return;
         }
-        Err(e) => {
-            if e.to_string().contains("certificate") || e.to_string().contains("cert") {
-                return Ok(false);
-            }
 
-            Err(e.into())
+        if let Ok(lifetime) = session.started_at.elapsed() {
+            eprintln!(
+                "[auth] El portal cerró la sesión después de {} segundos",
+                lifetime.as_secs()
+            );
+            history::record_session_lifetime(ssid, lifetime);
         }
     }
-}
 
-fn auto_login(username: &str, password: &str) -> Result<bool, Box<dyn std::error::Error>> {
-    let start_time = Instant::now();
-    let res = get_local_id();
-    match res {
-        Ok(local_id) => {
-            let _id_time = start_time.elapsed();
-
-            match send_login(username, password, &local_id) {
-                Ok(status) => {
-                    if status == reqwest::StatusCode::OK {
-                        if verify_connection_after_login() {
-                            return Ok(true);
-                        } else {
-                            return Ok(false);
-                        }
-                    } else {
-                        Ok(false)
-                    }
-                }
-                Err(e) => {
-                    if e.to_string().contains("certificate") && verify_connection_after_login() {
-                        return Ok(true);
-                    }
-
-                    Err(e)
-                }
-            }
+    /// Cambiar de red rompe la continuidad de la sesión observada. En la misma
+    /// red se conserva: el portal que vuelve a pedir login es el cierre que
+    /// `observe_logout` mide.
+    fn end_session_on_network_change(&self, ssid: Option<&str>) {
+        let mut session = self.lock_session();
+        if session.as_ref().is_some_and(|s| s.ssid.as_deref() != ssid) {
+            session.take();
         }
-        Err(e) => Err(e),
     }
-}
 
-fn send_login(
-    email: &str,
-    password: &str,
-    local_id: &str,
-) -> Result<reqwest::StatusCode, Box<dyn std::error::Error>> {
-    let client = build_client(Duration::from_secs(5), false)?;
-
-    let mut form = HashMap::new();
-    form.insert("url", local_id);
-    form.insert("username", email);
-    form.insert("password", password);
-
-    match client.post("https://pcw.uabc.mx/").form(&form).send() {
-        Ok(res) => {
-            let status = res.status();
-            let body = res.text()?;
-
-            if status.is_success() {
-                if body.contains("<title>Login Successful</title>") {
-                    Ok(status)
-                } else {
-                    Ok(reqwest::StatusCode::UNAUTHORIZED)
-                }
-            } else {
-                Ok(status)
+    /// Momento previsto en que el portal cerrará la sesión actual y de dónde
+    /// salió la duración usada para calcularlo.
+    pub fn session_expiry(&self) -> Option<(SystemTime, LifetimeSource)> {
+        let guard = self.lock_session();
+        let session = guard.as_ref()?;
+        let (lifetime, source) = session.lifetime?;
+        Some((session.started_at + lifetime, source))
+    }
+
+    /// Espera después de un login exitoso: el intervalo normal, acortado para
+    /// despertar justo antes del vencimiento previsto y vigilar de cerca hasta
+    /// que el portal cierre la sesión y se pueda reautenticar al instante.
+    fn next_check_after_success(&self) -> Duration {
+        let interval = settings::current().success_interval();
+        let Some((expires_at, _)) = self.session_expiry() else {
+            return interval;
+        };
+
+        match expires_at.duration_since(SystemTime::now()) {
+            Ok(remaining) if remaining > SESSION_EXPIRY_MARGIN => {
+                interval.min(remaining - SESSION_EXPIRY_MARGIN)
             }
+            Ok(_) => SESSION_EXPIRY_POLL,
+            // La predicción ya venció; si la sesión sigue viva, la estimación era corta
+            Err(overdue) if overdue.duration() < interval => SESSION_EXPIRY_POLL,
+            Err(_) => interval,
         }
-        Err(e) => Err(e.into()),
     }
-}
 
-fn verify_connection_after_login() -> bool {
-    use crate::network_controller::client_builder::get_simple_client;
-    
-    thread::sleep(Duration::from_millis(500));
-
-    let client = get_simple_client();
-    
-    match client.get("https://www.google.com").send() {
-        Ok(response) => response.status().is_success(),
-        Err(_) => match client.get("https://www.cloudflare.com").send() {
-            Ok(response) => response.status().is_success(),
-            Err(_) => false,
-        },
+    /// Mantiene la sesión activa reaccionando a los cambios de red publicados
+    /// por `network_sync`: un portal cautivo dispara el login de inmediato y en
+    /// redes ajenas a la UABC el monitor queda en espera sin hacer peticiones.
+    /// Sin estado de red conocido se comporta como el sondeo periódico original.
+    ///
+    /// Cada instancia se monitorea una sola vez; `stop_monitoring` la despierta
+    /// de inmediato aunque esté esperando el backoff máximo.
+    pub async fn start_monitoring(&self) -> AuthResult<()> {
+        let mut signals = match self.signal_rx.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        }
+        .ok_or_else(|| AuthError::Internal("El monitoreo ya fue iniciado".to_string()))?;
+
+        subscribe_network_state(self.signal_tx.clone(), MonitorSignal::Network);
+        let mut state = current_network_state();
+
+        while !self.should_stop.load(Ordering::SeqCst) {
+            let wait = match state {
+                Some(current) if !current.is_uabc() => settings::current().monitoring_interval(),
+                // Un portal lento no retrasa la salida: el intento se abandona
+                _ => tokio::select! {
+                    wait = self.attempt_login() => wait,
+                    _ = self.stop_notify.notified() => break,
+                },
+            };
+
+            if self.should_stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let event = match wait_for_signal(&mut signals, wait).await {
+                Wake::Timeout => continue,
+                Wake::Stop => break,
+                Wake::Network(event) => event,
+            };
+
+            if state != Some(event.state) {
+                eprintln!(
+                    "[auth] Cambio de red: {} ({})",
+                    event.state.as_key(),
+                    event.ssid.as_deref().unwrap_or("<sin red>")
+                );
+                // Un portal nuevo no debe heredar la espera de la red anterior
+                self.reset_backoff();
+                self.end_session_on_network_change(event.ssid.as_deref());
+            }
+            state = Some(event.state);
+        }
+        Ok(())
     }
-}
 
-fn check_redirect(url: &str) -> Result<(bool, Option<String>), Box<dyn std::error::Error>> {
-    let client = build_client(Duration::from_secs(3), true)?;
+    /// Intenta iniciar sesión y devuelve cuánto esperar antes de volver a verificar.
+    async fn attempt_login(&self) -> Duration {
+        let (result, mut attempt) = self.timed_login().await;
+
+        let wait = match result {
+            Ok(_) => {
+                self.record_success();
+                self.next_check_after_success()
+            }
+            Err(e) => {
+                self.record_2efailure();
+                let backoff = self.calculate_backoff();
+                eprintln!(
+                    "[auth] Error ({}): {}. Reintentando en {} segundos",
+                    e.code(),
+                    e,
+                    backoff.as_secs()
+                );
+                backoff
+            }
+        };
 
-    let response = client.get(url).send()?;
+        if attempt.outcome == AttemptOutcome::Failed {
+            attempt.backoff = Some(wait);
+        }
+        history::record(&attempt);
 
-    let status = response.status();
-    let is_redirect = status.is_redirection();
+        wait
+    }
 
-    let redirect_url = if is_redirect {
-        response
-            .headers()
-            .get("Location")
-            .or(response.headers().get("location"))
-            .map(|h| h.to_str().unwrap_or_default().to_string())
-    } else {
-        None
-    };
+    /// Pide al monitor que termine. No espera: quien lanzó la tarea debe
+    /// esperarla para saber cuándo salió (un login en curso se abandona).
+    pub fn stop_monitoring(&self) {
+        self.should_stop.store(true, Ordering::SeqCst);
+        self.stop_notify.notify_one();
+        let _ = self.signal_tx.send(MonitorSignal::Stop);
+    }
+}
 
-    Ok((is_redirect, redirect_url))
+enum Wake {
+    Timeout,
+    Stop,
+    Network(NetworkStateEvent),
 }
 
-fn get_local_id() -> Result<String, Box<dyn std::error::Error>> {
-    match check_redirect("https://pcw.uabc.mx/") {
-        Ok((redirected, redirect_url)) => {
-            if redirected {
-                if let Some(url) = redirect_url {
-                    if let Some(pos) = url.find("url=") {
-                        let local_id = &url[(pos + 4)..];
-                        return Ok(local_id.to_string());
-                    }
-                    return Err(ERROR_PORTAL_NO_DISPONIBLE.into());
-                }
-                return Err(ERROR_PORTAL_NO_DISPONIBLE.into());
-            }
-            Err(ERROR_PORTAL_NO_DISPONIBLE.into())
+/// Espera hasta `timeout` por una señal. Los cambios de red acumulados mientras
+/// se hacía login se colapsan en el más reciente; `Stop` tiene prioridad.
+async fn wait_for_signal(
+    signals: &mut UnboundedReceiver<MonitorSignal>,
+    timeout: Duration,
+) -> Wake {
+    let mut latest = match tokio::time::timeout(timeout, signals.recv()).await {
+        Ok(Some(MonitorSignal::Network(event))) => event,
+        Ok(Some(MonitorSignal::Stop)) | Ok(None) => return Wake::Stop,
+        Err(_) => return Wake::Timeout,
+    };
+
+    loop {
+        match signals.try_recv() {
+            Ok(MonitorSignal::Network(event)) => latest = event,
+            Ok(MonitorSignal::Stop) | Err(TryRecvError::Disconnected) => return Wake::Stop,
+            Err(TryRecvError::Empty) => return Wake::Network(latest),
         }
-        Err(_) => Err(ERROR_NO_CONEXION.into()),
     }
-}
\ No newline at end of file
+}
+
+/// Duración de cada fase de un intento. `detect` incluye la captura del
+/// redirect del portal; `submit` es solo el POST del formulario.
+#[derive(Default)]
+struct LoginTimings {
+    detect: Option<Duration>,
+    submit: Option<Duration>,
+    verify: Option<Duration>,
+}
+
+/// El portal recibe solo la parte local del correo institucional.
+pub fn portal_username(email: &str) -> &str {
+    email.split('@').next().unwrap_or(email)
+}
+
+/// Espera `step` acumulando su duración en `slot`.
+async fn timed<T>(slot: &mut Option<Duration>, step: impl Future<Output = T>) -> T {
+    let start_time = Instant::now();
+    let result = step.await;
+    *slot = Some(slot.unwrap_or_default() + start_time.elapsed());
+    result
+}
+
+/// Devuelve la duración de sesión anunciada por el portal, si la hubo.
+async fn auto_login(
+    driver: &dyn PortalDriver,
+    username: &str,
+    password: &str,
+    timings: &mut LoginTimings,
+) -> AuthResult<Option<Duration>> {
+    let local_id = timed(&mut timings.detect, driver.extract_session()).await?;
+
+    // Un error TLS al enviar el formulario nunca se interpreta como éxito,
+    // aunque después haya internet: pudo ser un portal falso.
+    let advertised =
+        timed(&mut timings.submit, driver.submit(username, password, &local_id)).await?;
+    timed(&mut timings.verify, driver.verify()).await?;
+    Ok(advertised)
+}
@@ -0,0 +1,195 @@
+//! Daemon sin interfaz gráfica: mantiene la sesión del portal cautivo en equipos
+//! sin pantalla (laboratorios, Raspberry Pi). Usa las credenciales guardadas en
+//! el keyring por la app y registra cada evento como una línea JSON en stdout,
+//! apta para journald.
+
+#[cfg(desktop)]
+fn main() {
+    std::process::exit(daemon::run());
+}
+
+#[cfg(not(desktop))]
+fn main() {
+    eprintln!("cima-sync-daemon solo está disponible en escritorio");
+    std::process::exit(1);
+}
+
+#[cfg(desktop)]
+mod daemon {
+    use std::path::PathBuf;
+    use std::sync::{mpsc, Arc};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use tauri::async_runtime;
+
+    use cima_sync_lib::auth::{portal_username, Auth};
+    use cima_sync_lib::events::{AppEvent, EventSink};
+    use cima_sync_lib::keyring_controller::keyring::{
+        get_credentials_from_keyring, init_crypto_system,
+    };
+    use cima_sync_lib::network_controller::network_sync::start_headless_network_monitor;
+    use cima_sync_lib::network_controller::{pins, profiles, tofu};
+    use cima_sync_lib::portal_controller::policy;
+    use cima_sync_lib::{history, settings};
+
+    const EXIT_OK: i32 = 0;
+    const EXIT_USAGE: i32 = 64;
+    const EXIT_CONFIG: i32 = 78;
+
+    const USAGE: &str = "Uso: cima-sync-daemon [--config-dir <directorio>]
+
+Mantiene la sesión del portal cautivo sin abrir la ventana de la app.
+Las credenciales se leen del keyring del sistema.";
+
+    struct JournalSink;
+
+    impl EventSink for JournalSink {
+        fn emit_event(&self, event: &AppEvent) {
+            log(
+                "info",
+                event.name(),
+                serde_json::json!({ "payload": event.envelope() }),
+            );
+        }
+    }
+
+    fn log(level: &str, message: &str, fields: serde_json::Value) {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut entry = serde_json::json!({
+            "ts": ts,
+            "level": level,
+            "target": "cima-sync-daemon",
+            "msg": message,
+        });
+
+        if let (Some(entry), serde_json::Value::Object(extra)) = (entry.as_object_mut(), fields) {
+            entry.extend(extra);
+        }
+
+        println!("{}", entry);
+    }
+
+    fn parse_config_dir() -> Result<Option<PathBuf>, String> {
+        let mut args = std::env::args().skip(1);
+        let mut config_dir = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config-dir" => {
+                    let dir = args
+                        .next()
+                        .ok_or_else(|| "--config-dir requiere un directorio".to_string())?;
+                    config_dir = Some(PathBuf::from(dir));
+                }
+                "-h" | "--help" => return Err(String::new()),
+                other => return Err(format!("Argumento desconocido: {}", other)),
+            }
+        }
+
+        Ok(config_dir)
+    }
+
+    pub fn run() -> i32 {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let config_dir = match parse_config_dir() {
+            Ok(dir) => dir.or_else(settings::default_config_dir),
+            Err(message) => {
+                if message.is_empty() {
+                    println!("{}", USAGE);
+                    return EXIT_OK;
+                }
+                eprintln!("{}\n\n{}", message, USAGE);
+                return EXIT_USAGE;
+            }
+        };
+
+        match &config_dir {
+            Some(dir) => {
+                settings::init(dir);
+                history::init(dir);
+                profiles::init(dir);
+                pins::init(dir);
+                log("info", "Configuración cargada", serde_json::json!({ "config_dir": dir }));
+            }
+            None => log(
+                "warn",
+                "Sin directorio de configuración, usando valores por defecto",
+                serde_json::json!({}),
+            ),
+        }
+
+        if let Err(e) = init_crypto_system() {
+            log("error", "No se pudo inicializar el keyring", serde_json::json!({ "error": e }));
+            return EXIT_CONFIG;
+        }
+
+        let credentials = match get_credentials_from_keyring() {
+            Ok(credentials) => credentials,
+            Err(e) => {
+                log("error", "No hay credenciales guardadas", serde_json::json!({ "error": e }));
+                return EXIT_CONFIG;
+            }
+        };
+
+        let username = portal_username(&credentials.email).to_string();
+
+        let (signal_tx, signal_rx) = mpsc::channel::<()>();
+        if let Err(e) = ctrlc::set_handler(move || {
+            let _ = signal_tx.send(());
+        }) {
+            log(
+                "error",
+                "No se pudo registrar el manejador de señales",
+                serde_json::json!({ "error": e.to_string() }),
+            );
+            return EXIT_CONFIG;
+        }
+
+        start_headless_network_monitor(Arc::new(JournalSink));
+        policy::on_security_alert(|alert| {
+            log(
+                "warn",
+                "Inicio de sesión bloqueado por seguridad",
+                serde_json::to_value(alert).unwrap_or_default(),
+            )
+        });
+        // Sin ventana no hay a quién pedir aprobación: se registra la huella para
+        // copiarla a `cert_sha256` del perfil o aprobarla desde la app.
+        tofu::on_pin_pending(|pending| {
+            log(
+                "warn",
+                "Certificado nuevo pendiente de aprobación",
+                serde_json::to_value(pending).unwrap_or_default(),
+            )
+        });
+
+        let auth = Arc::new(Auth::new(&username, &credentials.password));
+        let auth_for_task = Arc::clone(&auth);
+        let worker = async_runtime::spawn(async move {
+            if let Err(e) = auth_for_task.start_monitoring().await {
+                log(
+                    "error",
+                    "El monitoreo terminó con error",
+                    serde_json::json!({ "code": e.code(), "error": e.to_string() }),
+                );
+            }
+        });
+
+        log("info", "Monitoreo iniciado", serde_json::json!({ "username": username }));
+
+        let _ = signal_rx.recv();
+        log("info", "Señal de terminación recibida, deteniendo", serde_json::json!({}));
+        auth.stop_monitoring();
+        if async_runtime::block_on(worker).is_err() {
+            log("error", "La tarea de monitoreo terminó con pánico", serde_json::json!({}));
+        }
+        log("info", "Monitoreo detenido", serde_json::json!({}));
+
+        EXIT_OK
+    }
+}
@@ -0,0 +1,428 @@
+//! Interfaz de línea de comandos: `cima-sync <subcomando>`.
+//!
+//! Reutiliza los mismos comandos que invoca el frontend (`commands.rs`) para que
+//! los scripts y cron obtengan exactamente el mismo comportamiento que la app.
+
+use std::io::{self, BufRead, Write};
+use std::sync::{mpsc, Arc};
+
+use tauri::async_runtime;
+
+use crate::commands::{
+    auto_auth, delete_credentials, get_credentials, get_network_status, init_crypto,
+    list_profiles, login, save_credentials, select_profile, stop_auth,
+};
+use crate::events::{AppEvent, EventSink};
+use crate::keyring_controller::vault;
+use crate::network_controller::{pins, profiles};
+use crate::network_controller::network_sync::{
+    refresh_network_state, start_headless_network_monitor,
+};
+use crate::{history, settings};
+
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 64;
+
+const SUBCOMMANDS: [&str; 5] = ["login", "status", "creds", "watch", "help"];
+
+const USAGE: &str = "Uso: cima-sync <subcomando> [opciones]
+
+Subcomandos:
+  login [--email <correo> | --profile <id>] [--json]
+                                      Inicia sesión en el portal una vez.
+                                      Sin opciones usa las credenciales
+                                      recordadas o el perfil activo;
+                                      con --email lee la contraseña de stdin.
+  status [--json]                     Muestra el estado de la red actual.
+  creds set [--email <correo>]        Guarda las credenciales recordadas
+                                      (contraseña por stdin).
+  creds show [--json] [--reveal]      Muestra las credenciales recordadas o,
+                                      si no hay, las del perfil activo.
+  creds delete                        Olvida las credenciales recordadas;
+                                      los perfiles se conservan.
+  creds list [--json]                 Lista los perfiles guardados.
+  creds select <id>                   Marca un perfil como activo.
+  watch [--auth] [--profile <id>] [--json]
+                                      Sigue los cambios de red hasta Ctrl+C;
+                                      con --auth mantiene la sesión activa.
+
+Sin subcomando se abre la aplicación de escritorio.";
+
+/// Ejecuta la CLI si el primer argumento es un subcomando conocido. Devuelve
+/// `None` para que `main` continúe con la aplicación de escritorio.
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let subcommand = args.first()?;
+    if !SUBCOMMANDS.contains(&subcommand.as_str()) {
+        return None;
+    }
+
+    #[cfg(target_os = "windows")]
+    attach_parent_console();
+
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    if let Some(dir) = settings::default_config_dir() {
+        settings::init(&dir);
+        history::init(&dir);
+        profiles::init(&dir);
+        pins::init(&dir);
+    }
+
+    let options = &args[1..];
+    let code = match subcommand.as_str() {
+        "login" => run_login(options),
+        "status" => run_status(options),
+        "creds" => run_creds(options),
+        "watch" => run_watch(options),
+        _ => {
+            println!("{}", USAGE);
+            EXIT_OK
+        }
+    };
+
+    Some(code)
+}
+
+fn has_flag(options: &[String], flag: &str) -> bool {
+    options.iter().any(|opt| opt == flag)
+}
+
+fn option_value(options: &[String], name: &str) -> Result<Option<String>, String> {
+    match options.iter().position(|opt| opt == name) {
+        Some(pos) => options
+            .get(pos + 1)
+            .filter(|value| !value.starts_with("--"))
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("{} requiere un valor", name)),
+        None => Ok(None),
+    }
+}
+
+fn check_options(options: &[String], allowed: &[&str], with_value: &[&str]) -> Result<(), String> {
+    let mut iter = options.iter();
+    while let Some(opt) = iter.next() {
+        if with_value.contains(&opt.as_str()) {
+            iter.next();
+        } else if !allowed.contains(&opt.as_str()) {
+            return Err(format!("Opción desconocida: {}", opt));
+        }
+    }
+    Ok(())
+}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("{}\n\n{}", message, USAGE);
+    EXIT_USAGE
+}
+
+fn read_line(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    let _ = io::stderr().flush();
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("Error leyendo stdin: {}", e))?;
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn ensure_crypto() -> Result<(), String> {
+    init_crypto().map(|_| ())
+}
+
+fn run_login(options: &[String]) -> i32 {
+    if let Err(e) = check_options(options, &["--json"], &["--email", "--profile"]) {
+        return usage_error(&e);
+    }
+    let json = has_flag(options, "--json");
+
+    let (email, profile) = match (
+        option_value(options, "--email"),
+        option_value(options, "--profile"),
+    ) {
+        (Ok(Some(_)), Ok(Some(_))) => {
+            return usage_error("--email y --profile no se pueden combinar")
+        }
+        (Ok(email), Ok(profile)) => (email, profile),
+        (Err(e), _) | (_, Err(e)) => return usage_error(&e),
+    };
+
+    let credentials = match (email, profile) {
+        (Some(email), _) => read_line("Contraseña: ").map(|password| (email, password)),
+        (None, Some(id)) => ensure_crypto()
+            .and_then(|_| vault::get_profile_credentials(&id))
+            .map(|creds| (creds.email.clone(), creds.password.clone())),
+        (None, None) => ensure_crypto()
+            .and_then(|_| get_credentials())
+            .map(|creds| (creds.email.clone(), creds.password.clone())),
+    };
+
+    let (email, password) = match credentials {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    refresh_network_state();
+
+    match async_runtime::block_on(login(&email, &password)) {
+        Ok(message) => {
+            if json {
+                println!("{}", serde_json::json!({ "ok": true, "message": message }));
+            } else {
+                println!("{}", message);
+            }
+            EXIT_OK
+        }
+        Err(e) => {
+            if json {
+                println!("{}", serde_json::json!({ "ok": false, "error": e }));
+            } else {
+                eprintln!("[{}] {}", e.code(), e);
+                if let Some(details) = e.details() {
+                    eprintln!("  {}", details);
+                }
+            }
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn run_status(options: &[String]) -> i32 {
+    if let Err(e) = check_options(options, &["--json"], &[]) {
+        return usage_error(&e);
+    }
+
+    refresh_network_state();
+    let mut status =
+        serde_json::to_value(async_runtime::block_on(get_network_status())).unwrap_or_default();
+    let credentials_saved = ensure_crypto().and_then(|_| get_credentials()).is_ok();
+    status["credentials_saved"] = serde_json::Value::Bool(credentials_saved);
+
+    if has_flag(options, "--json") {
+        println!("{}", status);
+    } else {
+        print_status_line(&status);
+    }
+
+    EXIT_OK
+}
+
+fn print_status_line(status: &serde_json::Value) {
+    let text = status["status_text"].as_str().unwrap_or("Estado desconocido");
+    let ssid = status["ssid"].as_str().unwrap_or("<sin red>");
+    let state = status["network_state"].as_str().unwrap_or("unknown");
+    let line = format!("{} | ssid={} | estado={}", text, ssid, state);
+    match status["credentials_saved"].as_bool() {
+        Some(saved) => println!("{} | credenciales={}", line, if saved { "sí" } else { "no" }),
+        None => println!("{}", line),
+    }
+}
+
+fn run_creds(options: &[String]) -> i32 {
+    let Some(action) = options.first() else {
+        return usage_error("creds requiere una acción: set, show o delete");
+    };
+    let options = &options[1..];
+
+    if let Err(e) = ensure_crypto() {
+        eprintln!("{}", e);
+        return EXIT_FAILURE;
+    }
+
+    match action.as_str() {
+        "set" => {
+            if let Err(e) = check_options(options, &[], &["--email"]) {
+                return usage_error(&e);
+            }
+            let email = match option_value(options, "--email") {
+                Ok(Some(email)) => Ok(email),
+                Ok(None) => read_line("Correo: "),
+                Err(e) => return usage_error(&e),
+            };
+            let result = email.and_then(|email| {
+                let password = read_line("Contraseña: ")?;
+                save_credentials(&email, &password)
+            });
+            match result {
+                Ok(()) => {
+                    println!("Credenciales guardadas");
+                    EXIT_OK
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        "show" => {
+            if let Err(e) = check_options(options, &["--json", "--reveal"], &[]) {
+                return usage_error(&e);
+            }
+            match get_credentials() {
+                Ok(creds) => {
+                    let password = if has_flag(options, "--reveal") {
+                        creds.password.clone()
+                    } else {
+                        "*".repeat(8)
+                    };
+                    if has_flag(options, "--json") {
+                        println!(
+                            "{}",
+                            serde_json::json!({ "email": creds.email, "password": password })
+                        );
+                    } else {
+                        println!("email={}\npassword={}", creds.email, password);
+                    }
+                    EXIT_OK
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        "delete" => {
+            if let Err(e) = check_options(options, &[], &[]) {
+                return usage_error(&e);
+            }
+            match delete_credentials() {
+                Ok(()) => {
+                    println!("Credenciales eliminadas");
+                    EXIT_OK
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        "list" => {
+            if let Err(e) = check_options(options, &["--json"], &[]) {
+                return usage_error(&e);
+            }
+            match list_profiles() {
+                Ok(profiles) => {
+                    if has_flag(options, "--json") {
+                        println!("{}", serde_json::json!(profiles));
+                    } else if profiles.is_empty() {
+                        println!("No hay perfiles guardados");
+                    } else {
+                        for profile in profiles {
+                            println!(
+                                "{} {}  {}  {}",
+                                if profile.active { "*" } else { " " },
+                                profile.id,
+                                profile.label,
+                                profile.email
+                            );
+                        }
+                    }
+                    EXIT_OK
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        "select" => {
+            let [id] = options else {
+                return usage_error("creds select requiere el id del perfil");
+            };
+            match select_profile(id) {
+                Ok(()) => {
+                    println!("Perfil {} activo", id);
+                    EXIT_OK
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        other => usage_error(&format!("Acción desconocida para creds: {}", other)),
+    }
+}
+
+struct PrintSink {
+    json: bool,
+}
+
+impl EventSink for PrintSink {
+    fn emit_event(&self, event: &AppEvent) {
+        if !matches!(event, AppEvent::NetworkStatus(_)) {
+            return;
+        }
+        let payload = serde_json::to_value(event.envelope()).unwrap_or_default();
+        if self.json {
+            println!("{}", payload);
+        } else {
+            print_status_line(&payload);
+        }
+    }
+}
+
+fn run_watch(options: &[String]) -> i32 {
+    if let Err(e) = check_options(options, &["--auth", "--json"], &["--profile"]) {
+        return usage_error(&e);
+    }
+
+    let (signal_tx, signal_rx) = mpsc::channel::<()>();
+    if let Err(e) = ctrlc::set_handler(move || {
+        let _ = signal_tx.send(());
+    }) {
+        eprintln!("No se pudo registrar el manejador de Ctrl+C: {}", e);
+        return EXIT_FAILURE;
+    }
+
+    let profile = match option_value(options, "--profile") {
+        Ok(profile) => profile,
+        Err(e) => return usage_error(&e),
+    };
+
+    start_headless_network_monitor(Arc::new(PrintSink {
+        json: has_flag(options, "--json"),
+    }));
+
+    if has_flag(options, "--auth") {
+        let started = ensure_crypto().and_then(|_| {
+            auto_auth(None, None, profile).map_err(|e| e.to_string())
+        });
+        match started {
+            Ok(message) => eprintln!("{}", message),
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    }
+
+    let _ = signal_rx.recv();
+    if has_flag(options, "--auth") {
+        eprintln!("{}", async_runtime::block_on(stop_auth()));
+    }
+
+    EXIT_OK
+}
+
+/// En release la app usa el subsistema "windows" y no tiene consola propia;
+/// se adjunta a la de la terminal que la invocó para que la salida sea visible.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
@@ -3,17 +3,46 @@ use crate::keyring_controller::keyring::{
     encrypt_text_with_session, get_credentials_from_keyring, init_crypto_system,
     save_credentials_to_keyring, UserCredentials,
 };
+use crate::keyring_controller::vault::{self, ProfileSummary};
 
-use crate::auth::Auth;
+use crate::auth::{portal_username, Auth};
+use crate::error::AuthError;
+use crate::history::{self, AuthHistoryPage};
 use crate::network_controller::network_sync::{get_current_network_status, update_android_ssid};
+use crate::network_controller::profiles::{self, NetworkProfile};
+use crate::network_controller::status::NetworkStatus;
+use crate::network_controller::tofu::{self, PendingPin};
+use crate::portal_controller::policy::SecurityAlert;
+use crate::settings::{self, Settings};
 
 use regex::Regex;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::thread;
+use std::time::UNIX_EPOCH;
+use tauri::async_runtime::{self, JoinHandle};
+
+/// Monitor en segundo plano junto con la tarea que lo ejecuta.
+struct MonitorHandle {
+    auth: Arc<Auth>,
+    /// `None` mientras `stop_auth` espera a que la tarea termine: el monitor
+    /// sigue ocupando `CURRENT_AUTH` para que no se inicie otro en paralelo.
+    worker: Option<JoinHandle<()>>,
+}
+
+impl MonitorHandle {
+    fn is_running(&self) -> bool {
+        self.worker
+            .as_ref()
+            .is_some_and(|worker| !worker.inner().is_finished())
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.worker.is_none()
+    }
+}
 
 lazy_static::lazy_static! {
-    static ref CURRENT_AUTH: Arc<Mutex<Option<Arc<Auth>>>> = Arc::new(Mutex::new(None));
+    static ref CURRENT_AUTH: Arc<Mutex<Option<MonitorHandle>>> = Arc::new(Mutex::new(None));
     static ref EMAIL_REGEX: Regex = Regex::new(
         r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"
     ).expect("Regex de email inválido");
@@ -22,6 +51,8 @@ lazy_static::lazy_static! {
 const MAX_EMAIL_LENGTH: usize = 254;
 const MAX_PASSWORD_LENGTH: usize = 128;
 const MIN_PASSWORD_LENGTH: usize = 1;
+const DEFAULT_HISTORY_PAGE_SIZE: u32 = 50;
+const DEFAULT_SECURITY_ALERTS_LIMIT: u32 = 20;
 #[derive(Debug)]
 pub enum ValidationError {
     EmptyEmail,
@@ -94,75 +125,127 @@ fn sanitize_text(text: &str) -> Result<String, ValidationError> {
     Ok(text.to_string())
 }
 
+/// Detiene el monitoreo y regresa hasta que la tarea terminó (puede esperar a
+/// que venza una petición en curso).
 #[tauri::command]
-pub fn stop_auth() -> String {
+pub async fn stop_auth() -> String {
+    let (auth, worker) = match CURRENT_AUTH.lock() {
+        Ok(mut guard) => match guard.as_mut() {
+            Some(handle) => match handle.worker.take() {
+                Some(worker) => (Arc::clone(&handle.auth), worker),
+                None => return "El proceso de monitoreo ya se está deteniendo".to_string(),
+            },
+            None => return "No hay proceso de monitoreo activo".to_string(),
+        },
+        Err(_) => return "Error al acceder al estado de autenticación".to_string(),
+    };
+
+    auth.stop_monitoring();
+    let result = worker.await;
+
     match CURRENT_AUTH.lock() {
-        Ok(mut guard) => {
-            if let Some(auth) = guard.as_ref() {
-                auth.stop_monitoring();
-                *guard = None;
-                "Proceso de monitoreo detenido".to_string()
-            } else {
-                "No hay proceso de monitoreo activo".to_string()
-            }
-        }
-        Err(_) => "Error al acceder al estado de autenticación".to_string(),
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    }
+
+    match result {
+        Ok(()) => "Proceso de monitoreo detenido".to_string(),
+        Err(_) => "El proceso de monitoreo terminó con un error".to_string(),
     }
 }
 
 #[tauri::command]
 pub fn get_auth_status() -> serde_json::Value {
-    let is_active = match CURRENT_AUTH.lock() {
-        Ok(guard) => guard.is_some(),
-        Err(_) => false,
+    let (is_active, expiry) = match CURRENT_AUTH.lock() {
+        Ok(guard) => match guard.as_ref() {
+            Some(handle) if handle.is_running() => (true, handle.auth.session_expiry()),
+            _ => (false, None),
+        },
+        Err(_) => (false, None),
     };
 
+    let session_expires_at = expiry.and_then(|(expires_at, _)| {
+        expires_at
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|since_epoch| since_epoch.as_millis() as u64)
+    });
+
     serde_json::json!({
         "is_active": is_active,
+        "session_expires_at": session_expires_at,
+        "session_lifetime_source": expiry.map(|(_, source)| source.as_key()),
     })
 }
 
+/// Inicia el monitoreo con credenciales explícitas o con las de un perfil del
+/// vault (`profile_id`). Sin ninguno de los dos se usan las credenciales
+/// recordadas o, si no hay, el perfil activo.
 #[tauri::command]
-pub fn auto_auth(email: &str, password: &str) -> Result<String, String> {
-    validate_credentials(email, password)?;
-    
-    let username = email.split('@').next().unwrap_or(email).to_string();
+pub fn auto_auth(
+    email: Option<String>,
+    password: Option<String>,
+    profile_id: Option<String>,
+) -> Result<String, AuthError> {
+    let credentials = match (profile_id, email, password) {
+        (Some(id), _, _) => vault::get_profile_credentials(&id).map_err(AuthError::InvalidInput)?,
+        (None, Some(email), Some(password)) => UserCredentials { email, password },
+        (None, None, None) => get_credentials_from_keyring().map_err(AuthError::InvalidInput)?,
+        _ => {
+            return Err(AuthError::InvalidInput(
+                "Se requieren email y contraseña o un perfil".to_string(),
+            ))
+        }
+    };
 
-    // Crear solo una instancia de Auth envuelta en Arc
-    let auth = Arc::new(Auth::new(&username, password));
-    let auth_for_thread = Arc::clone(&auth);
+    start_monitoring(&credentials.email, &credentials.password)
+}
+
+fn start_monitoring(email: &str, password: &str) -> Result<String, AuthError> {
+    validate_credentials(email, password).map_err(AuthError::InvalidInput)?;
     
-    match CURRENT_AUTH.lock() {
-        Ok(mut guard) => {
-            if guard.is_some() {
-                return Ok("El monitoreo de Cima Sync ya está activo".to_string());
-            }
-            *guard = Some(auth);
-        }
-        Err(_) => return Err("Error al inicializar autenticación".to_string()),
+    let username = portal_username(email);
+
+    let mut guard = CURRENT_AUTH
+        .lock()
+        .map_err(|_| AuthError::Internal("Error al inicializar autenticación".to_string()))?;
+
+    if guard.as_ref().is_some_and(MonitorHandle::is_running) {
+        return Ok("El monitoreo de Cima Sync ya está activo".to_string());
     }
+    if guard.as_ref().is_some_and(MonitorHandle::is_stopping) {
+        return Err(AuthError::Internal(
+            "El monitoreo anterior aún se está deteniendo, intenta de nuevo".to_string(),
+        ));
+    }
+
+    // Crear solo una instancia de Auth envuelta en Arc
+    let auth = Arc::new(Auth::new(username, password));
+    let auth_for_task = Arc::clone(&auth);
 
-    thread::spawn(move || {
-        if let Err(e) = auth_for_thread.start_monitoring() {
+    let worker = async_runtime::spawn(async move {
+        if let Err(e) = auth_for_task.start_monitoring().await {
             eprintln!("[auth] Error en monitoreo: {}", e);
         }
     });
 
+    *guard = Some(MonitorHandle {
+        auth,
+        worker: Some(worker),
+    });
+
     Ok(format!("Proceso de autenticación iniciado para: {}", username))
 }
 
 #[tauri::command]
-pub fn login(email: &str, password: &str) -> Result<String, String> {
-    validate_credentials(email, password)?;
+pub async fn login(email: &str, password: &str) -> Result<String, AuthError> {
+    validate_credentials(email, password).map_err(AuthError::InvalidInput)?;
     
-    let username = email.split('@').next().unwrap_or(email).to_string();
+    let username = portal_username(email);
 
-    let auth = Auth::new(&username, password);
-    match auth.login() {
-        Ok(true) => Ok(format!("Login exitoso para: {}", username)),
-        Ok(false) => Err("Credenciales inválidas".to_string()),
-        Err(e) => Err(format!("{}", e)),
-    }
+    let auth = Auth::new(username, password);
+    auth.login().await?;
+    Ok(format!("Login exitoso para: {}", username))
 }
 
 #[tauri::command]
@@ -189,11 +272,81 @@ pub fn delete_credentials() -> Result<(), String> {
     clear_credentials_from_keyring()
 }
 
+#[tauri::command]
+pub fn add_profile(
+    label: &str,
+    email: &str,
+    password: &str,
+    activate: Option<bool>,
+) -> Result<ProfileSummary, String> {
+    validate_credentials(email, password)?;
+    vault::add_profile(label, email, password, activate.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn list_profiles() -> Result<Vec<ProfileSummary>, String> {
+    vault::list_profiles()
+}
+
+#[tauri::command]
+pub fn select_profile(id: &str) -> Result<(), String> {
+    vault::select_profile(id)
+}
+
+#[tauri::command]
+pub fn delete_profile(id: &str) -> Result<(), String> {
+    vault::delete_profile(id)
+}
+
+#[tauri::command]
+pub fn get_network_profiles() -> Vec<NetworkProfile> {
+    profiles::list()
+}
+
+/// Crea o reemplaza (por `id`) un perfil de red. Devuelve la lista actualizada.
+#[tauri::command]
+pub fn save_network_profile(profile: NetworkProfile) -> Result<Vec<NetworkProfile>, String> {
+    if let Some(id) = &profile.credential_profile_id {
+        vault::get_profile_credentials(id)?;
+    }
+    profiles::save(profile)
+}
+
+#[tauri::command]
+pub fn delete_network_profile(id: &str) -> Result<Vec<NetworkProfile>, String> {
+    profiles::delete(id)
+}
+
+/// Certificados vistos por primera vez que esperan la aprobación del usuario.
+#[tauri::command]
+pub fn get_pending_pins() -> Vec<PendingPin> {
+    tofu::pending()
+}
+
+/// Aprueba la huella mostrada al usuario y la guarda como pin del perfil.
+#[tauri::command]
+pub fn approve_network_pin(profile_id: &str, cert_sha256: &str) -> Result<NetworkProfile, String> {
+    tofu::approve(profile_id, cert_sha256)
+}
+
+#[tauri::command]
+pub fn reject_network_pin(profile_id: &str) -> Result<(), String> {
+    tofu::reject(profile_id)
+}
+
 #[tauri::command]
 pub fn clear_crypto() -> Result<(), String> {
     clear_stored_key()
 }
 
+/// Borra todos los perfiles del vault, las credenciales recordadas y la clave
+/// de sesión.
+#[tauri::command]
+pub fn clear_vault() -> Result<(), String> {
+    vault::clear_vault()?;
+    clear_stored_key()
+}
+
 #[tauri::command]
 pub fn encrypt_credentials(plaintext: &str) -> Result<String, String> {
     let sanitized = sanitize_text(plaintext)
@@ -218,11 +371,42 @@ pub fn set_crypto_key(_key_b64: &str) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn get_network_status() -> serde_json::Value {
-    get_current_network_status()
+pub async fn get_network_status() -> NetworkStatus {
+    get_current_network_status().await
 }
 
 #[tauri::command]
 pub fn set_mobile_wifi_info(ssid: Option<String>) {
     update_android_ssid(ssid);
 }
+
+/// Historial paginado de intentos de login (página 0 = más recientes) con
+/// estadísticas del mismo periodo.
+#[tauri::command]
+pub fn get_auth_history(
+    page: Option<u32>,
+    page_size: Option<u32>,
+    since_ms: Option<i64>,
+) -> Result<AuthHistoryPage, String> {
+    history::page(
+        page.unwrap_or(0),
+        page_size.unwrap_or(DEFAULT_HISTORY_PAGE_SIZE),
+        since_ms,
+    )
+}
+
+/// Alertas de seguridad recientes (por qué se bloqueó el envío de credenciales).
+#[tauri::command]
+pub fn get_security_alerts(limit: Option<u32>) -> Result<Vec<SecurityAlert>, String> {
+    history::security_alerts(limit.unwrap_or(DEFAULT_SECURITY_ALERTS_LIMIT))
+}
+
+#[tauri::command]
+pub fn get_settings() -> Settings {
+    settings::current()
+}
+
+#[tauri::command]
+pub fn update_settings(settings: Settings) -> Result<Settings, String> {
+    settings::update(settings)
+}
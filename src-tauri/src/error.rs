@@ -0,0 +1,226 @@
+use serde::{ser::SerializeStruct, ser::Serializer, Serialize};
+
+use crate::network_controller::client_builder::PinMismatch;
+use crate::portal_controller::policy::SecurityAlertKind;
+
+pub type AuthResult<T> = std::result::Result<T, AuthError>;
+
+/// Errores del flujo de autenticación contra el portal cautivo.
+///
+/// Se serializa hacia el frontend como `{ code, message, details }` para que
+/// la interfaz (y los scripts) puedan ramificar por `code` sin depender del texto.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("No se pudo resolver el dominio del portal.")]
+    Dns(#[source] reqwest::Error),
+    #[error("El certificado del portal no coincide con el pin configurado.")]
+    TlsPinMismatch(#[source] reqwest::Error),
+    #[error("El certificado del portal no es válido.")]
+    Tls(#[source] reqwest::Error),
+    #[error("No estas en el wifi UABC o ya estas conectado.")]
+    PortalNotPresent { reason: String },
+    #[error("Credenciales invalidas.")]
+    InvalidCredentials,
+    #[error("El portal respondió con un error HTTP.")]
+    PortalHttp {
+        status: Option<reqwest::StatusCode>,
+        #[source]
+        source: Option<reqwest::Error>,
+    },
+    #[error("Se inició sesión pero no se detecta conexión a internet.")]
+    VerificationFailed {
+        #[source]
+        source: Option<reqwest::Error>,
+    },
+    #[error("Tiempo de espera agotado.")]
+    Timeout(#[source] reqwest::Error),
+    #[error("No se detecta conexión a internet.")]
+    Network(#[source] reqwest::Error),
+    #[error("Se bloqueó el envío de credenciales: el portal no es confiable.")]
+    UnsafePortal {
+        kind: SecurityAlertKind,
+        observed: String,
+    },
+    #[error("El certificado del portal es nuevo y necesita tu aprobación.")]
+    PinApprovalRequired {
+        profile_id: String,
+        cert_sha256: String,
+    },
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("Ocurrió un error al conectarse a la red UABC.")]
+    Internal(String),
+}
+
+impl AuthError {
+    /// Código estable usado por el frontend para distinguir el tipo de error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::Dns(_) => "dns_failure",
+            AuthError::TlsPinMismatch(_) => "tls_pin_mismatch",
+            AuthError::Tls(_) => "tls_error",
+            AuthError::PortalNotPresent { .. } => "portal_not_present",
+            AuthError::InvalidCredentials => "invalid_credentials",
+            AuthError::PortalHttp { .. } => "portal_http_error",
+            AuthError::VerificationFailed { .. } => "verification_failed",
+            AuthError::Timeout(_) => "timeout",
+            AuthError::Network(_) => "network_unreachable",
+            AuthError::UnsafePortal { .. } => "unsafe_portal",
+            AuthError::PinApprovalRequired { .. } => "pin_approval_required",
+            AuthError::InvalidInput(_) => "invalid_input",
+            AuthError::Internal(_) => "internal",
+        }
+    }
+
+    /// Indica si el error proviene de la validación TLS del portal.
+    pub fn is_tls(&self) -> bool {
+        matches!(self, AuthError::TlsPinMismatch(_) | AuthError::Tls(_))
+    }
+
+    /// Pines probados y huella observada cuando el certificado no coincidió.
+    pub fn pin_mismatch(&self) -> Option<&PinMismatch> {
+        match self {
+            AuthError::TlsPinMismatch(err) => find_pin_mismatch(err),
+            _ => None,
+        }
+    }
+
+    /// Información técnica adicional: la cadena de errores de origen y,
+    /// cuando aplica, el estado HTTP o el motivo.
+    pub fn details(&self) -> Option<String> {
+        let mut parts: Vec<String> = Vec::new();
+
+        match self {
+            AuthError::PortalNotPresent { reason } => parts.push(reason.clone()),
+            AuthError::TlsPinMismatch(_) => {
+                if let Some(mismatch) = self.pin_mismatch() {
+                    parts.push(mismatch.to_string())
+                }
+            }
+            AuthError::UnsafePortal { kind, observed } => {
+                parts.push(format!("{}: {}", kind.description(), observed))
+            }
+            AuthError::PinApprovalRequired {
+                profile_id,
+                cert_sha256,
+            } => parts.push(format!(
+                "perfil {}: cert-sha256:{}",
+                profile_id, cert_sha256
+            )),
+            AuthError::PortalHttp {
+                status: Some(status),
+                ..
+            } => parts.push(format!("HTTP {}", status)),
+            AuthError::Internal(detail) => parts.push(detail.clone()),
+            _ => {}
+        }
+
+        let mut current = std::error::Error::source(self);
+        while let Some(err) = current {
+            parts.push(err.to_string());
+            current = match err.downcast_ref::<std::io::Error>() {
+                Some(io) => io.get_ref().map(|inner| inner as &(dyn std::error::Error + 'static)),
+                None => err.source(),
+            };
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(": "))
+        }
+    }
+}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(err: reqwest::Error) -> Self {
+        if let Some(tls_err) = find_in_chain::<rustls::Error>(&err) {
+            return if is_pin_mismatch(tls_err) {
+                AuthError::TlsPinMismatch(err)
+            } else {
+                AuthError::Tls(err)
+            };
+        }
+
+        if err.is_timeout() {
+            return AuthError::Timeout(err);
+        }
+
+        if err.is_connect() {
+            // hyper-util no expone un tipo público para los fallos del resolvedor,
+            // solo el prefijo "dns error" en su ConnectError.
+            if chain_contains(&err, "dns error") {
+                return AuthError::Dns(err);
+            }
+            return AuthError::Network(err);
+        }
+
+        AuthError::PortalHttp {
+            status: err.status(),
+            source: Some(err),
+        }
+    }
+}
+
+impl Serialize for AuthError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("AuthError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+fn is_pin_mismatch(err: &rustls::Error) -> bool {
+    pin_mismatch(err).is_some()
+}
+
+fn pin_mismatch(err: &rustls::Error) -> Option<&PinMismatch> {
+    match err {
+        rustls::Error::InvalidCertificate(rustls::CertificateError::Other(other)) => {
+            other.0.downcast_ref::<PinMismatch>()
+        }
+        _ => None,
+    }
+}
+
+/// Pines probados y huella observada, para el detalle del error.
+fn find_pin_mismatch(err: &reqwest::Error) -> Option<&PinMismatch> {
+    find_in_chain::<rustls::Error>(err).and_then(pin_mismatch)
+}
+
+/// Recorre la cadena de errores buscando un tipo concreto. `io::Error` oculta
+/// el error que envuelve en `source()`, así que se inspecciona con `get_ref()`.
+fn find_in_chain<'a, T: std::error::Error + 'static>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a T> {
+    let mut current: Option<&'a (dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = current {
+        if let Some(found) = e.downcast_ref::<T>() {
+            return Some(found);
+        }
+        if let Some(io) = e.downcast_ref::<std::io::Error>()
+            && let Some(inner) = io.get_ref()
+            && let Some(found) = find_in_chain::<T>(inner)
+        {
+            return Some(found);
+        }
+        current = e.source();
+    }
+    None
+}
+
+fn chain_contains(err: &(dyn std::error::Error + 'static), needle: &str) -> bool {
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = current {
+        if e.to_string().contains(needle) {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
@@ -0,0 +1,67 @@
+//! Eventos que el backend entrega a la ventana (`app.emit`), a la CLI y al
+//! daemon. Todos salen envueltos en `EventEnvelope`, con la versión del
+//! esquema y el nombre del evento en `type`.
+//!
+//! Los tipos de TypeScript se generan con ts-rs en `src/bindings` al correr
+//! `cargo test`; el frontend los importa desde ahí.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::network_controller::status::NetworkStatus;
+use crate::network_controller::tofu::PendingPin;
+use crate::portal_controller::policy::SecurityAlert;
+
+/// Se incrementa con cada cambio incompatible en la forma de los eventos.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AppEvent {
+    NetworkStatus(Box<NetworkStatus>),
+    UabcDetected(UabcDetected),
+    SecurityAlert(SecurityAlert),
+    PinApprovalRequired(PendingPin),
+}
+
+/// Evento `uabc-detected`: la red actual coincide con un perfil.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+pub struct UabcDetected {
+    pub ssid: Option<String>,
+    pub network_profile_id: String,
+}
+
+impl AppEvent {
+    /// Nombre con el que se emite en Tauri; coincide con `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::NetworkStatus(_) => "network-status",
+            AppEvent::UabcDetected(_) => "uabc-detected",
+            AppEvent::SecurityAlert(_) => "security-alert",
+            AppEvent::PinApprovalRequired(_) => "pin-approval-required",
+        }
+    }
+
+    pub fn envelope(&self) -> EventEnvelope<'_> {
+        EventEnvelope {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event: self,
+        }
+    }
+}
+
+/// Payload de cada evento: los campos del evento quedan al nivel superior
+/// junto a `schema_version` y `type`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[ts(export, rename = "AppEventPayload")]
+pub struct EventEnvelope<'a> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: &'a AppEvent,
+}
+
+/// Destino de los eventos: la ventana de Tauri o un proceso sin interfaz
+/// (daemon, CLI) que solo los registra.
+pub trait EventSink: Send + Sync + 'static {
+    fn emit_event(&self, event: &AppEvent);
+}
@@ -0,0 +1,391 @@
+use lazy_static::lazy_static;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::json_store;
+use crate::portal_controller::policy::{SecurityAlert, SecurityAlertKind};
+
+/// Mismo archivo que abre `tauri-plugin-sql` desde el frontend (`sqlite:cima-config.db`).
+const DATABASE_FILE: &str = "cima-config.db";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_PAGE_SIZE: u32 = 200;
+/// Observaciones recientes usadas para estimar la duración de sesión.
+const SESSION_LIFETIME_SAMPLES: u32 = 10;
+const MAX_SECURITY_ALERTS: u32 = 100;
+/// Intentos que se conservan; el monitor registra una fila en cada
+/// verificación, así que los más viejos se descartan.
+pub const MAX_HISTORY_ROWS: u32 = 5000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS auth_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        ssid TEXT,
+        outcome TEXT NOT NULL,
+        error_code TEXT,
+        error_message TEXT,
+        detect_ms INTEGER,
+        submit_ms INTEGER,
+        verify_ms INTEGER,
+        total_ms INTEGER NOT NULL,
+        backoff_secs INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_auth_history_timestamp ON auth_history (timestamp_ms);
+    CREATE TABLE IF NOT EXISTS session_lifetimes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        ssid TEXT,
+        lifetime_secs INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS security_alerts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        ssid TEXT,
+        details TEXT
+    );
+";
+
+lazy_static! {
+    static ref DATABASE: Mutex<Option<Connection>> = Mutex::new(None);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// Se envió el formulario y la conexión quedó verificada.
+    Authenticated,
+    /// El portal no interceptó la petición: la sesión seguía activa.
+    AlreadyAuthenticated,
+    Failed,
+}
+
+impl AttemptOutcome {
+    fn as_key(self) -> &'static str {
+        match self {
+            AttemptOutcome::Authenticated => "authenticated",
+            AttemptOutcome::AlreadyAuthenticated => "already_authenticated",
+            AttemptOutcome::Failed => "failed",
+        }
+    }
+}
+
+/// Un intento de login con la duración de cada fase. Las fases que no
+/// llegaron a ejecutarse quedan en `None`.
+#[derive(Clone, Debug)]
+pub struct AuthAttempt {
+    pub timestamp_ms: i64,
+    pub ssid: Option<String>,
+    pub outcome: AttemptOutcome,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub detect: Option<Duration>,
+    pub submit: Option<Duration>,
+    pub verify: Option<Duration>,
+    pub total: Duration,
+    pub backoff: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthHistoryEntry {
+    pub id: i64,
+    pub timestamp_ms: i64,
+    pub ssid: Option<String>,
+    pub outcome: String,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub detect_ms: Option<u64>,
+    pub submit_ms: Option<u64>,
+    pub verify_ms: Option<u64>,
+    pub total_ms: u64,
+    pub backoff_secs: Option<u64>,
+}
+
+/// Cuenta todos los intentos guardados, también los que fallaron antes de
+/// enviar el formulario (pin distinto, redirección insegura, timeout).
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthStats {
+    pub attempts: u64,
+    pub successes: u64,
+    /// Fracción de intentos exitosos (0.0 a 1.0); `None` si no hay intentos.
+    pub success_rate: Option<f64>,
+    /// Promedio de `total_ms` de los logins exitosos.
+    pub mean_time_to_auth_ms: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthHistoryPage {
+    pub entries: Vec<AuthHistoryEntry>,
+    pub page: u32,
+    pub page_size: u32,
+    pub total: u64,
+    pub stats: AuthStats,
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Abre (o crea) la base de datos en `config_dir`. Si falla, el historial
+/// queda deshabilitado y el resto de la app sigue funcionando.
+pub fn init(config_dir: &Path) {
+    match open(config_dir) {
+        Ok(conn) => match DATABASE.lock() {
+            Ok(mut guard) => *guard = Some(conn),
+            Err(poisoned) => *poisoned.into_inner() = Some(conn),
+        },
+        Err(e) => eprintln!("[history] Historial deshabilitado: {}", e),
+    }
+}
+
+fn open(config_dir: &Path) -> Result<Connection, String> {
+    json_store::create_config_dir(config_dir)?;
+
+    let conn = Connection::open(config_dir.join(DATABASE_FILE))
+        .map_err(|e| format!("Error abriendo base de datos: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Error configurando base de datos: {}", e))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Error creando tabla de historial: {}", e))?;
+    prune(&conn).map_err(|e| format!("Error depurando historial: {}", e))?;
+
+    Ok(conn)
+}
+
+/// Deja solo los `MAX_HISTORY_ROWS` intentos más recientes.
+fn prune(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute(
+        "DELETE FROM auth_history WHERE id <= (
+            SELECT id FROM auth_history ORDER BY id DESC LIMIT 1 OFFSET ?1
+        )",
+        params![MAX_HISTORY_ROWS],
+    )
+}
+
+fn with_connection<T>(f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+    let guard = match DATABASE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let conn = guard
+        .as_ref()
+        .ok_or_else(|| "El historial de autenticación no está disponible".to_string())?;
+
+    f(conn).map_err(|e| format!("Error consultando historial: {}", e))
+}
+
+/// Guarda un intento. Sin `init` (pruebas) no hace nada, y un fallo de
+/// escritura solo se registra en el log para no interrumpir el monitoreo.
+/// Las verificaciones con la sesión ya activa no son inicios de sesión y no
+/// se guardan: cada latido del monitor desplazaría el historial real.
+pub fn record(attempt: &AuthAttempt) {
+    if attempt.outcome == AttemptOutcome::AlreadyAuthenticated {
+        return;
+    }
+
+    let guard = match DATABASE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let Some(conn) = guard.as_ref() else {
+        return;
+    };
+
+    let result = conn.execute(
+        "INSERT INTO auth_history (
+            timestamp_ms, ssid, outcome, error_code, error_message,
+            detect_ms, submit_ms, verify_ms, total_ms, backoff_secs
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            attempt.timestamp_ms,
+            attempt.ssid,
+            attempt.outcome.as_key(),
+            attempt.error_code,
+            attempt.error_message,
+            attempt.detect.map(as_millis),
+            attempt.submit.map(as_millis),
+            attempt.verify.map(as_millis),
+            as_millis(attempt.total),
+            attempt.backoff.map(|b| b.as_secs() as i64),
+        ],
+    );
+
+    if let Err(e) = result.and_then(|_| prune(conn)) {
+        eprintln!("[history] No se pudo guardar el intento: {}", e);
+    }
+}
+
+/// Página de intentos (la más reciente primero) y estadísticas agregadas.
+/// `since_ms` limita tanto las entradas como las estadísticas.
+pub fn page(page: u32, page_size: u32, since_ms: Option<i64>) -> Result<AuthHistoryPage, String> {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let since = since_ms.unwrap_or(0);
+    let offset = i64::from(page) * i64::from(page_size);
+
+    with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp_ms, ssid, outcome, error_code, error_message,
+                    detect_ms, submit_ms, verify_ms, total_ms, backoff_secs
+             FROM auth_history
+             WHERE timestamp_ms >= ?1
+             ORDER BY timestamp_ms DESC, id DESC
+             LIMIT ?2 OFFSET ?3",
+        )?;
+        let entries = stmt
+            .query_map(params![since, page_size, offset], |row| {
+                Ok(AuthHistoryEntry {
+                    id: row.get(0)?,
+                    timestamp_ms: row.get(1)?,
+                    ssid: row.get(2)?,
+                    outcome: row.get(3)?,
+                    error_code: row.get(4)?,
+                    error_message: row.get(5)?,
+                    detect_ms: row.get(6)?,
+                    submit_ms: row.get(7)?,
+                    verify_ms: row.get(8)?,
+                    total_ms: row.get(9)?,
+                    backoff_secs: row.get(10)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let (total, attempts, successes, mean_time_to_auth_ms) = conn
+            .query_row(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(outcome != 'already_authenticated'), 0),
+                        COALESCE(SUM(outcome = 'authenticated'), 0),
+                        AVG(CASE WHEN outcome = 'authenticated' THEN total_ms END)
+                 FROM auth_history
+                 WHERE timestamp_ms >= ?1",
+                params![since],
+                |row| {
+                    Ok((
+                        row.get::<_, u64>(0)?,
+                        row.get::<_, u64>(1)?,
+                        row.get::<_, u64>(2)?,
+                        row.get(3)?,
+                    ))
+                },
+            )
+            .optional()?
+            .unwrap_or((0, 0, 0, None));
+
+        Ok(AuthHistoryPage {
+            entries,
+            page,
+            page_size,
+            total,
+            stats: AuthStats {
+                attempts,
+                successes,
+                success_rate: (attempts > 0).then(|| successes as f64 / attempts as f64),
+                mean_time_to_auth_ms,
+            },
+        })
+    })
+}
+
+/// Guarda cuánto duró una sesión antes de que el portal la cerrara.
+pub fn record_session_lifetime(ssid: Option<&str>, lifetime: Duration) {
+    let guard = match DATABASE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let Some(conn) = guard.as_ref() else {
+        return;
+    };
+
+    if let Err(e) = conn.execute(
+        "INSERT INTO session_lifetimes (timestamp_ms, ssid, lifetime_secs) VALUES (?1, ?2, ?3)",
+        params![now_ms(), ssid, lifetime.as_secs() as i64],
+    ) {
+        eprintln!("[history] No se pudo guardar la duración de sesión: {}", e);
+    }
+}
+
+/// Mediana de las últimas sesiones observadas en `ssid`. La mediana evita
+/// que una desconexión manual o un corte de red arruinen la estimación.
+pub fn learned_session_lifetime(ssid: Option<&str>) -> Option<Duration> {
+    let mut samples: Vec<u64> = with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT lifetime_secs FROM session_lifetimes
+             WHERE ssid IS ?1
+             ORDER BY timestamp_ms DESC, id DESC
+             LIMIT ?2",
+        )?;
+        stmt.query_map(params![ssid, SESSION_LIFETIME_SAMPLES], |row| row.get(0))?
+            .collect()
+    })
+    .ok()?;
+
+    if samples.is_empty() {
+        return None;
+    }
+
+    samples.sort_unstable();
+    Some(Duration::from_secs(samples[samples.len() / 2]))
+}
+
+/// Guarda una alerta de seguridad (portal falso, pin distinto, etc.).
+pub fn record_security_alert(alert: &SecurityAlert) {
+    let guard = match DATABASE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let Some(conn) = guard.as_ref() else {
+        return;
+    };
+
+    if let Err(e) = conn.execute(
+        "INSERT INTO security_alerts (timestamp_ms, kind, ssid, details) VALUES (?1, ?2, ?3, ?4)",
+        params![alert.timestamp_ms, alert.kind.as_key(), alert.ssid, alert.details],
+    ) {
+        eprintln!("[history] No se pudo guardar la alerta de seguridad: {}", e);
+    }
+}
+
+/// Alertas más recientes primero.
+pub fn security_alerts(limit: u32) -> Result<Vec<SecurityAlert>, String> {
+    let limit = limit.clamp(1, MAX_SECURITY_ALERTS);
+
+    with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT timestamp_ms, kind, ssid, details FROM security_alerts
+             ORDER BY timestamp_ms DESC, id DESC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let mut alerts = Vec::new();
+        for row in rows {
+            let (timestamp_ms, kind, ssid, details) = row?;
+            // Tipos desconocidos (de una versión más nueva) se omiten
+            if let Some(kind) = SecurityAlertKind::from_key(&kind) {
+                alerts.push(SecurityAlert {
+                    timestamp_ms,
+                    kind,
+                    ssid,
+                    message: kind.description().to_string(),
+                    details,
+                });
+            }
+        }
+        Ok(alerts)
+    })
+}
+
+fn as_millis(duration: Duration) -> i64 {
+    duration.as_millis() as i64
+}
@@ -0,0 +1,137 @@
+//! Archivo JSON en el directorio de configuración con su copia en memoria.
+//!
+//! Lo usan la configuración (`settings.json`) y los perfiles de red
+//! (`network-profiles.json`): se carga una vez al iniciar, se lee desde
+//! memoria y cada cambio se escribe completo en disco antes de aplicarse.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub struct JsonStore<T> {
+    file_name: &'static str,
+    /// Prefijo de los mensajes del log, p. ej. `settings`.
+    log_tag: &'static str,
+    /// Qué se guarda, para los mensajes de error ("configuración").
+    description: &'static str,
+    default: fn() -> T,
+    value: RwLock<T>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> JsonStore<T> {
+    pub fn new(
+        file_name: &'static str,
+        log_tag: &'static str,
+        description: &'static str,
+        default: fn() -> T,
+    ) -> Self {
+        JsonStore {
+            file_name,
+            log_tag,
+            description,
+            default,
+            value: RwLock::new(default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Carga el archivo desde `config_dir`. Si no existe se crea con el valor
+    /// por defecto; si no se puede leer o `validate` lo rechaza, se conserva el
+    /// valor en memoria.
+    pub fn init(&self, config_dir: &Path, validate: impl Fn(&T) -> Result<(), String>) {
+        let path = config_dir.join(self.file_name);
+        let tag = self.log_tag;
+
+        let loaded = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<T>(&contents) {
+                Ok(value) => match validate(&value) {
+                    Ok(()) => Some(value),
+                    Err(e) => {
+                        eprintln!(
+                            "[{}] {} inválido, usando valores por defecto: {}",
+                            tag,
+                            path.display(),
+                            e
+                        );
+                        None
+                    }
+                },
+                Err(e) => {
+                    eprintln!("[{}] Error leyendo {}: {}", tag, path.display(), e);
+                    None
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if let Err(e) = self.write(&path, &(self.default)()) {
+                    eprintln!("[{}] No se pudo crear {}: {}", tag, path.display(), e);
+                }
+                None
+            }
+            Err(e) => {
+                eprintln!("[{}] Error leyendo {}: {}", tag, path.display(), e);
+                None
+            }
+        };
+
+        match self.path.write() {
+            Ok(mut guard) => *guard = Some(path),
+            Err(poisoned) => *poisoned.into_inner() = Some(path),
+        }
+
+        if let Some(value) = loaded {
+            self.replace(value);
+        }
+    }
+
+    pub fn current(&self) -> T {
+        self.read(T::clone)
+    }
+
+    /// Consulta la copia en memoria sin clonarla completa.
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        match self.value.read() {
+            Ok(guard) => f(&guard),
+            Err(poisoned) => f(&poisoned.into_inner()),
+        }
+    }
+
+    /// Escribe `value` en disco (si ya hubo `init`) y lo aplica. Quien llama
+    /// valida antes.
+    pub fn store(&self, value: T) -> Result<T, String> {
+        let path = match self.path.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        if let Some(path) = path {
+            self.write(&path, &value)?;
+        }
+
+        self.replace(value.clone());
+        Ok(value)
+    }
+
+    fn replace(&self, value: T) {
+        match self.value.write() {
+            Ok(mut guard) => *guard = value,
+            Err(poisoned) => *poisoned.into_inner() = value,
+        }
+    }
+
+    fn write(&self, path: &Path, value: &T) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            create_config_dir(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Error serializando {}: {}", self.description, e))?;
+
+        fs::write(path, json).map_err(|e| format!("Error guardando {}: {}", self.description, e))
+    }
+}
+
+pub fn create_config_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Error creando directorio de configuración: {}", e))
+}
@@ -1,4 +1,5 @@
 use crate::keyring_controller::crypto::{decrypt_text, encrypt_text, generate_session_key};
+use crate::keyring_controller::vault;
 use base64::{engine::general_purpose, Engine as _};
 use lazy_static::lazy_static;
 use secrecy::{ExposeSecret, SecretBox};
@@ -28,6 +29,7 @@ use keyring_core::Error as PlatformError;
 
 const SERVICE_NAME: &str = "cima-sync";
 const KEY_USER: &str = "master_key";
+/// Credenciales de "recordar sesión"; separadas de los perfiles del vault.
 const KEY_CREDS: &str = "user_creds";
 const KEYRING_KEY_PREFIX: &str = "cimasync:";
 
@@ -157,7 +159,7 @@ fn android_store() -> Result<Arc<AndroidStore>, String> {
     Ok(ANDROID_STORE.get().expect("ANDROID_STORE set above").clone())
 }
 
-fn get_keyring_entry(key_name: &str) -> Result<PlatformEntry, String> {
+pub(super) fn get_keyring_entry(key_name: &str) -> Result<PlatformEntry, String> {
     let namespaced_key = format!("{}{}", KEYRING_KEY_PREFIX, key_name);
 
     #[cfg(target_os = "android")]
@@ -174,7 +176,7 @@ fn get_keyring_entry(key_name: &str) -> Result<PlatformEntry, String> {
     }
 }
 
-fn is_no_entry(err: &PlatformError) -> bool {
+pub(super) fn is_no_entry(err: &PlatformError) -> bool {
     matches!(err, PlatformError::NoEntry)
 }
 
@@ -219,6 +221,8 @@ pub fn get_session_key() -> Result<Vec<u8>, String> {
     }
 }
 
+/// Guarda las credenciales de "recordar sesión". No toca los perfiles del
+/// vault.
 pub fn save_credentials_to_keyring(email: &str, password: &str) -> Result<(), String> {
     let creds = UserCredentials {
         email: email.to_string(),
@@ -239,23 +243,38 @@ pub fn save_credentials_to_keyring(email: &str, password: &str) -> Result<(), St
     Ok(())
 }
 
+/// Credenciales de "recordar sesión" o, si no hay, las del perfil activo del
+/// vault.
 pub fn get_credentials_from_keyring() -> Result<UserCredentials, String> {
+    match get_remembered_credentials()? {
+        Some(creds) => Ok(creds),
+        None => vault::active_credentials()?
+            .ok_or_else(|| "No se encontraron credenciales".to_string()),
+    }
+}
+
+fn get_remembered_credentials() -> Result<Option<UserCredentials>, String> {
     let entry = get_keyring_entry(KEY_CREDS)?;
 
-    let encrypted = entry.get_password().map_err(|e| match e {
-        PlatformError::NoEntry => "No se encontraron credenciales".to_string(),
-        PlatformError::BadEncoding(_) => {
-            "Datos de keyring corruptos o invalidos".to_string()
+    let encrypted = match entry.get_password() {
+        Ok(encrypted) => encrypted,
+        Err(PlatformError::NoEntry) => return Ok(None),
+        Err(PlatformError::BadEncoding(_)) => {
+            return Err("Datos de keyring corruptos o invalidos".to_string());
         }
-        other => format!("Error de keyring: {}", other),
-    })?;
+        Err(other) => return Err(format!("Error de keyring: {}", other)),
+    };
 
     let session_key = get_session_key()?;
     let json = decrypt_text(&session_key, &encrypted)?;
 
-    serde_json::from_str(&json).map_err(|e| format!("Error deserializando credenciales: {}", e))
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| format!("Error deserializando credenciales: {}", e))
 }
 
+/// Olvida las credenciales de "recordar sesión". Los perfiles del vault se
+/// borran solo con `vault::delete_profile`.
 pub fn clear_credentials_from_keyring() -> Result<(), String> {
     let entry = get_keyring_entry(KEY_CREDS)?;
     let _ = entry.delete_credential();
@@ -266,7 +285,16 @@ pub fn init_crypto_system() -> Result<(), String> {
     get_session_key().map(|_| ())
 }
 
+/// Borra las credenciales de "recordar sesión" y la clave de sesión. Si el
+/// vault tiene perfiles la clave se conserva, porque sin ella ya no se podrían
+/// descifrar; para borrarlos está `vault::clear_vault`.
 pub fn clear_stored_key() -> Result<(), String> {
+    clear_credentials_from_keyring()?;
+    if !vault::is_empty() {
+        eprintln!("[keyring] El vault tiene perfiles; se conserva la clave de sesión");
+        return Ok(());
+    }
+
     let mut session_key = SESSION_KEY
         .lock()
         .map_err(|_| "Error al acceder a la clave de sesión".to_string())?;
@@ -276,9 +304,6 @@ pub fn clear_stored_key() -> Result<(), String> {
     let entry_key = get_keyring_entry(KEY_USER)?;
     let _ = entry_key.delete_credential();
 
-    let entry_creds = get_keyring_entry(KEY_CREDS)?;
-    let _ = entry_creds.delete_credential();
-
     Ok(())
 }
 
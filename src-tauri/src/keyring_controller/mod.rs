@@ -1,2 +1,3 @@
 pub mod crypto;
-pub mod keyring;
\ No newline at end of file
+pub mod keyring;
+pub mod vault;
\ No newline at end of file
@@ -0,0 +1,281 @@
+use crate::history::now_ms;
+use crate::keyring_controller::crypto::{decrypt_text, encrypt_text};
+use crate::keyring_controller::keyring::{
+    get_keyring_entry, get_session_key, is_no_entry, UserCredentials,
+};
+use lazy_static::lazy_static;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Índice del vault: etiquetas de los perfiles y cuál está activo. Las
+/// credenciales viven en una entrada de keyring por perfil (`profile:<id>`)
+/// porque algunos keyrings (Windows) limitan el tamaño de cada entrada.
+const KEY_VAULT_INDEX: &str = "vault_index";
+const KEY_PROFILE_PREFIX: &str = "profile:";
+const MAX_LABEL_LENGTH: usize = 64;
+
+lazy_static! {
+    // Serializa las operaciones de lectura-modificación-escritura del índice
+    static ref VAULT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VaultIndex {
+    active: Option<String>,
+    profiles: Vec<ProfileEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ProfileEntry {
+    id: String,
+    label: String,
+    created_at_ms: i64,
+}
+
+/// Perfil tal como se muestra al usuario; nunca incluye la contraseña.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub label: String,
+    pub email: String,
+    pub active: bool,
+    pub created_at_ms: i64,
+}
+
+fn lock_vault() -> std::sync::MutexGuard<'static, ()> {
+    match VAULT_LOCK.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn load_index() -> Result<VaultIndex, String> {
+    let entry = get_keyring_entry(KEY_VAULT_INDEX)?;
+    match entry.get_password() {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Índice de perfiles corrupto: {}", e)),
+        Err(e) if is_no_entry(&e) => Ok(VaultIndex::default()),
+        Err(e) => Err(format!("Error de keyring: {}", e)),
+    }
+}
+
+fn save_index(index: &VaultIndex) -> Result<(), String> {
+    let json = serde_json::to_string(index)
+        .map_err(|e| format!("Error serializando índice de perfiles: {}", e))?;
+    get_keyring_entry(KEY_VAULT_INDEX)?
+        .set_password(&json)
+        .map_err(|e| format!("Error guardando índice de perfiles: {}", e))
+}
+
+fn profile_key(id: &str) -> String {
+    format!("{}{}", KEY_PROFILE_PREFIX, id)
+}
+
+fn write_profile(id: &str, email: &str, password: &str) -> Result<(), String> {
+    let creds = UserCredentials {
+        email: email.to_string(),
+        password: password.to_string(),
+    };
+    let json = serde_json::to_string(&creds)
+        .map_err(|e| format!("Error serializando credenciales: {}", e))?;
+
+    let encrypted = encrypt_text(&get_session_key()?, &json)?;
+    get_keyring_entry(&profile_key(id))?
+        .set_password(&encrypted)
+        .map_err(|e| format!("Error guardando perfil en keyring: {}", e))
+}
+
+fn read_profile(id: &str) -> Result<UserCredentials, String> {
+    let encrypted = get_keyring_entry(&profile_key(id))?
+        .get_password()
+        .map_err(|e| {
+            if is_no_entry(&e) {
+                "El perfil no tiene credenciales guardadas".to_string()
+            } else {
+                format!("Error de keyring: {}", e)
+            }
+        })?;
+
+    let json = decrypt_text(&get_session_key()?, &encrypted)?;
+    serde_json::from_str(&json).map_err(|e| format!("Error deserializando credenciales: {}", e))
+}
+
+fn remove_profile_entry(id: &str) -> Result<(), String> {
+    let entry = get_keyring_entry(&profile_key(id))?;
+    let _ = entry.delete_credential();
+    Ok(())
+}
+
+fn new_profile_id() -> String {
+    let mut bytes = [0u8; 8];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn validate_label(label: &str) -> Result<String, String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("La etiqueta del perfil no puede estar vacía".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_LENGTH {
+        return Err(format!(
+            "La etiqueta del perfil es demasiado larga (máximo {} caracteres)",
+            MAX_LABEL_LENGTH
+        ));
+    }
+    if label.chars().any(|c| c.is_control()) {
+        return Err("Se detectaron caracteres de control no permitidos".to_string());
+    }
+    Ok(label.to_string())
+}
+
+fn insert_profile(
+    index: &mut VaultIndex,
+    label: &str,
+    email: &str,
+    password: &str,
+) -> Result<ProfileEntry, String> {
+    let entry = ProfileEntry {
+        id: new_profile_id(),
+        label: validate_label(label)?,
+        created_at_ms: now_ms(),
+    };
+    write_profile(&entry.id, email, password)?;
+
+    index.profiles.push(entry.clone());
+    if index.active.is_none() {
+        index.active = Some(entry.id.clone());
+    }
+    Ok(entry)
+}
+
+/// Agrega un perfil. El primero que se agrega queda activo; `activate`
+/// fuerza que el nuevo perfil pase a ser el activo.
+pub fn add_profile(
+    label: &str,
+    email: &str,
+    password: &str,
+    activate: bool,
+) -> Result<ProfileSummary, String> {
+    let _guard = lock_vault();
+    let mut index = load_index()?;
+
+    let entry = insert_profile(&mut index, label, email, password)?;
+    if activate {
+        index.active = Some(entry.id.clone());
+    }
+
+    if let Err(e) = save_index(&index) {
+        let _ = remove_profile_entry(&entry.id);
+        return Err(e);
+    }
+
+    Ok(ProfileSummary {
+        active: index.active.as_deref() == Some(entry.id.as_str()),
+        id: entry.id,
+        label: entry.label,
+        email: email.to_string(),
+        created_at_ms: entry.created_at_ms,
+    })
+}
+
+/// Lista los perfiles con su correo descifrado. Los perfiles que ya no se
+/// pueden descifrar (p. ej. tras reiniciar la clave) se omiten.
+pub fn list_profiles() -> Result<Vec<ProfileSummary>, String> {
+    let _guard = lock_vault();
+    let index = load_index()?;
+
+    Ok(index
+        .profiles
+        .iter()
+        .filter_map(|entry| match read_profile(&entry.id) {
+            Ok(creds) => Some(ProfileSummary {
+                id: entry.id.clone(),
+                label: entry.label.clone(),
+                email: creds.email.clone(),
+                active: index.active.as_deref() == Some(entry.id.as_str()),
+                created_at_ms: entry.created_at_ms,
+            }),
+            Err(e) => {
+                eprintln!("[vault] Perfil {} ilegible: {}", entry.id, e);
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn select_profile(id: &str) -> Result<(), String> {
+    let _guard = lock_vault();
+    let mut index = load_index()?;
+
+    if !index.profiles.iter().any(|entry| entry.id == id) {
+        return Err("Perfil no encontrado".to_string());
+    }
+
+    index.active = Some(id.to_string());
+    save_index(&index)
+}
+
+/// Elimina un perfil. Si era el activo, pasa a activo el primero restante.
+pub fn delete_profile(id: &str) -> Result<(), String> {
+    let _guard = lock_vault();
+    let mut index = load_index()?;
+
+    let before = index.profiles.len();
+    index.profiles.retain(|entry| entry.id != id);
+    if index.profiles.len() == before {
+        return Err("Perfil no encontrado".to_string());
+    }
+
+    if index.active.as_deref() == Some(id) {
+        index.active = index.profiles.first().map(|entry| entry.id.clone());
+    }
+
+    save_index(&index)?;
+    remove_profile_entry(id)
+}
+
+pub fn get_profile_credentials(id: &str) -> Result<UserCredentials, String> {
+    let _guard = lock_vault();
+    let index = load_index()?;
+
+    if !index.profiles.iter().any(|entry| entry.id == id) {
+        return Err("Perfil no encontrado".to_string());
+    }
+    read_profile(id)
+}
+
+/// Credenciales del perfil activo, o `None` si el vault está vacío.
+pub fn active_credentials() -> Result<Option<UserCredentials>, String> {
+    let _guard = lock_vault();
+    let index = load_index()?;
+
+    match index.active {
+        Some(id) => read_profile(&id).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// `true` si no hay perfiles guardados. Ante un error de lectura se asume que
+/// sí los hay, para no descartar la clave que los cifra.
+pub fn is_empty() -> bool {
+    let _guard = lock_vault();
+    load_index()
+        .map(|index| index.profiles.is_empty())
+        .unwrap_or(false)
+}
+
+/// Borra todos los perfiles y el índice.
+pub fn clear_vault() -> Result<(), String> {
+    let _guard = lock_vault();
+    let index = load_index().unwrap_or_default();
+
+    for entry in &index.profiles {
+        remove_profile_entry(&entry.id)?;
+    }
+
+    let entry = get_keyring_entry(KEY_VAULT_INDEX)?;
+    let _ = entry.delete_credential();
+    Ok(())
+}
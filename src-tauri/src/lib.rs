@@ -1,20 +1,56 @@
-mod auth;
+pub mod auth;
+#[cfg(desktop)]
+pub mod cli;
 mod commands;
-mod keyring_controller;
-mod network_controller;
+pub mod error;
+pub mod events;
+pub mod history;
+mod json_store;
+pub mod keyring_controller;
+pub mod network_controller;
+pub mod portal_controller;
+pub mod settings;
 mod tray;
 
+use tauri::Manager;
+
+use crate::events::{AppEvent, EventSink};
 use crate::network_controller::network_sync::start_network_monitor;
+use crate::network_controller::{pins, profiles, tofu};
+use crate::portal_controller::policy;
 
 #[cfg(desktop)]
 use crate::tray::system_tray;
 
 use crate::commands::{
-    auto_auth, clear_crypto, decrypt_credentials, delete_credentials, encrypt_credentials,
-    get_auth_status, get_credentials, get_network_status, init_crypto, login,
-    save_credentials, set_crypto_key, set_mobile_wifi_info, stop_auth,
+    add_profile, approve_network_pin, auto_auth, clear_crypto, clear_vault, decrypt_credentials,
+    delete_credentials, delete_network_profile, delete_profile, encrypt_credentials,
+    get_auth_history, get_auth_status, get_credentials, get_network_profiles,
+    get_network_status, get_pending_pins, get_security_alerts, get_settings, init_crypto,
+    list_profiles, login, reject_network_pin, save_credentials, save_network_profile,
+    select_profile, set_crypto_key, set_mobile_wifi_info, stop_auth, update_settings,
 };
 
+/// Reenvía cada alerta de la política del portal a la ventana como `security-alert`.
+fn forward_security_alerts(app: tauri::AppHandle) {
+    policy::on_security_alert(move |alert| {
+        app.emit_event(&AppEvent::SecurityAlert(alert.clone()));
+    });
+}
+
+/// Pide a la ventana aprobar el certificado de un portal nuevo (`pin-approval-required`).
+fn forward_pending_pins(app: tauri::AppHandle) {
+    tofu::on_pin_pending(move |pending| {
+        app.emit_event(&AppEvent::PinApprovalRequired(pending.clone()));
+        // El monitor suele correr con la ventana oculta en la bandeja
+        #[cfg(desktop)]
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let _ = rustls::crypto::ring::default_provider().install_default();
@@ -39,6 +75,13 @@ pub fn run() {
                 Some(vec!["--flag1", "--flag2"]),
             ))
             .setup(|app| {
+                let config_dir = app.path().app_config_dir()?;
+                settings::init(&config_dir);
+                history::init(&config_dir);
+                profiles::init(&config_dir);
+                pins::init(&config_dir);
+                forward_security_alerts(app.handle().clone());
+                forward_pending_pins(app.handle().clone());
                 system_tray(app)?;
                 start_network_monitor(app.handle().clone());
                 Ok(())
@@ -54,6 +97,13 @@ pub fn run() {
     #[cfg(not(desktop))]
     {
         builder = builder.setup(|app| { 
+            let config_dir = app.path().app_config_dir()?;
+            settings::init(&config_dir);
+            history::init(&config_dir);
+            profiles::init(&config_dir);
+            pins::init(&config_dir);
+            forward_security_alerts(app.handle().clone());
+            forward_pending_pins(app.handle().clone());
             start_network_monitor(app.handle().clone());
             Ok(())
         });
@@ -68,6 +118,7 @@ pub fn run() {
             encrypt_credentials,
             decrypt_credentials,
             clear_crypto,
+            clear_vault,
             set_crypto_key,
             save_credentials,
             get_credentials,
@@ -75,6 +126,20 @@ pub fn run() {
             get_network_status,
             get_auth_status,
             set_mobile_wifi_info,
+            get_settings,
+            update_settings,
+            get_auth_history,
+            get_security_alerts,
+            add_profile,
+            list_profiles,
+            select_profile,
+            delete_profile,
+            get_network_profiles,
+            save_network_profile,
+            delete_network_profile,
+            get_pending_pins,
+            approve_network_pin,
+            reject_network_pin,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
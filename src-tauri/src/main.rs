@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    #[cfg(desktop)]
+    if let Some(code) = cima_sync_lib::cli::run_from_args() {
+        std::process::exit(code);
+    }
+
     cima_sync_lib::run()
 }
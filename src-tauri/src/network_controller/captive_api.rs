@@ -0,0 +1,148 @@
+//! Detección estándar del portal cautivo (RFC 8910 y RFC 8908).
+//!
+//! La red anuncia la URI de su API de portal cautivo por DHCP (opción 114) o
+//! en los Router Advertisements; en Linux, NetworkManager la expone entre las
+//! opciones DHCP del dispositivo. La API responde un JSON con `captive`,
+//! `user-portal-url` y `seconds-remaining`, así que no hace falta adivinar el
+//! portal por redirecciones y se obtiene el tiempo restante de la sesión.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::network_controller::client_builder::get_simple_client;
+use crate::network_controller::prober::{Connectivity, Verdict};
+use crate::network_controller::rtt;
+use crate::settings;
+
+const CAPTIVE_JSON: &str = "application/captive+json";
+/// Valor anunciado por una red sin portal (RFC 8910, sección 2).
+pub const UNRESTRICTED_URN: &str = "urn:ietf:params:capport:unrestricted";
+/// Nombres con los que los clientes DHCP reportan la opción 114.
+const DHCP_OPTION_KEYS: [&str; 3] = ["captive_portal", "captive-portal", "default_url"];
+
+lazy_static! {
+    static ref API_URI: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Estado reportado por la API del portal (RFC 8908, sección 5).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CaptiveApiStatus {
+    pub captive: bool,
+    pub user_portal_url: Option<String>,
+    pub venue_info_url: Option<String>,
+    pub can_extend_session: Option<bool>,
+    pub seconds_remaining: Option<u64>,
+    pub bytes_remaining: Option<u64>,
+}
+
+impl CaptiveApiStatus {
+    /// Tiempo que le queda a la sesión, si la red ya dejó salir al cliente.
+    pub fn session_remaining(&self) -> Option<Duration> {
+        if self.captive {
+            return None;
+        }
+        self.seconds_remaining.map(Duration::from_secs)
+    }
+}
+
+pub fn parse_status(body: &str) -> Result<CaptiveApiStatus, String> {
+    serde_json::from_str(body)
+        .map_err(|e| format!("Respuesta inválida de la API del portal: {}", e))
+}
+
+/// URI de la API entre las opciones DHCP del dispositivo (propiedad
+/// `Options` de DHCP4Config/DHCP6Config en NetworkManager).
+pub fn find_dhcp_captive_uri<'a>(
+    options: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Option<String> {
+    options.into_iter().find_map(|(key, value)| {
+        let value = value.trim();
+        (DHCP_OPTION_KEYS.contains(&key) && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Propiedad `Connectivity` de NetworkManager (`NMConnectivityState`).
+/// `limited` y `unknown` no dicen nada sobre el portal y se ignoran.
+pub fn from_nm_connectivity(state: u32) -> Option<Connectivity> {
+    match state {
+        4 => Some(Connectivity::Online),
+        2 => Some(Connectivity::CaptivePortal),
+        1 => Some(Connectivity::Offline),
+        _ => None,
+    }
+}
+
+/// Guarda la URI anunciada por la red actual (o la olvida con `None`).
+pub fn remember_uri(uri: Option<String>) {
+    match API_URI.lock() {
+        Ok(mut guard) => *guard = uri,
+        Err(poisoned) => *poisoned.into_inner() = uri,
+    }
+}
+
+pub fn current_uri() -> Option<String> {
+    match API_URI.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Consulta la API en `uri`. El RFC exige HTTPS, así que otro esquema se
+/// rechaza sin conectarse.
+pub async fn fetch(uri: &str) -> Result<CaptiveApiStatus, String> {
+    if uri == UNRESTRICTED_URN {
+        return Ok(CaptiveApiStatus::default());
+    }
+
+    let parsed = reqwest::Url::parse(uri).map_err(|e| format!("URI de la API inválida: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("La API del portal debe usar HTTPS: {}", uri));
+    }
+
+    let request = get_simple_client()
+        .get(uri)
+        .header(reqwest::header::ACCEPT, CAPTIVE_JSON);
+    let response = rtt::send(request, uri, settings::current().connectivity_timeout())
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Error consultando la API del portal: {}", e))?;
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Error leyendo la API del portal: {}", e))?;
+
+    parse_status(&body)
+}
+
+/// Estado de la API anunciada por la red actual, si hay una.
+pub async fn query() -> Option<CaptiveApiStatus> {
+    let uri = current_uri()?;
+    match fetch(&uri).await {
+        Ok(status) => Some(status),
+        Err(e) => {
+            eprintln!("[captive-api] {}", e);
+            None
+        }
+    }
+}
+
+/// Ajusta el diagnóstico de las sondas con las señales estándar. La API manda
+/// cuando dice que hay portal; el sistema operativo solo desempata cuando las
+/// sondas no llegaron a internet.
+pub fn apply(
+    mut verdict: Verdict,
+    api: Option<&CaptiveApiStatus>,
+    os_hint: Option<Connectivity>,
+) -> Verdict {
+    if api.is_some_and(|status| status.captive) {
+        verdict.connectivity = Connectivity::CaptivePortal;
+        verdict.confidence = 1.0;
+    } else if api.is_none() && os_hint == Some(Connectivity::CaptivePortal) && !verdict.is_online()
+    {
+        verdict.connectivity = Connectivity::CaptivePortal;
+    }
+    verdict
+}
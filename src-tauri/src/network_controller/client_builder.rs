@@ -1,17 +1,22 @@
 use lazy_static::lazy_static;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
 use rustls::client::WebPkiServerVerifier;
 use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
-use rustls::{DigitallySignedStruct, Error as RustlsError, RootCertStore, SignatureScheme};
-use sha2::{Digest, Sha256};
+use rustls::{
+    CertificateError, DigitallySignedStruct, Error as RustlsError, OtherError, RootCertStore,
+    SignatureScheme,
+};
+
+use crate::network_controller::pins::{self, Pin, PinSet};
+pub use crate::network_controller::pins::PinMismatch;
 
 #[derive(Debug)]
 struct PinnedCertVerifier {
     inner: Arc<dyn ServerCertVerifier>,
-    expected_cert_sha256: [u8; 32],
+    pins: PinSet,
 }
 
 impl ServerCertVerifier for PinnedCertVerifier {
@@ -27,17 +32,12 @@ impl ServerCertVerifier for PinnedCertVerifier {
         self.inner
             .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
 
-        // Cálculo del SHA-256 del certificado presentado por el servidor
-        let mut hasher = Sha256::new();
-        hasher.update(end_entity.as_ref());
-        let digest = hasher.finalize();
-
-        if digest.as_slice() == self.expected_cert_sha256 {
-            Ok(ServerCertVerified::assertion())
-        } else {
-            Err(RustlsError::General(
-                "El certificado del servidor no coincide con el pin configurado".into(),
-            ))
+        // Algún pin vigente del conjunto debe coincidir con el certificado o su llave
+        match self.pins.check(end_entity.as_ref(), now.as_secs() as i64) {
+            Ok(()) => Ok(ServerCertVerified::assertion()),
+            Err(mismatch) => Err(RustlsError::InvalidCertificate(CertificateError::Other(
+                OtherError(Arc::new(mismatch)),
+            ))),
         }
     }
 
@@ -64,22 +64,65 @@ impl ServerCertVerifier for PinnedCertVerifier {
     }
 }
 
-fn build_pinned_client(no_redirect: bool) -> reqwest::blocking::Client {
-    // Mismo pin que en `cima-sync-cli`
-    // No sabes que es esto?
-    // checa este repo: https://github.com/Yoyiyoniu/cima-tool.git
-    const CERT_SHA256_HEX: &str =
-        "19DC98BB1F0806934A375019394A01A9DAD4A18758EB1E4BB82607CDEB1DD25B";
+/// Pines del certificado del portal y anclas de confianza adicionales.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinConfig {
+    pins: PinSet,
+    extra_roots: Vec<CertificateDer<'static>>,
+}
+
+impl PinConfig {
+    pub fn new(pins: PinSet) -> Self {
+        PinConfig {
+            pins,
+            extra_roots: Vec::new(),
+        }
+    }
+
+    /// Pines del portal de la UABC (pcw.uabc.mx): los del `pins.json` firmado
+    /// si se cargó uno, o los integrados en la compilación.
+    pub fn uabc() -> Self {
+        Self::new(
+            pins::signed_pin_set(pins::UABC_PIN_SET).unwrap_or_else(PinSet::builtin_uabc),
+        )
+    }
+
+    /// Crea un conjunto con un solo pin (`cert-sha256:`/`spki-sha256:` o hex
+    /// del certificado).
+    pub fn from_hex(pin: &str) -> Result<Self, String> {
+        Ok(Self::new(PinSet::new("personalizado", vec![Pin::parse(pin)?])))
+    }
+
+    /// Agrega un pin de respaldo (p. ej. el del próximo certificado).
+    pub fn with_pin(mut self, pin: Pin) -> Self {
+        self.pins.pins.push(pin);
+        self
+    }
 
-    let expected_cert_sha256_vec =
-        hex::decode(CERT_SHA256_HEX).expect("CERT_SHA256_HEX inválido, no es hex");
-    let expected_cert_sha256: [u8; 32] = expected_cert_sha256_vec
-        .try_into()
-        .expect("CERT_SHA256_HEX no tiene longitud de 32 bytes");
+    /// Agrega un ancla de confianza además de las de webpki-roots
+    /// (p. ej. la CA de un portal de pruebas).
+    pub fn with_root(mut self, root: CertificateDer<'static>) -> Self {
+        self.extra_roots.push(root);
+        self
+    }
+}
 
+/// Configuración rustls con los anchors de webpki-roots (más los de `pins`)
+/// y el verificador que exige los pines. La comparten todos los clientes del
+/// portal.
+pub fn pinned_tls_config(pins: &PinConfig) -> rustls::ClientConfig {
     // RootCertStore con los anchors de webpki-roots
     let mut root_store = RootCertStore::empty();
     root_store.roots = webpki_roots::TLS_SERVER_ROOTS.to_vec();
+    let global_roots = match EXTRA_ROOTS.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    for root in pins.extra_roots.iter().chain(&global_roots) {
+        if let Err(e) = root_store.add(root.clone()) {
+            eprintln!("[client-builder] Ancla de confianza ignorada: {}", e);
+        }
+    }
     let root_store = Arc::new(root_store);
 
     let inner_verifier: Arc<dyn ServerCertVerifier> = WebPkiServerVerifier::builder(root_store)
@@ -88,19 +131,24 @@ fn build_pinned_client(no_redirect: bool) -> reqwest::blocking::Client {
 
     let pinned_verifier = Arc::new(PinnedCertVerifier {
         inner: inner_verifier,
-        expected_cert_sha256,
+        pins: pins.pins.clone(),
     });
 
-    let root_config = rustls::ClientConfig::builder()
+    rustls::ClientConfig::builder()
         .dangerous()
         .with_custom_certificate_verifier(pinned_verifier)
-        .with_no_client_auth();
+        .with_no_client_auth()
+}
 
-    let mut builder = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
+/// Cliente asíncrono con pinning y el timeout pedido. No necesita un runtime
+/// para construirse; las peticiones corren en el runtime de Tauri (o el de las
+/// pruebas). Normalmente se obtiene de la caché con `pinned_client`.
+pub fn build_pinned_client(timeout: Duration, no_redirect: bool, pins: &PinConfig) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .timeout(timeout)
         .pool_max_idle_per_host(2)
         .pool_idle_timeout(Duration::from_secs(30))
-        .use_preconfigured_tls(root_config);
+        .use_preconfigured_tls(pinned_tls_config(pins));
 
     if no_redirect {
         builder = builder.redirect(reqwest::redirect::Policy::none());
@@ -109,20 +157,23 @@ fn build_pinned_client(no_redirect: bool) -> reqwest::blocking::Client {
     builder.build().expect("Failed to build HTTP client with pinning")
 }
 
-lazy_static! {
-    // Cliente con pinning y redirecciones permitidas (para la mayoría de peticiones al portal)
-    static ref CLIENT_WITH_REDIRECT: reqwest::blocking::Client = {
-        build_pinned_client(false)
-    };
+/// Clientes ya construidos por (timeout, sin redirecciones, pines). Construir
+/// uno arma la configuración TLS completa, así que se reutilizan.
+type ClientKey = (Duration, bool, PinConfig);
 
-    // Cliente con pinning y sin seguir redirecciones (para detectar el portal cautivo)
-    static ref CLIENT_NO_REDIRECT: reqwest::blocking::Client = {
-        build_pinned_client(true)
-    };
+/// Suficiente para los portales de los perfiles de red en uso.
+const MAX_CACHED_CLIENTS: usize = 16;
 
-    // Cliente simple sin pinning, usado para comprobar conectividad general (Google/Cloudflare)
-    static ref CLIENT_SIMPLE: reqwest::blocking::Client = {
-        reqwest::blocking::Client::builder()
+lazy_static! {
+    static ref PINNED_CLIENTS: Mutex<Vec<(ClientKey, reqwest::Client)>> = Mutex::new(Vec::new());
+    // Anclas agregadas con `trust_root`; valen para todos los clientes del portal.
+    static ref EXTRA_ROOTS: Mutex<Vec<CertificateDer<'static>>> = Mutex::new(Vec::new());
+
+    // Cliente simple sin pinning, usado para comprobar conectividad general
+    // (generate_204, Google/Cloudflare). Se comparte para reutilizar conexiones;
+    // quien necesite otro timeout lo fija en la petición.
+    static ref CLIENT_SIMPLE: reqwest::Client = {
+        reqwest::Client::builder()
             .timeout(Duration::from_secs(3))
             .pool_max_idle_per_host(1)
             .pool_idle_timeout(Duration::from_secs(20))
@@ -131,17 +182,48 @@ lazy_static! {
     };
 }
 
-pub fn build_client(
-    _timeout: Duration,
-    no_redirect: bool,
-) -> Result<&'static reqwest::blocking::Client, Box<dyn std::error::Error>> {
-    if no_redirect {
-        Ok(&*CLIENT_NO_REDIRECT)
-    } else {
-        Ok(&*CLIENT_WITH_REDIRECT)
+/// Cliente con pinning que respeta `timeout` y la política de redirecciones.
+/// Los clientes se comparten por combinación de parámetros.
+pub fn pinned_client(timeout: Duration, no_redirect: bool, pins: &PinConfig) -> reqwest::Client {
+    let mut clients = match PINNED_CLIENTS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if let Some((_, client)) = clients
+        .iter()
+        .find(|((t, r, p), _)| *t == timeout && *r == no_redirect && p == pins)
+    {
+        return client.clone();
     }
+
+    let client = build_pinned_client(timeout, no_redirect, pins);
+    if clients.len() >= MAX_CACHED_CLIENTS {
+        clients.remove(0);
+    }
+    clients.push(((timeout, no_redirect, pins.clone()), client.clone()));
+    client
+}
+
+/// Agrega un ancla de confianza para todos los clientes del portal, incluidos
+/// los que se arman desde los perfiles de red (p. ej. la CA de un portal de
+/// pruebas). Los clientes ya construidos se descartan.
+pub fn trust_root(root: CertificateDer<'static>) {
+    match EXTRA_ROOTS.lock() {
+        Ok(mut guard) => guard.push(root),
+        Err(poisoned) => poisoned.into_inner().push(root),
+    }
+    match PINNED_CLIENTS.lock() {
+        Ok(mut guard) => guard.clear(),
+        Err(poisoned) => poisoned.into_inner().clear(),
+    }
+}
+
+/// Cliente del portal de la UABC con el timeout pedido (con o sin redirecciones).
+pub fn build_client(timeout: Duration, no_redirect: bool) -> reqwest::Client {
+    pinned_client(timeout, no_redirect, &PinConfig::uabc())
 }
 
-pub fn get_simple_client() -> &'static reqwest::blocking::Client {
-    &*CLIENT_SIMPLE
+pub fn get_simple_client() -> &'static reqwest::Client {
+    &CLIENT_SIMPLE
 }
@@ -1,2 +1,18 @@
 pub mod network_sync;
-pub mod client_builder;
\ No newline at end of file
+#[cfg(target_os = "linux")]
+pub mod networkmanager;
+pub mod captive_api;
+pub mod client_builder;
+pub mod pins;
+pub mod prober;
+pub mod profiles;
+pub mod rtt;
+pub mod state_machine;
+pub mod status;
+pub mod tofu;
+pub mod wifi;
+pub mod wifi_backend;
+pub mod wifi_parse;
+pub mod wlan;
+#[cfg(target_os = "windows")]
+pub mod wlanapi;
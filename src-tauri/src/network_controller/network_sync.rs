@@ -1,27 +1,68 @@
+use async_trait::async_trait;
 use lazy_static::lazy_static;
 #[cfg(not(target_os = "android"))]
-use netwatcher::{watch_interfaces, Interface, Update};
+use netwatcher::{list_interfaces, watch_interfaces, Interface, Update};
 use regex::Regex;
-use reqwest::blocking::Client;
 #[cfg(not(target_os = "android"))]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(not(target_os = "android"))]
-use std::process::Command;
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex, Once};
 #[cfg(not(target_os = "android"))]
 use std::thread;
 use std::time::Duration;
 use tauri::Emitter;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::events::{AppEvent, EventSink, UabcDetected};
+use crate::network_controller::captive_api::{self, CaptiveApiStatus};
+#[cfg(target_os = "linux")]
+use crate::network_controller::networkmanager;
+#[cfg(target_os = "windows")]
+use crate::network_controller::wlanapi;
+use crate::network_controller::prober::{self, Verdict};
+use crate::network_controller::profiles::{self, NetworkObservation, NetworkProfile};
+use crate::network_controller::state_machine::{
+    ConnectivityProber, Hysteresis, Link, NetworkStateMachine, SystemClock,
+};
+use crate::network_controller::status::{LinkAddresses, NetworkStatus};
+pub use crate::network_controller::status::SyncNetworkState;
+use crate::network_controller::wifi::WifiInfo;
+#[cfg(not(target_os = "android"))]
+use crate::network_controller::wifi_backend::{SystemCommands, WifiBackend};
+#[cfg(target_os = "linux")]
+use crate::network_controller::wifi_backend::LinuxCommands;
+#[cfg(target_os = "macos")]
+use crate::network_controller::wifi_backend::MacCommands;
+#[cfg(target_os = "windows")]
+use crate::network_controller::wifi_backend::WindowsCommands;
+use crate::{history, settings};
 
 static MONITOR_ONCE: Once = Once::new();
 
 lazy_static! {
     static ref LAST_STATE: Mutex<Option<WifiState>> = Mutex::new(None);
-    static ref LAST_SYNC_NETWORK_STATE: Mutex<Option<SyncNetworkState>> = Mutex::new(None);
+    static ref STATE_MACHINE: NetworkStateMachine = network_state_machine();
+    // Destino de las re-evaluaciones que pide la máquina de estados
+    static ref MONITOR_SINK: Mutex<Option<Arc<dyn EventSink>>> = Mutex::new(None);
+    // BSSID y MAC del gateway de la última red vista; se guarda antes de
+    // evaluar la red, mientras LAST_STATE aún tiene la anterior.
+    static ref LAST_LINK: Mutex<LinkIdentity> = Mutex::new(LinkIdentity::default());
+    // SSID de la última red evaluada; su perfil se busca de nuevo al usarlo
+    // para ver los perfiles guardados, editados o borrados desde entonces.
+    static ref LAST_EVALUATED_SSID: Mutex<Option<String>> = Mutex::new(None);
+    static ref STATE_SUBSCRIBERS: Mutex<Vec<StateSubscriber>> = Mutex::new(Vec::new());
     static ref INTERFACE_NAME_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9_\-\. ]+$")
         .expect("Regex de interfaz inválido");
+    #[cfg(not(target_os = "android"))]
+    static ref WIFI_BACKENDS: Vec<Box<dyn WifiBackend>> = wifi_backends();
+    #[cfg(not(target_os = "android"))]
+    static ref FAILED_WIFI_BACKENDS: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
     // Conservado para compatibilidad con get_current_network_status en Android
     static ref ANDROID_SSID: Mutex<Option<Box<str>>> = Mutex::new(None);
+    // RSSI y velocidad del último evento del observer de Android
+    #[cfg(target_os = "android")]
+    static ref ANDROID_WIFI: Mutex<Option<WifiInfo>> = Mutex::new(None);
 }
 
 /// Actualiza el SSID en Android (llamado desde el frontend vía comando Tauri).
@@ -38,41 +79,9 @@ pub fn update_android_ssid(ssid: Option<String>) {
 
 const SSID_RETRY_DELAY_MS: u64 = 500;
 #[cfg(not(target_os = "android"))]
+const SSID_UNAVAILABLE: &str = "SSID no disponible";
+#[cfg(not(target_os = "android"))]
 const MAX_INTERFACE_NAME_LENGTH: usize = 64;
-const CONNECTIVITY_TIMEOUT_SECS: u64 = 3;
-const GENERATE_204_URL: &str = "http://clients3.google.com/generate_204";
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum SyncNetworkState {
-    FineConnection,
-    HaveCautivePortal,
-    InvalidConnection,
-    MobileConnection,
-    MobileConnectionRequiereAuth,
-}
-
-impl SyncNetworkState {
-    fn as_key(self) -> &'static str {
-        match self {
-            SyncNetworkState::FineConnection => "fineConnection",
-            SyncNetworkState::HaveCautivePortal => "haveCautivePortal",
-            SyncNetworkState::InvalidConnection => "invalidConnection",
-            SyncNetworkState::MobileConnection => "mobileConnection",
-            SyncNetworkState::MobileConnectionRequiereAuth => "mobileConnectionRequiereAuth",
-        }
-    }
-
-    fn as_status_text(self) -> &'static str {
-        match self {
-            SyncNetworkState::FineConnection => "WI-FI Cimarrón Autenticado",
-            SyncNetworkState::HaveCautivePortal | SyncNetworkState::MobileConnectionRequiereAuth => {
-                "Red UABC disponible, inicia sesión"
-            }
-            SyncNetworkState::InvalidConnection => "WI-FI Cimarrón No Disponible",
-            SyncNetworkState::MobileConnection => "Sin conexión a la red de la universidad",
-        }
-    }
-}
 
 #[cfg(not(target_os = "android"))]
 #[derive(Debug)]
@@ -131,6 +140,19 @@ struct WifiState {
     interface: Box<str>,
     ssid: Option<Box<str>>,
     ipv4: Option<Box<str>>,
+    ipv6: Option<Box<str>>,
+    gateway: Option<Box<str>>,
+    link: LinkIdentity,
+    /// Señal, frecuencia y seguridad, cuando el backend del sistema las da.
+    details: Option<WifiInfo>,
+}
+
+/// Identidad física de la red, usada por los perfiles que fijan el punto de
+/// acceso o el gateway. Solo se obtiene en escritorio.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct LinkIdentity {
+    bssid: Option<Box<str>>,
+    gateway_mac: Option<Box<str>>,
 }
 
 #[cfg(not(target_os = "android"))]
@@ -140,115 +162,401 @@ impl WifiState {
             .values()
             .filter(|iface| !iface.ips.is_empty() && is_wifi_interface(&iface.name))
             .find_map(|iface| {
-                let ipv4 = iface
-                    .ips
-                    .iter()
-                    .map(|ip| ip.ip.to_string())
-                    .find(|ip_str| {
-                        !ip_str.starts_with("fe80:") && !ip_str.starts_with("169.254.")
-                    })?;
+                let ipv4 = iface.ips.iter().find_map(|ip| match ip.ip {
+                    IpAddr::V4(addr) if !addr.is_link_local() => Some(addr.to_string()),
+                    _ => None,
+                });
+                let ipv6 = iface.ips.iter().find_map(|ip| match ip.ip {
+                    IpAddr::V6(addr) if !addr.is_unicast_link_local() => Some(addr.to_string()),
+                    _ => None,
+                });
+                if ipv4.is_none() && ipv6.is_none() {
+                    return None;
+                }
 
+                let (ssid, bssid, details) = read_wifi_link(&iface.name);
                 Some(WifiState {
                     interface: iface.name.clone().into_boxed_str(),
-                    ssid: get_wifi_ssid(&iface.name).map(|s| s.into_boxed_str()),
-                    ipv4: Some(ipv4.into_boxed_str()),
+                    ssid: ssid.map(|s| s.into_boxed_str()),
+                    ipv4: ipv4.map(|s| s.into_boxed_str()),
+                    ipv6: ipv6.map(|s| s.into_boxed_str()),
+                    gateway: get_default_gateway(&iface.name).map(|s| s.into_boxed_str()),
+                    link: LinkIdentity {
+                        bssid: bssid.map(|s| s.into_boxed_str()),
+                        gateway_mac: get_gateway_mac(&iface.name).map(|s| s.into_boxed_str()),
+                    },
+                    details,
                 })
             })
     }
 }
 
-#[inline]
-fn check_is_uabc(ssid: Option<&str>) -> bool {
-    ssid.map(|s| s.contains("UABC")).unwrap_or(false)
+#[cfg(not(target_os = "android"))]
+fn remember_link(state: Option<&WifiState>) {
+    let link = state.map(|s| s.link.clone()).unwrap_or_default();
+    match LAST_LINK.lock() {
+        Ok(mut guard) => *guard = link,
+        Err(poisoned) => *poisoned.into_inner() = link,
+    }
 }
 
-fn has_internet_access() -> bool {
-    let client = match Client::builder()
-        .timeout(Duration::from_secs(CONNECTIVITY_TIMEOUT_SECS))
-        .build()
-    {
-        Ok(client) => client,
-        Err(err) => {
-            eprintln!("[network-sync] Error creando cliente HTTP para verificación: {err}");
-            return false;
-        }
+/// Perfil de red que corresponde al SSID (y, si el perfil lo exige, al BSSID
+/// o al gateway) de la conexión actual.
+fn match_network_profile(ssid: Option<&str>) -> Option<NetworkProfile> {
+    let link = match LAST_LINK.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
     };
 
-    match client.get(GENERATE_204_URL).send() {
-        Ok(response) => response.status().as_u16() == 204,
-        Err(err) => {
-            eprintln!("[network-sync] Falló generate_204: {err}");
-            false
+    profiles::find_match(&NetworkObservation {
+        ssid,
+        bssid: link.bssid.as_deref(),
+        gateway_mac: link.gateway_mac.as_deref(),
+    })
+}
+
+/// Perfil de la red evaluada por última vez (usado por `Auth` para elegir
+/// credenciales, portal y pin), buscado entre los perfiles guardados ahora.
+pub fn current_network_profile() -> Option<NetworkProfile> {
+    let ssid = match LAST_EVALUATED_SSID.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    match_network_profile(ssid.as_deref())
+}
+
+/// Diagnóstico de las sondas y, si la red la anuncia, el estado de su API de
+/// portal cautivo (RFC 8908).
+struct ConnectivityReport {
+    verdict: Verdict,
+    captive_api: Option<CaptiveApiStatus>,
+}
+
+/// Red a evaluar, copiada por quien emite. La ruta de emisión no vuelve a
+/// leer LAST_STATE: `handle_interfaces` puede estar decidiendo qué guardar en
+/// él y `std::sync::Mutex` no es reentrante.
+#[derive(Clone, Debug, Default)]
+struct NetworkSnapshot {
+    ssid: Option<String>,
+    /// Interfaz, IP y gateway; Android no los expone al plugin, así que allí
+    /// quedan vacíos.
+    addresses: LinkAddresses,
+    /// Señal, frecuencia y seguridad, cuando el backend del sistema las da.
+    wifi: Option<WifiInfo>,
+}
+
+impl NetworkSnapshot {
+    fn ssid(&self) -> Option<&str> {
+        self.ssid.as_deref()
+    }
+
+    #[cfg(not(target_os = "android"))]
+    fn from_state(state: Option<&WifiState>) -> Self {
+        state
+            .map(|state| NetworkSnapshot {
+                ssid: state.ssid.as_deref().map(str::to_string),
+                addresses: LinkAddresses {
+                    interface: Some(state.interface.to_string()),
+                    ipv4: state.ipv4.as_deref().map(str::to_string),
+                    ipv6: state.ipv6.as_deref().map(str::to_string),
+                    gateway: state.gateway.as_deref().map(str::to_string),
+                },
+                wifi: state.details.clone(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// En Android el enlace llega por el observer: RSSI y velocidad del
+    /// último evento.
+    #[cfg(target_os = "android")]
+    fn from_ssid(ssid: Option<&str>) -> Self {
+        let wifi = match ANDROID_WIFI.lock() {
+            Ok(g) => g.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        NetworkSnapshot {
+            ssid: ssid.map(str::to_string),
+            wifi,
+            ..NetworkSnapshot::default()
         }
     }
-}
 
-fn resolve_sync_network_state(ssid: Option<&str>, connected: bool, is_uabc: bool) -> SyncNetworkState {
-    let has_wifi = has_internet_access();
+    /// Última red vista por el monitor; el lock se suelta antes de evaluarla.
+    fn current() -> Self {
+        #[cfg(target_os = "android")]
+        {
+            NetworkSnapshot::from_ssid(current_ssid().as_deref())
+        }
 
-    if connected && is_uabc {
-        if has_wifi {
-            return SyncNetworkState::FineConnection;
+        #[cfg(not(target_os = "android"))]
+        {
+            let state = match LAST_STATE.lock() {
+                Ok(g) => g.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
+            };
+            NetworkSnapshot::from_state(state.as_ref())
         }
-        return SyncNetworkState::HaveCautivePortal;
     }
+}
+
+/// Señales estándar del portal: la URI anunciada por DHCP/RA y el estado de
+/// conectividad que reporta el sistema operativo.
+async fn query_captive_portal(
+    interface: Option<String>,
+) -> (Option<CaptiveApiStatus>, Option<prober::Connectivity>) {
+    // Solo NetworkManager expone ambas señales; se leen por D-Bus
+    #[cfg(target_os = "linux")]
+    let (uri, os_hint) = {
+        let (options, state) = tokio::join!(
+            async {
+                match interface.as_deref() {
+                    Some(name) => networkmanager::dhcp_options(name).await.ok(),
+                    None => None,
+                }
+            },
+            networkmanager::connectivity(),
+        );
+        let uri = options.and_then(|options| {
+            let options = options.iter().map(|(key, value)| (key.as_str(), value.as_str()));
+            captive_api::find_dhcp_captive_uri(options)
+        });
+        (uri, state.ok().and_then(captive_api::from_nm_connectivity))
+    };
+    #[cfg(not(target_os = "linux"))]
+    let (uri, os_hint) = {
+        let _ = interface;
+        (None, None)
+    };
+
+    captive_api::remember_uri(uri);
+    (captive_api::query().await, os_hint)
+}
+
+async fn probe_connectivity(interface: Option<String>) -> ConnectivityReport {
+    let config = settings::current();
+    let (verdict, (captive_api, os_hint)) =
+        tokio::join!(prober::probe(&config), query_captive_portal(interface));
+    let verdict = captive_api::apply(verdict, captive_api.as_ref(), os_hint);
 
-    if !connected && has_wifi {
-        return SyncNetworkState::MobileConnection;
+    if !verdict.is_online() {
+        let failed: Vec<String> = verdict
+            .results
+            .iter()
+            .filter(|r| r.outcome != prober::ProbeOutcome::Reachable)
+            .map(|r| format!("{:?}: {:?}", r.probe.kind, r.outcome))
+            .collect();
+        eprintln!(
+            "[network-sync] Conectividad: {:?} (confianza {:.0}%) | {}",
+            verdict.connectivity,
+            verdict.confidence * 100.0,
+            failed.join(" | ")
+        );
+    }
+    ConnectivityReport {
+        verdict,
+        captive_api,
     }
+}
+
+/// Usa `probe_connectivity` como sonda de la máquina de estados, sobre la
+/// interfaz que se está evaluando.
+struct SystemProber {
+    interface: Option<String>,
+}
+
+#[async_trait]
+impl ConnectivityProber for SystemProber {
+    type Report = ConnectivityReport;
 
-    if !connected && !has_wifi && ssid.map(|s| s.contains("UABC")).unwrap_or(false) {
-        return SyncNetworkState::MobileConnectionRequiereAuth;
+    async fn probe(&self) -> ConnectivityReport {
+        probe_connectivity(self.interface.clone()).await
     }
 
-    SyncNetworkState::InvalidConnection
+    fn is_online(report: &ConnectivityReport) -> bool {
+        report.verdict.is_online()
+    }
 }
 
-fn log_state_transition(new_state: SyncNetworkState, ssid: Option<&str>, connected: bool, is_uabc: bool) {
-    let mut guard = match LAST_SYNC_NETWORK_STATE.lock() {
-        Ok(g) => g,
+fn network_state_machine() -> NetworkStateMachine {
+    let machine =
+        NetworkStateMachine::new(SystemClock, Hysteresis::from_settings(&settings::current()));
+    machine.on_transition(|transition| {
+        eprintln!(
+            "[network-sync] Estado: {:?} → {:?} | ssid={} | causa={:?} | tras {:?}",
+            transition.from,
+            transition.to,
+            transition.ssid.as_deref().unwrap_or("<none>"),
+            transition.cause,
+            transition.time_in_previous.unwrap_or_default(),
+        );
+    });
+    machine
+}
+
+fn network_link(ssid: Option<&str>, profile: Option<&NetworkProfile>) -> Link {
+    // Los umbrales pueden cambiar en caliente desde la configuración
+    STATE_MACHINE.set_hysteresis(Hysteresis::from_settings(&settings::current()));
+    Link {
+        ssid: ssid.map(str::to_string),
+        network_profile_id: profile.map(|p| p.id.clone()),
+    }
+}
+
+/// Transición publicada hacia los suscriptores internos (monitor de autenticación).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkStateEvent {
+    pub state: SyncNetworkState,
+    pub ssid: Option<String>,
+}
+
+/// Entrega un evento y devuelve `false` cuando el receptor ya no existe.
+type StateSubscriber = Box<dyn Fn(&NetworkStateEvent) -> bool + Send>;
+
+/// Reenvía cada estado evaluado por `emit_network_status` al canal `tx`,
+/// envuelto con `wrap` para que el receptor pueda mezclarlo con sus propias
+/// señales. La suscripción se descarta cuando el receptor deja de existir.
+pub fn subscribe_network_state<T: Send + 'static>(
+    tx: UnboundedSender<T>,
+    wrap: fn(NetworkStateEvent) -> T,
+) {
+    let subscriber: StateSubscriber = Box::new(move |event| tx.send(wrap(event.clone())).is_ok());
+    match STATE_SUBSCRIBERS.lock() {
+        Ok(mut guard) => guard.push(subscriber),
+        Err(poisoned) => poisoned.into_inner().push(subscriber),
+    }
+}
+
+/// Último estado evaluado, si el monitor de red ya emitió alguno.
+pub fn current_network_state() -> Option<SyncNetworkState> {
+    STATE_MACHINE.state()
+}
+
+/// Entrega un estado evaluado a los suscriptores de `subscribe_network_state`.
+pub fn publish_network_state(state: SyncNetworkState, ssid: Option<&str>) {
+    let event = NetworkStateEvent {
+        state,
+        ssid: ssid.map(str::to_string),
+    };
+
+    let mut subscribers = match STATE_SUBSCRIBERS.lock() {
+        Ok(guard) => guard,
         Err(poisoned) => poisoned.into_inner(),
     };
+    subscribers.retain(|deliver| deliver(&event));
+}
 
-    let previous = *guard;
-    if previous != Some(new_state) {
-        println!(
-            "[network-sync] Estado: {:?} → {:?} | ssid={} | connected={} | is_uabc={}",
-            previous,
-            Some(new_state),
-            ssid.unwrap_or("<none>"),
-            connected,
-            is_uabc,
-        );
-        *guard = Some(new_state);
+async fn evaluate_network_state(
+    network: &NetworkSnapshot,
+) -> (SyncNetworkState, Option<NetworkProfile>, ConnectivityReport) {
+    let ssid = network.ssid();
+    let profile = match_network_profile(ssid);
+    let link = network_link(ssid, profile.as_ref());
+    let prober = SystemProber {
+        interface: network.addresses.interface.clone(),
+    };
+    let (step, report) = STATE_MACHINE.evaluate(link, &prober).await;
+    if let Some(delay) = step.recheck_after {
+        schedule_recheck(delay);
+    }
+
+    remember_evaluated_ssid(ssid);
+    (step.state, profile, report)
+}
+
+/// Vuelve a evaluar la red tras `delay` mientras la máquina de estados
+/// confirma una caída. Sin monitor activo (p. ej. `cima-sync status`) no hay
+/// a quién avisar y el fallo queda contado para la próxima evaluación.
+fn schedule_recheck(delay: Duration) {
+    let sink = match MONITOR_SINK.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    let Some(sink) = sink else {
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        emit_network_status(sink.as_ref(), &NetworkSnapshot::current()).await;
+    });
+}
+
+fn set_monitor_sink(sink: &Arc<dyn EventSink>) {
+    let sink = Some(Arc::clone(sink));
+    match MONITOR_SINK.lock() {
+        Ok(mut guard) => *guard = sink,
+        Err(poisoned) => *poisoned.into_inner() = sink,
     }
 }
 
-fn create_status_payload(ssid: Option<&str>) -> serde_json::Value {
-    let is_uabc = check_is_uabc(ssid);
-    let connected = ssid.is_some();
-    let network_state = resolve_sync_network_state(ssid, connected, is_uabc);
+fn remember_evaluated_ssid(ssid: Option<&str>) {
+    let ssid = ssid.map(str::to_string);
+    match LAST_EVALUATED_SSID.lock() {
+        Ok(mut guard) => *guard = ssid,
+        Err(poisoned) => *poisoned.into_inner() = ssid,
+    }
+}
 
-    log_state_transition(network_state, ssid, connected, is_uabc);
+async fn create_network_status(network: &NetworkSnapshot) -> NetworkStatus {
+    let (network_state, profile, report) = evaluate_network_state(network).await;
+    network_status(network, network_state, profile.as_ref(), Some(&report))
+}
 
-    serde_json::json!({
-        "connected": connected,
-        "ssid": ssid,
-        "is_uabc": is_uabc,
-        "network_state": network_state.as_key(),
-        "status_text": network_state.as_status_text()
-    })
+/// `report` es `None` cuando el estado viene del sistema operativo sin sondear.
+fn network_status(
+    network: &NetworkSnapshot,
+    network_state: SyncNetworkState,
+    profile: Option<&NetworkProfile>,
+    report: Option<&ConnectivityReport>,
+) -> NetworkStatus {
+    let ssid = network.ssid();
+    let verdict = report.map(|r| &r.verdict);
+    let captive_api = report.and_then(|r| r.captive_api.as_ref());
+
+    NetworkStatus {
+        connected: ssid.is_some(),
+        ssid: ssid.map(str::to_string),
+        is_uabc: profile.is_some(),
+        network_profile_id: profile.map(|p| p.id.clone()),
+        network_state,
+        status_text: network_state.as_status_text().to_string(),
+        connectivity: verdict.map(|v| v.connectivity),
+        connectivity_confidence: verdict.map(|v| v.confidence),
+        captive_portal_url: captive_api.and_then(|s| s.user_portal_url.clone()),
+        session_seconds_remaining: captive_api.and_then(|s| s.seconds_remaining),
+        timestamp_ms: history::now_ms(),
+        ..NetworkStatus::default()
+    }
+    .with_link(network.addresses.clone(), network.wifi.clone())
+}
+
+pub async fn get_current_network_status() -> NetworkStatus {
+    // Se copia la red para no retener el lock durante la verificación HTTP
+    create_network_status(&NetworkSnapshot::current()).await
+}
+
+/// Toma una instantánea de las interfaces sin iniciar el monitor (uso desde la CLI).
+#[cfg(not(target_os = "android"))]
+pub fn refresh_network_state() {
+    match list_interfaces() {
+        Ok(interfaces) => {
+            let state = WifiState::from_interfaces(&interfaces);
+            remember_link(state.as_ref());
+            store_state(state);
+        }
+        Err(err) => eprintln!("[network-sync] Error listando interfaces: {err}"),
+    }
 }
 
-pub fn get_current_network_status() -> serde_json::Value {
+/// Último SSID conocido por el monitor de red (usado para elegir el driver del portal).
+pub fn current_ssid() -> Option<String> {
     #[cfg(target_os = "android")]
     {
         let guard = match ANDROID_SSID.lock() {
             Ok(g) => g,
             Err(poisoned) => poisoned.into_inner(),
         };
-        let ssid = guard.as_deref();
-        return create_status_payload(ssid);
+        return guard.as_deref().map(str::to_string);
     }
 
     #[cfg(not(target_os = "android"))]
@@ -257,218 +565,130 @@ pub fn get_current_network_status() -> serde_json::Value {
             Ok(g) => g,
             Err(poisoned) => poisoned.into_inner(),
         };
-        let ssid = guard.as_ref().and_then(|state| state.ssid.as_deref());
-        create_status_payload(ssid)
+        guard
+            .as_ref()
+            .and_then(|state| state.ssid.as_deref())
+            .map(str::to_string)
     }
 }
 
-#[cfg(any(target_os = "windows", target_os = "macos"))]
-fn extract_ssid_from_line(line: &str) -> Option<String> {
-    line.find(':')
-        .map(|pos| line[pos + 1..].trim().to_string())
-        .filter(|s| !s.is_empty())
-}
+/// SSID, BSSID y datos del enlace según el primer backend que responda: el
+/// nativo del sistema (NetworkManager, WLAN API) y después las herramientas
+/// de línea de comandos.
+#[cfg(not(target_os = "android"))]
+fn read_wifi_link(interface_name: &str) -> (Option<String>, Option<String>, Option<WifiInfo>) {
+    let unavailable = || (Some(SSID_UNAVAILABLE.to_string()), None, None);
+    let Some(safe_name) = get_safe_interface_name(interface_name) else {
+        return unavailable();
+    };
 
-#[cfg(target_os = "linux")]
-fn parse_nmcli_wifi_line(line: &str) -> Option<(&str, String, &str)> {
-    let first = line.find(':')?;
-    let last = line.rfind(':')?;
-    if first == last {
-        return None;
+    for backend in WIFI_BACKENDS.iter() {
+        match backend.link(&safe_name) {
+            Ok(Some(info)) => return (info.ssid.clone(), info.bssid.clone(), Some(info)),
+            Ok(None) => return (None, None, None),
+            Err(err) => log_backend_failure(backend.name(), &err),
+        }
     }
 
-    let active = line[..first].trim();
-    let ssid = line[first + 1..last]
-        .replace(r#"\:"#, ":")
-        .replace(r#"\\"#, r#"\"#)
-        .trim()
-        .to_string();
-    let device = line[last + 1..].trim();
-
-    Some((active, ssid, device))
+    unavailable()
 }
 
-#[cfg(target_os = "windows")]
-fn parse_ssid_line(line: &str) -> Option<String> {
-    let line_lower = line.to_lowercase();
-    if (line_lower.contains("ssid") && !line_lower.contains("bssid"))
-        || line_lower.contains("nombre de red")
-        || line_lower.contains("network name")
-    {
-        extract_ssid_from_line(line)
-            .filter(|s| !matches!(s.as_str(), "N/A" | "Ninguno" | "None"))
-    } else {
-        None
+/// Cada backend que falla se reporta una sola vez; se sigue intentando en
+/// cada cambio de red por si vuelve a estar disponible.
+#[cfg(not(target_os = "android"))]
+fn log_backend_failure(name: &'static str, err: &str) {
+    let mut reported = match FAILED_WIFI_BACKENDS.lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if reported.insert(name) {
+        eprintln!("[network-sync] {name} no disponible, probando el siguiente backend: {err}");
     }
 }
 
 #[cfg(not(target_os = "android"))]
-fn get_wifi_ssid(interface_name: &str) -> Option<String> {
-    let safe_name = match get_safe_interface_name(interface_name) {
-        Some(name) => name,
-        None => return Some("SSID no disponible".to_string()),
-    };
+fn wifi_backends() -> Vec<Box<dyn WifiBackend>> {
+    let mut backends: Vec<Box<dyn WifiBackend>> = Vec::new();
 
     #[cfg(target_os = "linux")]
     {
-        if let Ok(output) = Command::new("iwgetid")
-            .arg(&safe_name)
-            .arg("--raw")
-            .output()
-        {
-            if output.status.success() {
-                let ssid = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !ssid.is_empty() {
-                    return Some(ssid);
-                }
-            }
-        }
-
-        if let Ok(output) = Command::new("nmcli")
-            .args(["-t", "-f", "active,ssid,device", "dev", "wifi"])
-            .output()
-        {
-            if output.status.success() {
-                for line in String::from_utf8_lossy(&output.stdout).lines() {
-                    if let Some((active, ssid, device)) = parse_nmcli_wifi_line(line) {
-                        if active == "yes" && device == safe_name && !ssid.is_empty() {
-                            return Some(ssid);
-                        }
-                    }
-                }
-            }
-        }
+        backends.push(Box::new(networkmanager::NetworkManagerBackend));
+        backends.push(Box::new(LinuxCommands::new(SystemCommands)));
     }
 
     #[cfg(target_os = "windows")]
     {
-        match Command::new("netsh")
-            .args(["wlan", "show", "interfaces"])
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let mut current_interface = false;
-
-                    for line in stdout.lines() {
-                        let line_lower = line.to_lowercase();
-                        if line_lower.contains("nombre") || line_lower.contains("name") {
-                            current_interface = line.contains(&safe_name as &str);
-                            continue;
-                        }
-                        if current_interface {
-                            if let Some(ssid) = parse_ssid_line(line) {
-                                return Some(ssid);
-                            }
-                        }
-                    }
+        backends.push(Box::new(wlanapi::WlanApiBackend));
+        backends.push(Box::new(WindowsCommands::new(SystemCommands)));
+    }
 
-                    for line in stdout.lines() {
-                        if let Some(ssid) = parse_ssid_line(line) {
-                            return Some(ssid);
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("[network-sync] Error netsh: {}", e);
-            }
-        }
+    #[cfg(target_os = "macos")]
+    backends.push(Box::new(MacCommands::new(SystemCommands)));
 
-        let escaped_name = safe_name.replace("'", "''");
-        match Command::new("powershell")
-            .args([
-                "-NoProfile",
-                "-NonInteractive",
-                "-Command",
-                &format!(
-                    "(Get-NetConnectionProfile | Where-Object {{ $_.InterfaceAlias -like '*{}*' }}).Name",
-                    escaped_name
-                ),
-            ])
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let ssid = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    if !ssid.is_empty() {
-                        return Some(ssid);
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("[network-sync] Error PowerShell: {}", e);
-            }
-        }
+    backends
+}
+
+/// Gateway IPv4 por defecto de la interfaz, leído de las tablas del kernel.
+#[cfg(not(target_os = "android"))]
+fn get_default_gateway(interface_name: &str) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+        parse_default_gateway(&routes, interface_name)
     }
 
-    #[cfg(target_os = "macos")]
+    #[cfg(not(target_os = "linux"))]
     {
-        for airport_path in &[
-            "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport",
-            "/usr/sbin/airport",
-        ] {
-            if let Ok(output) = Command::new(airport_path).arg("-I").output() {
-                if output.status.success() {
-                    for line in String::from_utf8_lossy(&output.stdout).lines() {
-                        if line.trim().starts_with("SSID:") {
-                            if let Some(ssid) = extract_ssid_from_line(line) {
-                                return Some(ssid);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let _ = interface_name;
+        None
+    }
+}
 
-        let valid_interfaces: Vec<&str> = [safe_name.as_str(), "en0", "en1"]
-            .iter()
-            .filter(|iface| get_safe_interface_name(iface).is_some())
-            .copied()
-            .collect();
+/// MAC del gateway por defecto de la interfaz, leída de las tablas del kernel.
+#[cfg(not(target_os = "android"))]
+fn get_gateway_mac(interface_name: &str) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let gateway = get_default_gateway(interface_name)?;
+        let arp = std::fs::read_to_string("/proc/net/arp").ok()?;
+        parse_arp_mac(&arp, &gateway, interface_name)
+    }
 
-        for iface in valid_interfaces {
-            if let Ok(output) = Command::new("networksetup")
-                .args(["-getairportnetwork", iface])
-                .output()
-            {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    if let Some(ssid) = extract_ssid_from_line(&stdout) {
-                        if !ssid.contains("You are not associated") && !ssid.contains("not associated") {
-                            return Some(ssid);
-                        }
-                    }
-                }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = interface_name;
+        None
+    }
+}
+
+/// `/proc/net/route` guarda el gateway como hex en orden little-endian.
+#[cfg(target_os = "linux")]
+fn parse_default_gateway(routes: &str, interface_name: &str) -> Option<String> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [iface, "00000000", gateway, ..] if *iface == interface_name => {
+                let bytes = u32::from_str_radix(gateway, 16).ok()?.to_le_bytes();
+                Some(std::net::Ipv4Addr::from(bytes).to_string())
             }
+            _ => None,
         }
+    })
+}
 
-        if let Ok(output) = Command::new("system_profiler")
-            .args(["SPAirPortDataType"])
-            .output()
-        {
-            if output.status.success() {
-                let mut in_current = false;
-                for line in String::from_utf8_lossy(&output.stdout).lines() {
-                    let trimmed = line.trim();
-                    if trimmed.starts_with("Current Network Information:") {
-                        in_current = true;
-                    } else if in_current {
-                        if trimmed.starts_with("Network Name:") || trimmed.starts_with("SSID:") {
-                            if let Some(ssid) = extract_ssid_from_line(trimmed) {
-                                return Some(ssid);
-                            }
-                        } else if trimmed.starts_with("---") {
-                            in_current = false;
-                        }
-                    }
-                }
+#[cfg(target_os = "linux")]
+fn parse_arp_mac(arp: &str, gateway: &str, interface_name: &str) -> Option<String> {
+    arp.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [ip, _, _, mac, _, iface]
+                if *ip == gateway && *iface == interface_name && *mac != "00:00:00:00:00:00" =>
+            {
+                Some(mac.to_string())
             }
+            _ => None,
         }
-    }
-
-    Some("SSID no disponible".to_string())
+    })
 }
 
 #[inline]
@@ -509,12 +729,33 @@ fn is_wifi_interface(name: &str) -> bool {
     }
 }
 
-fn emit_network_status(app: &tauri::AppHandle, ssid: Option<&str>) {
-    let payload = create_status_payload(ssid);
-    let _ = app.emit("network-status", payload);
+impl<R: tauri::Runtime> EventSink for tauri::AppHandle<R> {
+    fn emit_event(&self, event: &AppEvent) {
+        let _ = self.emit(event.name(), event.envelope());
+    }
+}
+
+async fn emit_network_status(app: &dyn EventSink, network: &NetworkSnapshot) {
+    let (network_state, profile, report) = evaluate_network_state(network).await;
+    let status = network_status(network, network_state, profile.as_ref(), Some(&report));
+    emit_status_events(app, status, profile.as_ref());
+    publish_network_state(network_state, network.ssid());
+}
 
-    if check_is_uabc(ssid) {
-        let _ = app.emit("uabc-detected", ());
+/// `network-status` y, si la red coincide con un perfil, `uabc-detected`.
+fn emit_status_events(
+    app: &dyn EventSink,
+    status: NetworkStatus,
+    profile: Option<&NetworkProfile>,
+) {
+    let ssid = status.ssid.clone();
+    app.emit_event(&AppEvent::NetworkStatus(Box::new(status)));
+
+    if let Some(profile) = profile {
+        app.emit_event(&AppEvent::UabcDetected(UabcDetected {
+            ssid,
+            network_profile_id: profile.id.clone(),
+        }));
     }
 }
 
@@ -522,41 +763,56 @@ fn emit_network_status(app: &tauri::AppHandle, ssid: Option<&str>) {
 /// Si la red es UABC y hay internet según el sistema operativo, emitimos FineConnection
 /// sin hacer ningún HTTP adicional. Si no cumple las condiciones, cae al check normal.
 #[cfg(target_os = "android")]
-fn emit_with_android_hint(app: &tauri::AppHandle, ssid: Option<&str>, os_validated_internet: bool) {
-    let is_uabc = check_is_uabc(ssid);
-    let connected = ssid.is_some();
+async fn emit_with_android_hint(
+    app: &tauri::AppHandle,
+    ssid: Option<&str>,
+    os_validated_internet: bool,
+) {
+    let profile = match_network_profile(ssid);
+    let link = network_link(ssid, profile.as_ref());
 
-    let network_state = if os_validated_internet && connected && is_uabc {
+    let (step, report) = if os_validated_internet && ssid.is_some() && profile.is_some() {
         // El SO ya confirmó internet + red UABC → directamente autenticado
-        SyncNetworkState::FineConnection
+        (STATE_MACHINE.observe(link, true), None)
     } else {
-        // Verificación completa (HTTP check)
-        resolve_sync_network_state(ssid, connected, is_uabc)
+        // Verificación completa (sondas en paralelo)
+        let prober = SystemProber { interface: None };
+        let (step, report) = STATE_MACHINE.evaluate(link, &prober).await;
+        (step, Some(report))
     };
+    if let Some(delay) = step.recheck_after {
+        schedule_recheck(delay);
+    }
+    let network_state = step.state;
 
-    log_state_transition(network_state, ssid, connected, is_uabc);
-
-    let payload = serde_json::json!({
-        "connected": connected,
-        "ssid": ssid,
-        "is_uabc": is_uabc,
-        "network_state": network_state.as_key(),
-        "status_text": network_state.as_status_text()
-    });
+    remember_evaluated_ssid(ssid);
 
-    let _ = app.emit("network-status", payload);
-    if is_uabc {
-        let _ = app.emit("uabc-detected", ());
-    }
+    let network = NetworkSnapshot::from_ssid(ssid);
+    let status = network_status(&network, network_state, profile.as_ref(), report.as_ref());
+    emit_status_events(app, status, profile.as_ref());
+    publish_network_state(network_state, ssid);
 }
 
 pub fn start_network_monitor(app: tauri::AppHandle) {
     MONITOR_ONCE.call_once(|| {
+        let sink: Arc<dyn EventSink> = Arc::new(app.clone());
+        set_monitor_sink(&sink);
+
         #[cfg(target_os = "android")]
         tauri::async_runtime::spawn(android_monitor_loop(app));
 
         #[cfg(not(target_os = "android"))]
-        thread::spawn(move || desktop_monitor_loop(app));
+        thread::spawn(move || desktop_monitor_loop(sink));
+    });
+}
+
+/// Inicia el monitor de escritorio sin ventana de Tauri; los eventos se
+/// entregan a `sink`.
+#[cfg(not(target_os = "android"))]
+pub fn start_headless_network_monitor(sink: Arc<dyn EventSink>) {
+    MONITOR_ONCE.call_once(|| {
+        set_monitor_sink(&sink);
+        thread::spawn(move || desktop_monitor_loop(sink));
     });
 }
 
@@ -580,17 +836,14 @@ async fn android_monitor_loop(app: tauri::AppHandle) {
     {
         let wifi = app.wifi_interface().clone();
         match tauri::async_runtime::spawn_blocking(move || wifi.start_observing()).await {
-            Ok(Ok(_)) => println!("[network-sync] Observer WiFi Android activado"),
+            Ok(Ok(_)) => eprintln!("[network-sync] Observer WiFi Android activado"),
             Ok(Err(e)) => eprintln!("[network-sync] Error activando observer: {e}"),
             Err(e) => eprintln!("[network-sync] Panic en start_observing: {e}"),
         }
     }
 
     // Estado inicial mientras llega el primer evento
-    {
-        let app_c = app.clone();
-        tauri::async_runtime::spawn_blocking(move || emit_network_status(&app_c, None)).await.ok();
-    }
+    emit_network_status(&app, &NetworkSnapshot::default()).await;
 
     let mut rx = app.wifi_events();
 
@@ -611,11 +864,7 @@ async fn android_monitor_loop(app: tauri::AppHandle) {
                     Ok(e) => e,
                     Err(RecvError::Lagged(n)) => {
                         eprintln!("[network-sync] WiFi bus lagged {n} eventos — re-emitiendo estado");
-                        let ssid = last_ssid.clone();
-                        let app_c = app.clone();
-                        tauri::async_runtime::spawn_blocking(move || {
-                            emit_network_status(&app_c, ssid.as_deref())
-                        }).await.ok();
+                        emit_network_status(&app, &NetworkSnapshot::from_ssid(last_ssid.as_deref())).await;
                         continue;
                     }
                     Err(RecvError::Closed) => {
@@ -625,6 +874,22 @@ async fn android_monitor_loop(app: tauri::AppHandle) {
                     }
                 };
 
+                // RSSI y velocidad se guardan aunque no haga falta reevaluar
+                let wifi = match event.event.as_str() {
+                    "capabilitiesChanged" => Some(event.ssid.as_ref().map(|ssid| WifiInfo {
+                        ssid: Some(ssid.clone()),
+                        rssi_dbm: event.rssi,
+                        link_speed_mbps: event.link_speed.and_then(|s| u32::try_from(s).ok()),
+                        ..WifiInfo::default()
+                    })),
+                    "lost" | "unavailable" => Some(None),
+                    _ => None,
+                };
+                if let Some(wifi) = wifi {
+                    let mut guard = ANDROID_WIFI.lock().unwrap_or_else(|p| p.into_inner());
+                    *guard = wifi;
+                }
+
                 let (new_ssid, needs_eval) = match event.event.as_str() {
                     "available" => (last_ssid.clone(), true),
                     "lost" | "unavailable" => (None, true),
@@ -654,25 +919,17 @@ async fn android_monitor_loop(app: tauri::AppHandle) {
                     *guard = new_ssid.as_deref().map(Box::from);
                 }
 
-                let ssid_owned = new_ssid.clone();
-                let app_c = app.clone();
                 let android_validated = event.has_validated.unwrap_or(false)
                     && event.has_internet.unwrap_or(false);
 
-                tauri::async_runtime::spawn_blocking(move || {
-                    emit_with_android_hint(&app_c, ssid_owned.as_deref(), android_validated);
-                }).await.ok();
+                emit_with_android_hint(&app, new_ssid.as_deref(), android_validated).await;
             }
 
             // ── RAMA 2: heartbeat de polling ──────────────────────────────
             _ = poll_ticker.tick() => {
                 // Re-verificar el estado actual sin importar si llegó o no un evento.
                 // Sirve como red de seguridad contra eventos perdidos o bugs del SO.
-                let ssid = last_ssid.clone();
-                let app_c = app.clone();
-                tauri::async_runtime::spawn_blocking(move || {
-                    emit_network_status(&app_c, ssid.as_deref());
-                }).await.ok();
+                emit_network_status(&app, &NetworkSnapshot::from_ssid(last_ssid.as_deref())).await;
             }
         }
     }
@@ -683,13 +940,16 @@ async fn android_monitor_loop(app: tauri::AppHandle) {
 // -------------------------------------------------------
 
 #[cfg(not(target_os = "android"))]
-fn desktop_monitor_loop(app: tauri::AppHandle) {
+fn desktop_monitor_loop(app: Arc<dyn EventSink>) {
     let is_first_update = Arc::new(Mutex::new(true));
     let is_first_clone = Arc::clone(&is_first_update);
     let app_clone = app.clone();
 
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    watch_access_point_changes(app.clone(), Arc::clone(&is_first_update));
+
     match watch_interfaces(move |update: Update| {
-        handle_network_update(update, &is_first_clone, &app_clone);
+        handle_network_update(update, &is_first_clone, app_clone.as_ref());
     }) {
         Ok(_handle) => {
             loop {
@@ -698,12 +958,11 @@ fn desktop_monitor_loop(app: tauri::AppHandle) {
         }
         Err(err) => {
             eprintln!("[network-sync] Error al iniciar el monitor de red: {err}");
-            let _ = app.emit("network-status", serde_json::json!({
-                "connected": false,
-                "ssid": null,
-                "is_uabc": false,
-                "error": format!("No se pudo iniciar el monitor de red: {}", err)
-            }));
+            let status = NetworkStatus::from_error(
+                format!("No se pudo iniciar el monitor de red: {}", err),
+                history::now_ms(),
+            );
+            app.emit_event(&AppEvent::NetworkStatus(Box::new(status)));
 
             loop {
                 thread::sleep(Duration::from_secs(30));
@@ -713,7 +972,7 @@ fn desktop_monitor_loop(app: tauri::AppHandle) {
                 let app_retry = app.clone();
 
                 match watch_interfaces(move |update: Update| {
-                    handle_network_update(update, &is_first_retry_clone, &app_retry);
+                    handle_network_update(update, &is_first_retry_clone, app_retry.as_ref());
                 }) {
                     Ok(_new_handle) => {
                         eprintln!("[network-sync] Monitor de red reiniciado exitosamente");
@@ -730,13 +989,75 @@ fn desktop_monitor_loop(app: tauri::AppHandle) {
     }
 }
 
+/// netwatcher solo avisa de cambios de IP; un roaming o un cambio de red en la
+/// misma subred llega como señal de NetworkManager o notificación de la WLAN
+/// API. Se atienden en un hilo propio porque `handle_interfaces` bloquea
+/// mientras evalúa la red.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn watch_access_point_changes(app: Arc<dyn EventSink>, is_first_update: Arc<Mutex<bool>>) {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    #[cfg(target_os = "linux")]
+    tauri::async_runtime::spawn(async move {
+        let notify = move || {
+            let _ = tx.send(());
+        };
+        if let Err(err) = networkmanager::watch_access_points(notify).await {
+            eprintln!("[network-sync] Sin señales de NetworkManager: {err}");
+        }
+    });
+
+    #[cfg(target_os = "windows")]
+    {
+        // El callback corre en hilos del servicio WLAN: el Sender necesita Sync
+        let tx = Mutex::new(tx);
+        let notify = move || {
+            let tx = match tx.lock() {
+                Ok(g) => g,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let _ = tx.send(());
+        };
+        if let Err(err) = wlanapi::watch_connections(notify) {
+            eprintln!("[network-sync] Sin notificaciones de la WLAN API: {err}");
+        }
+    }
+
+    thread::spawn(move || {
+        for () in rx {
+            match list_interfaces() {
+                Ok(interfaces) => handle_interfaces(&interfaces, &is_first_update, app.as_ref()),
+                Err(err) => eprintln!("[network-sync] Error listando interfaces: {err}"),
+            }
+        }
+    });
+}
+
+/// El callback de netwatcher corre en su propio hilo, fuera del runtime: ahí
+/// se espera la evaluación para que los eventos salgan en el orden observado.
+#[cfg(not(target_os = "android"))]
+fn emit_network_status_blocking(app: &dyn EventSink, state: Option<&WifiState>) {
+    let network = NetworkSnapshot::from_state(state);
+    tauri::async_runtime::block_on(emit_network_status(app, &network));
+}
+
 #[cfg(not(target_os = "android"))]
 fn handle_network_update(
     update: Update,
     is_first_update: &Arc<Mutex<bool>>,
-    app: &tauri::AppHandle,
+    app: &dyn EventSink,
+) {
+    handle_interfaces(&update.interfaces, is_first_update, app);
+}
+
+#[cfg(not(target_os = "android"))]
+fn handle_interfaces(
+    interfaces: &HashMap<u32, Interface>,
+    is_first_update: &Arc<Mutex<bool>>,
+    app: &dyn EventSink,
 ) {
-    let current_state = WifiState::from_interfaces(&update.interfaces);
+    let current_state = WifiState::from_interfaces(interfaces);
+    remember_link(current_state.as_ref());
 
     let is_first = match is_first_update.lock() {
         Ok(mut guard) => std::mem::replace(&mut *guard, false),
@@ -747,45 +1068,57 @@ fn handle_network_update(
     };
 
     if is_first {
-        emit_network_status(app, current_state.as_ref().and_then(|s| s.ssid.as_deref()));
-        match LAST_STATE.lock() {
-            Ok(mut guard) => *guard = current_state,
-            Err(poisoned) => *poisoned.into_inner() = current_state,
-        }
+        emit_network_status_blocking(app, current_state.as_ref());
+        store_state(current_state);
         return;
     }
 
-    let mut state_guard = match LAST_STATE.lock() {
-        Ok(guard) => guard,
+    // Se copia y se suelta el lock: la evaluación de abajo consulta la red
+    // (portal cautivo, perfiles) y no debe competir por LAST_STATE.
+    let previous_state = match LAST_STATE.lock() {
+        Ok(guard) => guard.clone(),
         Err(poisoned) => {
             eprintln!("[network-sync] Lock envenenado en LAST_STATE, recuperando");
-            poisoned.into_inner()
+            poisoned.into_inner().clone()
         }
     };
-    let previous_state = state_guard.clone();
 
     match (&previous_state, &current_state) {
-        (Some(prev), Some(curr)) if prev.ssid != curr.ssid || prev.ipv4 != curr.ipv4 => {
+        (Some(prev), Some(curr))
+            if prev.ssid != curr.ssid || prev.ipv4 != curr.ipv4 || prev.link != curr.link =>
+        {
             let mut updated_state = curr.clone();
             if updated_state.ssid.is_none() {
                 thread::sleep(Duration::from_millis(SSID_RETRY_DELAY_MS));
-                updated_state.ssid = get_wifi_ssid(&curr.interface).map(|s| s.into_boxed_str());
+                let (ssid, bssid, details) = read_wifi_link(&curr.interface);
+                updated_state.ssid = ssid.map(|s| s.into_boxed_str());
+                updated_state.link.bssid = bssid.map(|s| s.into_boxed_str());
+                updated_state.details = details;
             }
-            if prev.ssid != updated_state.ssid {
-                emit_network_status(app, updated_state.ssid.as_deref());
+            // Un roaming cambia el BSSID sin cambiar el SSID y puede cambiar de perfil
+            if prev.ssid != updated_state.ssid || prev.link.bssid != updated_state.link.bssid {
+                emit_network_status_blocking(app, Some(&updated_state));
             }
-            *state_guard = Some(updated_state);
+            store_state(Some(updated_state));
         }
         (None, Some(curr)) => {
             if curr.ssid.is_some() {
-                emit_network_status(app, curr.ssid.as_deref());
+                emit_network_status_blocking(app, Some(curr));
             }
-            *state_guard = current_state;
+            store_state(current_state);
         }
         (Some(_), None) => {
-            emit_network_status(app, None);
-            *state_guard = None;
+            emit_network_status_blocking(app, None);
+            store_state(None);
         }
         _ => {}
     }
 }
+
+#[cfg(not(target_os = "android"))]
+fn store_state(state: Option<WifiState>) {
+    match LAST_STATE.lock() {
+        Ok(mut guard) => *guard = state,
+        Err(poisoned) => *poisoned.into_inner() = state,
+    }
+}
@@ -0,0 +1,251 @@
+//! Backend de Linux: consulta el enlace WiFi a NetworkManager por D-Bus.
+//!
+//! Evita lanzar `iwgetid`/`nmcli` y parsear su texto (que cambia con el idioma
+//! y se rompe con SSIDs que contienen `:`), y permite suscribirse a los
+//! cambios del punto de acceso en lugar de sondear. Si NetworkManager no está
+//! disponible, `network_sync` vuelve a las herramientas de línea de comandos.
+
+use futures_util::stream::{self, BoxStream, StreamExt};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use tokio::sync::OnceCell;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+use zbus::{Connection, proxy};
+
+use crate::network_controller::profiles;
+use crate::network_controller::wifi::{self, WifiInfo, WifiSecurity};
+use crate::network_controller::wifi_backend::WifiBackend;
+
+/// NM_DEVICE_TYPE_WIFI
+const DEVICE_TYPE_WIFI: u32 = 2;
+
+lazy_static! {
+    static ref SYSTEM_BUS: OnceCell<Connection> = OnceCell::new();
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait NetworkManager {
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    fn get_device_by_ip_iface(&self, iface: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// `NMConnectivityState`
+    #[zbus(property)]
+    fn connectivity(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Device {
+    #[zbus(property)]
+    fn device_type(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn dhcp4_config(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn dhcp6_config(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.DHCP4Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Dhcp4Config {
+    #[zbus(property)]
+    fn options(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.DHCP6Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Dhcp6Config {
+    #[zbus(property)]
+    fn options(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device.Wireless",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Wireless {
+    #[zbus(property)]
+    fn active_access_point(&self) -> zbus::Result<OwnedObjectPath>;
+
+    /// Kbit/s
+    #[zbus(property)]
+    fn bitrate(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.AccessPoint",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait AccessPoint {
+    #[zbus(property)]
+    fn ssid(&self) -> zbus::Result<Vec<u8>>;
+
+    #[zbus(property)]
+    fn hw_address(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn strength(&self) -> zbus::Result<u8>;
+
+    #[zbus(property)]
+    fn frequency(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn wpa_flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn rsn_flags(&self) -> zbus::Result<u32>;
+}
+
+async fn system_bus() -> zbus::Result<Connection> {
+    SYSTEM_BUS
+        .get_or_try_init(Connection::system)
+        .await
+        .cloned()
+}
+
+/// Punto de acceso al que está asociada `interface`. `Ok(None)` si la
+/// interfaz no es WiFi o no está asociada.
+pub async fn wifi_info(interface: &str) -> zbus::Result<Option<WifiInfo>> {
+    let bus = system_bus().await?;
+    let path = NetworkManagerProxy::new(&bus)
+        .await?
+        .get_device_by_ip_iface(interface)
+        .await?;
+
+    let device = DeviceProxy::builder(&bus)
+        .path(path.clone())?
+        .build()
+        .await?;
+    if device.device_type().await? != DEVICE_TYPE_WIFI {
+        return Ok(None);
+    }
+
+    let wireless = WirelessProxy::builder(&bus).path(path)?.build().await?;
+    let ap_path = wireless.active_access_point().await?;
+    let bitrate = wireless.bitrate().await?;
+    if ap_path.as_str() == "/" {
+        return Ok(None);
+    }
+
+    let ap = AccessPointProxy::builder(&bus)
+        .path(ap_path)?
+        .build()
+        .await?;
+    let bssid = ap.hw_address().await?;
+
+    Ok(Some(WifiInfo {
+        interface: interface.to_string(),
+        ssid: wifi::decode_ssid(&ap.ssid().await?),
+        bssid: profiles::normalize_mac(&bssid).map(|_| bssid),
+        signal: Some(ap.strength().await?),
+        rssi_dbm: None,
+        link_speed_mbps: Some(bitrate / 1000).filter(|&rate| rate > 0),
+        frequency_mhz: Some(ap.frequency().await?),
+        security: WifiSecurity::from_nm_flags(
+            ap.flags().await?,
+            ap.wpa_flags().await?,
+            ap.rsn_flags().await?,
+        ),
+    }))
+}
+
+/// Resultado de la sonda de conectividad del propio NetworkManager
+/// (`NMConnectivityState`).
+pub async fn connectivity() -> zbus::Result<u32> {
+    let bus = system_bus().await?;
+    NetworkManagerProxy::new(&bus).await?.connectivity().await
+}
+
+/// Opciones DHCP4 y DHCP6 que recibió `interface`, con sus valores como texto.
+pub async fn dhcp_options(interface: &str) -> zbus::Result<Vec<(String, String)>> {
+    let bus = system_bus().await?;
+    let path = NetworkManagerProxy::new(&bus)
+        .await?
+        .get_device_by_ip_iface(interface)
+        .await?;
+    let device = DeviceProxy::builder(&bus).path(path)?.build().await?;
+
+    let mut options = HashMap::new();
+    // Sin concesión DHCP la ruta es "/"
+    let dhcp4 = device.dhcp4_config().await?;
+    if dhcp4.as_str() != "/" {
+        let config = Dhcp4ConfigProxy::builder(&bus).path(dhcp4)?.build().await?;
+        options.extend(config.options().await?);
+    }
+    let dhcp6 = device.dhcp6_config().await?;
+    if dhcp6.as_str() != "/" {
+        let config = Dhcp6ConfigProxy::builder(&bus).path(dhcp6)?.build().await?;
+        options.extend(config.options().await?);
+    }
+
+    Ok(options
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, String::try_from(value).ok()?)))
+        .collect())
+}
+
+/// Backend nativo de Linux. Se llama desde hilos fuera del runtime (el
+/// callback de netwatcher), así que espera la consulta con `block_on`.
+pub struct NetworkManagerBackend;
+
+impl WifiBackend for NetworkManagerBackend {
+    fn name(&self) -> &'static str {
+        "NetworkManager"
+    }
+
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String> {
+        tauri::async_runtime::block_on(wifi_info(interface)).map_err(|e| e.to_string())
+    }
+}
+
+/// Llama a `on_change` cada vez que un dispositivo WiFi cambia de punto de
+/// acceso o de estado (asociación, desconexión, roaming). Solo vigila los
+/// dispositivos presentes al iniciar; termina si el bus se cierra.
+pub async fn watch_access_points(on_change: impl Fn() + Send + 'static) -> zbus::Result<()> {
+    let bus = system_bus().await?;
+    let mut changes: Vec<BoxStream<'static, ()>> = Vec::new();
+
+    for path in NetworkManagerProxy::new(&bus).await?.get_devices().await? {
+        let device = DeviceProxy::builder(&bus)
+            .path(path.clone())?
+            .build()
+            .await?;
+        if device.device_type().await? != DEVICE_TYPE_WIFI {
+            continue;
+        }
+        let wireless = WirelessProxy::builder(&bus).path(path)?.build().await?;
+
+        changes.push(
+            wireless
+                .receive_active_access_point_changed()
+                .await
+                .map(|_| ())
+                .boxed(),
+        );
+        changes.push(device.receive_state_changed().await.map(|_| ()).boxed());
+    }
+
+    let mut changes = stream::select_all(changes);
+    while changes.next().await.is_some() {
+        on_change();
+    }
+    Ok(())
+}
@@ -0,0 +1,375 @@
+//! Pines del certificado del portal: conjuntos con pines de respaldo, hashes
+//! del certificado completo o de la llave pública (SPKI) y fechas de
+//! vencimiento. Los conjuntos pueden actualizarse sin publicar una versión
+//! nueva mediante un `pins.json` firmado (Ed25519).
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use lazy_static::lazy_static;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PINS_FILE: &str = "pins.json";
+const SUPPORTED_PINS_VERSION: u32 = 1;
+
+/// Llave pública Ed25519 (base64) con la que se firman los `pins.json`. Se
+/// define al compilar; sin ella solo se usan los pines integrados.
+const PIN_SIGNING_PUBLIC_KEY: Option<&str> = option_env!("CIMA_PIN_SIGNING_KEY");
+
+// Mismo pin que en `cima-sync-cli`
+// No sabes que es esto?
+// checa este repo: https://github.com/Yoyiyoniu/cima-tool.git
+const UABC_CERT_SHA256: [u8; 32] = [
+    0x19, 0xDC, 0x98, 0xBB, 0x1F, 0x08, 0x06, 0x93, 0x4A, 0x37, 0x50, 0x19, 0x39, 0x4A, 0x01,
+    0xA9, 0xDA, 0xD4, 0xA1, 0x87, 0x58, 0xEB, 0x1E, 0x4B, 0xB8, 0x26, 0x07, 0xCD, 0xEB, 0x1D,
+    0xD2, 0x5B,
+];
+
+pub const UABC_PIN_SET: &str = "uabc";
+
+lazy_static! {
+    static ref SIGNED_PIN_SETS: RwLock<Vec<PinSet>> = RwLock::new(Vec::new());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinKind {
+    /// SHA-256 del certificado DER completo; cambia en cada renovación.
+    CertSha256,
+    /// SHA-256 del SubjectPublicKeyInfo; sobrevive a renovaciones con la misma llave.
+    SpkiSha256,
+}
+
+impl PinKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            PinKind::CertSha256 => "cert-sha256",
+            PinKind::SpkiSha256 => "spki-sha256",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pin {
+    pub kind: PinKind,
+    pub digest: [u8; 32],
+    /// Segundos Unix a partir de los cuales el pin deja de aceptarse.
+    pub expires_at: Option<i64>,
+}
+
+impl Pin {
+    /// Acepta `cert-sha256:<hex>`, `spki-sha256:<hex>` o solo `<hex>`
+    /// (hash del certificado, el formato anterior).
+    pub fn parse(value: &str) -> Result<Pin, String> {
+        let value = value.trim();
+        let (kind, hex_digest) = match value.split_once(':') {
+            Some(("cert-sha256", rest)) => (PinKind::CertSha256, rest),
+            Some(("spki-sha256", rest)) => (PinKind::SpkiSha256, rest),
+            Some((other, _)) => return Err(format!("Tipo de pin desconocido: {}", other)),
+            None => (PinKind::CertSha256, value),
+        };
+
+        let bytes =
+            hex::decode(hex_digest.trim()).map_err(|e| format!("Pin inválido, no es hex: {}", e))?;
+        let digest: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| "El pin no tiene longitud de 32 bytes".to_string())?;
+
+        Ok(Pin {
+            kind,
+            digest,
+            expires_at: None,
+        })
+    }
+
+    pub fn expiring_at(mut self, expires_at: i64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    fn is_active(&self, now_secs: i64) -> bool {
+        self.expires_at.is_none_or(|expires_at| now_secs < expires_at)
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind.prefix(), hex::encode(self.digest))?;
+        if let Some(expires_at) = self.expires_at {
+            write!(f, " (vence {})", expires_at)?;
+        }
+        Ok(())
+    }
+}
+
+/// Conjunto de pines aceptados para un portal. Basta con que coincida uno
+/// vigente, así se puede publicar el pin del certificado nuevo (de respaldo)
+/// antes de que el portal lo instale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PinSet {
+    pub name: String,
+    pub pins: Vec<Pin>,
+}
+
+impl PinSet {
+    pub fn new(name: &str, pins: Vec<Pin>) -> Self {
+        PinSet {
+            name: name.to_string(),
+            pins,
+        }
+    }
+
+    /// Pines integrados del portal de la UABC (pcw.uabc.mx).
+    pub fn builtin_uabc() -> Self {
+        PinSet::new(
+            UABC_PIN_SET,
+            vec![Pin {
+                kind: PinKind::CertSha256,
+                digest: UABC_CERT_SHA256,
+                expires_at: None,
+            }],
+        )
+    }
+
+    /// Comprueba el certificado hoja (DER) contra los pines vigentes.
+    pub fn check(&self, cert_der: &[u8], now_secs: i64) -> Result<(), PinMismatch> {
+        let cert_digest: [u8; 32] = Sha256::digest(cert_der).into();
+        let spki_digest: Option<[u8; 32]> = spki_der(cert_der).map(|spki| Sha256::digest(spki).into());
+
+        let matched = self
+            .pins
+            .iter()
+            .filter(|pin| pin.is_active(now_secs))
+            .any(|pin| match pin.kind {
+                PinKind::CertSha256 => pin.digest == cert_digest,
+                PinKind::SpkiSha256 => spki_digest == Some(pin.digest),
+            });
+
+        if matched {
+            return Ok(());
+        }
+
+        Err(PinMismatch {
+            pin_set: self.name.clone(),
+            tried: self
+                .pins
+                .iter()
+                .map(|pin| {
+                    if pin.is_active(now_secs) {
+                        pin.to_string()
+                    } else {
+                        format!("{} [vencido]", pin)
+                    }
+                })
+                .collect(),
+            observed_cert_sha256: hex::encode(cert_digest),
+            observed_spki_sha256: spki_digest.map(hex::encode),
+        })
+    }
+}
+
+/// El certificado presentado es válido pero ningún pin vigente del conjunto
+/// coincide. Incluye lo observado para poder actualizar los pines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PinMismatch {
+    pub pin_set: String,
+    pub tried: Vec<String>,
+    pub observed_cert_sha256: String,
+    pub observed_spki_sha256: Option<String>,
+}
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "El certificado del servidor no coincide con el conjunto de pines '{}' (probados: {}); observado cert-sha256:{}",
+            self.pin_set,
+            if self.tried.is_empty() {
+                "ninguno".to_string()
+            } else {
+                self.tried.join(", ")
+            },
+            self.observed_cert_sha256,
+        )?;
+        if let Some(spki) = &self.observed_spki_sha256 {
+            write!(f, ", spki-sha256:{}", spki)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PinMismatch {}
+
+/// Extrae el SubjectPublicKeyInfo de un certificado X.509 en DER.
+///
+/// Certificate ::= SEQUENCE { tbsCertificate, ... } y dentro de
+/// tbsCertificate: [0] version (opcional), serialNumber, signature, issuer,
+/// validity, subject, subjectPublicKeyInfo.
+fn spki_der(cert: &[u8]) -> Option<&[u8]> {
+    let (certificate, _) = der_element(cert, 0x30)?;
+    let (tbs, _) = der_element(der_contents(certificate)?, 0x30)?;
+    let mut rest = der_contents(tbs)?;
+
+    if rest.first() == Some(&0xA0) {
+        rest = der_element(rest, 0xA0)?.1;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for tag in [0x02, 0x30, 0x30, 0x30, 0x30] {
+        rest = der_element(rest, tag)?.1;
+    }
+
+    der_element(rest, 0x30).map(|(spki, _)| spki)
+}
+
+/// Separa el primer elemento DER (con encabezado) del resto, si su etiqueta
+/// es `tag`.
+fn der_element(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if *input.first()? != tag {
+        return None;
+    }
+
+    let (header_len, content_len): (usize, usize) = match *input.get(1)? {
+        len if len < 0x80 => (2, len as usize),
+        0x81 => (3, *input.get(2)? as usize),
+        0x82 => (4, u16::from_be_bytes([*input.get(2)?, *input.get(3)?]) as usize),
+        0x83 => (
+            5,
+            u32::from_be_bytes([0, *input.get(2)?, *input.get(3)?, *input.get(4)?]) as usize,
+        ),
+        _ => return None,
+    };
+
+    let total = header_len.checked_add(content_len)?;
+    (total <= input.len()).then(|| input.split_at(total))
+}
+
+fn der_contents(element: &[u8]) -> Option<&[u8]> {
+    let header_len = match *element.get(1)? {
+        len if len < 0x80 => 2,
+        len => 2 + (len & 0x7F) as usize,
+    };
+    element.get(header_len..)
+}
+
+#[derive(Deserialize)]
+struct SignedPinFile {
+    /// JSON de `PinPayload` en base64; la firma cubre exactamente estos bytes.
+    payload: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PinPayload {
+    version: u32,
+    /// Segundos Unix; después de esta fecha el archivo completo se ignora.
+    expires_at: i64,
+    pin_sets: Vec<PinSetEntry>,
+}
+
+#[derive(Deserialize)]
+struct PinSetEntry {
+    name: String,
+    pins: Vec<PinEntry>,
+}
+
+#[derive(Deserialize)]
+struct PinEntry {
+    pin: String,
+    expires_at: Option<i64>,
+}
+
+/// Verifica la firma y la vigencia de un `pins.json` y devuelve sus conjuntos.
+pub fn verify_signed_pins(
+    contents: &str,
+    public_key: &[u8],
+    now_secs: i64,
+) -> Result<Vec<PinSet>, String> {
+    let file: SignedPinFile =
+        serde_json::from_str(contents).map_err(|e| format!("pins.json inválido: {}", e))?;
+    let payload = BASE64
+        .decode(file.payload.trim())
+        .map_err(|e| format!("payload de pines inválido: {}", e))?;
+    let signature = BASE64
+        .decode(file.signature.trim())
+        .map_err(|e| format!("firma de pines inválida: {}", e))?;
+
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&payload, &signature)
+        .map_err(|_| "La firma de pins.json no es válida".to_string())?;
+
+    let payload: PinPayload = serde_json::from_slice(&payload)
+        .map_err(|e| format!("Contenido de pins.json inválido: {}", e))?;
+
+    if payload.version != SUPPORTED_PINS_VERSION {
+        return Err(format!("Versión de pins.json no soportada: {}", payload.version));
+    }
+    if now_secs >= payload.expires_at {
+        return Err(format!("pins.json venció el {}", payload.expires_at));
+    }
+
+    payload
+        .pin_sets
+        .into_iter()
+        .map(|entry| {
+            let pins = entry
+                .pins
+                .iter()
+                .map(|pin| {
+                    let parsed = Pin::parse(&pin.pin)?;
+                    Ok(match pin.expires_at {
+                        Some(expires_at) => parsed.expiring_at(expires_at),
+                        None => parsed,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            if pins.is_empty() {
+                return Err(format!("El conjunto de pines '{}' está vacío", entry.name));
+            }
+            Ok(PinSet::new(&entry.name, pins))
+        })
+        .collect()
+}
+
+pub fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Carga `pins.json` de `config_dir` si existe y está firmado con la llave
+/// de la compilación. Debe llamarse antes de crear los clientes del portal.
+pub fn init(config_dir: &Path) {
+    let path = config_dir.join(PINS_FILE);
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return;
+    };
+
+    let Some(public_key) = PIN_SIGNING_PUBLIC_KEY.and_then(|key| BASE64.decode(key).ok()) else {
+        eprintln!("[pins] {} ignorado: esta compilación no tiene llave de firma", path.display());
+        return;
+    };
+
+    match verify_signed_pins(&contents, &public_key, now_secs()) {
+        Ok(sets) => {
+            eprintln!("[pins] {} conjuntos de pines cargados de {}", sets.len(), path.display());
+            match SIGNED_PIN_SETS.write() {
+                Ok(mut guard) => *guard = sets,
+                Err(poisoned) => *poisoned.into_inner() = sets,
+            }
+        }
+        Err(e) => eprintln!("[pins] {} ignorado: {}", path.display(), e),
+    }
+}
+
+/// Conjunto `name` del `pins.json` firmado, si se cargó uno.
+pub fn signed_pin_set(name: &str) -> Option<PinSet> {
+    let sets = match SIGNED_PIN_SETS.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    sets.iter().find(|set| set.name == name).cloned()
+}
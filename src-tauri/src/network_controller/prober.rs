@@ -0,0 +1,319 @@
+//! Detección de conectividad con varias sondas en paralelo.
+//!
+//! Cada sonda (HTTP 204, página de contenido conocido, resolución DNS y
+//! conexión TCP) es compatible con uno o más diagnósticos. El diagnóstico que
+//! acumula más peso gana y su confianza es la fracción del peso total que lo
+//! respalda. Así se distingue un portal cautivo (HTTP interceptado), una caída
+//! de DNS (TCP llega pero nada resuelve) y un enlace sin salida.
+
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpStream, lookup_host};
+use tokio::task::JoinSet;
+use ts_rs::TS;
+
+use crate::network_controller::client_builder::get_simple_client;
+use crate::network_controller::rtt;
+use crate::settings::Settings;
+
+/// Peso de la sonda `connectivity_probe_url`, la referencia histórica.
+const PRIMARY_PROBE_WEIGHT: f64 = 3.0;
+const MAX_PROBE_WEIGHT: f64 = 10.0;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProbeKind {
+    /// Espera un `204 No Content`; cualquier otra respuesta es un portal.
+    #[serde(rename = "http_204")]
+    Http204 { url: String },
+    /// Espera que el cuerpo contenga `expected`.
+    Content { url: String, expected: String },
+    /// Resuelve `host`. Un portal suele responder el DNS, así que solo
+    /// distingue "hay DNS" de "no hay DNS".
+    Dns { host: String },
+    /// Abre una conexión TCP a una IP literal, sin depender del DNS.
+    Tcp { address: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Probe {
+    #[serde(flatten)]
+    pub kind: ProbeKind,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl Probe {
+    pub fn new(kind: ProbeKind, weight: f64) -> Self {
+        Probe { kind, weight }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.weight > 0.0 && self.weight <= MAX_PROBE_WEIGHT) {
+            return Err(format!(
+                "El peso de una sonda debe estar entre 0 y {}",
+                MAX_PROBE_WEIGHT
+            ));
+        }
+
+        match &self.kind {
+            ProbeKind::Http204 { url } => check_http_url(url),
+            ProbeKind::Content { url, expected } => {
+                check_http_url(url)?;
+                if expected.trim().is_empty() {
+                    return Err("La sonda de contenido necesita un texto esperado".to_string());
+                }
+                Ok(())
+            }
+            ProbeKind::Dns { host } => {
+                if host.trim().is_empty() || host.contains(['/', ':', ' ']) {
+                    return Err(format!("Host inválido para la sonda DNS: {}", host));
+                }
+                Ok(())
+            }
+            ProbeKind::Tcp { address } => address
+                .parse::<SocketAddr>()
+                .map(|_| ())
+                .map_err(|_| format!("La sonda TCP requiere IP:puerto, recibió: {}", address)),
+        }
+    }
+}
+
+fn check_http_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("URL de sonda inválida: {}", e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(format!("La sonda usa un esquema no permitido: {}", scheme)),
+    }
+}
+
+/// Sondas por defecto que acompañan a `connectivity_probe_url`.
+pub fn default_probes() -> Vec<Probe> {
+    vec![
+        Probe::new(
+            ProbeKind::Content {
+                url: "http://detectportal.firefox.com/success.txt".to_string(),
+                expected: "success".to_string(),
+            },
+            2.0,
+        ),
+        Probe::new(
+            ProbeKind::Dns {
+                host: "clients3.google.com".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Tcp {
+                address: "1.1.1.1:443".to_string(),
+            },
+            1.0,
+        ),
+    ]
+}
+
+/// Todas las sondas de la configuración: la de `connectivity_probe_url` más
+/// las de `connectivity_probes`.
+pub fn configured_probes(config: &Settings) -> Vec<Probe> {
+    let mut probes = vec![Probe::new(
+        ProbeKind::Http204 {
+            url: config.connectivity_probe_url.clone(),
+        },
+        PRIMARY_PROBE_WEIGHT,
+    )];
+    probes.extend(config.connectivity_probes.iter().cloned());
+    probes
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum Connectivity {
+    Online,
+    CaptivePortal,
+    DnsFailure,
+    Offline,
+}
+
+impl Connectivity {
+    /// Orden de desempate: ante igual peso gana el primero.
+    const ALL: [Connectivity; 4] = [
+        Connectivity::Online,
+        Connectivity::CaptivePortal,
+        Connectivity::DnsFailure,
+        Connectivity::Offline,
+    ];
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Connectivity::Online => "online",
+            Connectivity::CaptivePortal => "captivePortal",
+            Connectivity::DnsFailure => "dnsFailure",
+            Connectivity::Offline => "offline",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ProbeOutcome {
+    /// La sonda obtuvo exactamente lo esperado.
+    Reachable,
+    /// Hubo respuesta, pero no la esperada (típico de un portal cautivo).
+    Intercepted,
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProbeResult {
+    pub probe: Probe,
+    pub outcome: ProbeOutcome,
+    pub elapsed_ms: u64,
+}
+
+impl ProbeResult {
+    /// Diagnósticos con los que este resultado es consistente.
+    fn supports(&self) -> &'static [Connectivity] {
+        use Connectivity::*;
+
+        match (&self.probe.kind, &self.outcome) {
+            (ProbeKind::Http204 { .. } | ProbeKind::Content { .. }, ProbeOutcome::Reachable) => {
+                &[Online]
+            }
+            (_, ProbeOutcome::Intercepted) => &[CaptivePortal],
+            (ProbeKind::Dns { .. }, ProbeOutcome::Reachable) => &[Online, CaptivePortal],
+            // Los portales suelen bloquear TCP hacia fuera hasta autenticarse.
+            (ProbeKind::Tcp { .. }, ProbeOutcome::Reachable) => {
+                &[Online, CaptivePortal, DnsFailure]
+            }
+            (ProbeKind::Tcp { .. }, ProbeOutcome::Failed { .. }) => &[CaptivePortal, Offline],
+            (_, ProbeOutcome::Failed { .. }) => &[DnsFailure, Offline],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Verdict {
+    pub connectivity: Connectivity,
+    /// Fracción (0.0–1.0) del peso de las sondas que respalda el diagnóstico.
+    pub confidence: f64,
+    pub results: Vec<ProbeResult>,
+}
+
+impl Verdict {
+    pub fn is_online(&self) -> bool {
+        self.connectivity == Connectivity::Online
+    }
+}
+
+/// Combina los resultados por voto ponderado.
+pub fn tally(results: Vec<ProbeResult>) -> Verdict {
+    let total: f64 = results.iter().map(|r| r.probe.weight).sum();
+
+    let score = |connectivity: Connectivity| -> f64 {
+        results
+            .iter()
+            .filter(|r| r.supports().contains(&connectivity))
+            .map(|r| r.probe.weight)
+            .sum()
+    };
+
+    let mut best = (Connectivity::Offline, 0.0);
+    for connectivity in Connectivity::ALL {
+        let weight = score(connectivity);
+        if weight > best.1 {
+            best = (connectivity, weight);
+        }
+    }
+
+    Verdict {
+        connectivity: best.0,
+        confidence: if total > 0.0 { best.1 / total } else { 0.0 },
+        results,
+    }
+}
+
+/// Lanza todas las sondas a la vez y combina sus resultados.
+pub async fn run(probes: &[Probe], timeout: Duration) -> Verdict {
+    let mut tasks = JoinSet::new();
+    for (index, probe) in probes.iter().cloned().enumerate() {
+        tasks.spawn(async move {
+            let started = Instant::now();
+            let outcome = run_probe(&probe.kind, timeout).await;
+            let result = ProbeResult {
+                probe,
+                outcome,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            };
+            (index, result)
+        });
+    }
+
+    let mut results = Vec::with_capacity(probes.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => eprintln!("[prober] Sonda interrumpida: {}", e),
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    tally(results.into_iter().map(|(_, result)| result).collect())
+}
+
+/// Ejecuta las sondas de la configuración vigente.
+pub async fn probe(config: &Settings) -> Verdict {
+    run(&configured_probes(config), config.connectivity_timeout()).await
+}
+
+async fn run_probe(kind: &ProbeKind, timeout: Duration) -> ProbeOutcome {
+    match kind {
+        ProbeKind::Http204 { url } => {
+            match rtt::send(get_simple_client().get(url), url, timeout).await {
+                Ok(response) if response.status().as_u16() == 204 => ProbeOutcome::Reachable,
+                Ok(_) => ProbeOutcome::Intercepted,
+                Err(e) => failed(e),
+            }
+        }
+        ProbeKind::Content { url, expected } => {
+            match rtt::send(get_simple_client().get(url), url, timeout).await {
+                Ok(response) if response.status().is_success() => match response.text().await {
+                    Ok(body) if body.contains(expected.as_str()) => ProbeOutcome::Reachable,
+                    Ok(_) => ProbeOutcome::Intercepted,
+                    Err(e) => failed(e),
+                },
+                Ok(_) => ProbeOutcome::Intercepted,
+                Err(e) => failed(e),
+            }
+        }
+        ProbeKind::Dns { host } => {
+            match tokio::time::timeout(timeout, lookup_host((host.as_str(), 0))).await {
+                Ok(Ok(mut addrs)) => match addrs.next() {
+                    Some(_) => ProbeOutcome::Reachable,
+                    None => failed("sin direcciones"),
+                },
+                Ok(Err(e)) => failed(e),
+                Err(_) => failed("tiempo de espera agotado"),
+            }
+        }
+        ProbeKind::Tcp { address } => {
+            match tokio::time::timeout(timeout, TcpStream::connect(address.as_str())).await {
+                Ok(Ok(_)) => ProbeOutcome::Reachable,
+                Ok(Err(e)) => failed(e),
+                Err(_) => failed("tiempo de espera agotado"),
+            }
+        }
+    }
+}
+
+fn failed(reason: impl std::fmt::Display) -> ProbeOutcome {
+    ProbeOutcome::Failed {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,236 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::json_store::JsonStore;
+use crate::network_controller::pins::Pin;
+
+const PROFILES_FILE: &str = "network-profiles.json";
+const DEFAULT_PROFILE_ID: &str = "uabc";
+const MAX_NAME_LENGTH: usize = 64;
+
+lazy_static! {
+    static ref PROFILES: JsonStore<Vec<NetworkProfile>> =
+        JsonStore::new(PROFILES_FILE, "profiles", "perfiles de red", || {
+            vec![NetworkProfile::uabc()]
+        });
+}
+
+/// Red conocida: qué SSID (y opcionalmente qué punto de acceso o gateway)
+/// la identifican y con qué credenciales, portal y pin se autentica.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkProfile {
+    pub id: String,
+    pub name: String,
+    /// SSIDs aceptados. Coincidencia exacta; `*` y `?` funcionan como comodines.
+    pub ssid_patterns: Vec<String>,
+    /// Si no está vacío, solo se acepta la red cuando el BSSID está en la lista.
+    pub bssids: Vec<String>,
+    /// Si no está vacío, solo se acepta la red cuando la MAC del gateway está en la lista.
+    pub gateway_macs: Vec<String>,
+    /// Perfil del vault con el que se inicia sesión; `None` usa las
+    /// credenciales con las que se inició el monitoreo.
+    pub credential_profile_id: Option<String>,
+    /// Portal de la red; `None` usa `portal_url` de la configuración.
+    pub portal_url: Option<String>,
+    /// Pin del portal (`cert-sha256:<hex>`, `spki-sha256:<hex>` o el hex del
+    /// certificado); `None` usa los pines de UABC.
+    pub cert_sha256: Option<String>,
+    /// Sin `cert_sha256`, aprende el pin en la primera conexión al portal y lo
+    /// guarda aquí cuando el usuario aprueba la huella.
+    pub trust_on_first_use: bool,
+}
+
+/// Identidad observada de la red actual.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NetworkObservation<'a> {
+    pub ssid: Option<&'a str>,
+    pub bssid: Option<&'a str>,
+    pub gateway_mac: Option<&'a str>,
+}
+
+impl NetworkProfile {
+    /// Red WiFi del campus (SSIDs publicados por la UABC).
+    pub fn uabc() -> Self {
+        NetworkProfile {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "WiFi UABC".to_string(),
+            ssid_patterns: vec![
+                "UABC".to_string(),
+                "UABC_5G".to_string(),
+                "UABC_2.4G".to_string(),
+            ],
+            ..NetworkProfile::default()
+        }
+    }
+
+    pub fn matches(&self, observed: &NetworkObservation) -> bool {
+        let Some(ssid) = observed.ssid else {
+            return false;
+        };
+
+        self.ssid_patterns
+            .iter()
+            .any(|pattern| glob_matches(pattern, ssid))
+            && matches_mac_list(&self.bssids, observed.bssid)
+            && matches_mac_list(&self.gateway_macs, observed.gateway_mac)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("El perfil de red necesita un id".to_string());
+        }
+        if self.name.trim().is_empty() || self.name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!(
+                "El nombre del perfil de red debe tener entre 1 y {} caracteres",
+                MAX_NAME_LENGTH
+            ));
+        }
+        if self.ssid_patterns.iter().all(|pattern| pattern.trim().is_empty()) {
+            return Err("El perfil de red necesita al menos un SSID".to_string());
+        }
+        if self.ssid_patterns.iter().any(|pattern| pattern.trim() == "*") {
+            return Err("Un SSID comodín (*) aceptaría cualquier red".to_string());
+        }
+
+        for mac in self.bssids.iter().chain(&self.gateway_macs) {
+            if normalize_mac(mac).is_none() {
+                return Err(format!("Dirección MAC inválida: {}", mac));
+            }
+        }
+
+        if let Some(url) = &self.portal_url {
+            let parsed =
+                reqwest::Url::parse(url).map_err(|e| format!("portal_url inválida: {}", e))?;
+            if parsed.scheme() != "https" {
+                return Err("portal_url debe usar https".to_string());
+            }
+        }
+
+        if let Some(pin) = &self.cert_sha256 {
+            Pin::parse(pin).map_err(|e| format!("cert_sha256: {}", e))?;
+        }
+
+        if self.trust_on_first_use && self.portal_url.is_none() {
+            return Err("Aprender el pin requiere indicar portal_url".to_string());
+        }
+        // Los pines de UABC nunca coinciden con otro portal
+        if self.portal_url.is_some() && self.cert_sha256.is_none() && !self.trust_on_first_use {
+            return Err(
+                "Un portal propio requiere cert_sha256 o aprender el pin (trust_on_first_use)"
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Compara MACs sin importar mayúsculas ni separadores (`:` o `-`).
+pub fn normalize_mac(mac: &str) -> Option<String> {
+    let hex: String = mac
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    (hex.len() == 12 && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some(hex)
+}
+
+fn matches_mac_list(allowed: &[String], observed: Option<&str>) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+
+    let Some(observed) = observed.and_then(normalize_mac) else {
+        return false;
+    };
+    allowed
+        .iter()
+        .filter_map(|mac| normalize_mac(mac))
+        .any(|mac| mac == observed)
+}
+
+/// Comodines estilo shell: `*` cualquier secuencia, `?` un carácter.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Carga `network-profiles.json` desde `config_dir` (ver `JsonStore::init`);
+/// por defecto solo contiene el perfil de UABC.
+pub fn init(config_dir: &Path) {
+    PROFILES.init(config_dir, |profiles| {
+        profiles.iter().try_for_each(NetworkProfile::validate)
+    });
+}
+
+pub fn list() -> Vec<NetworkProfile> {
+    PROFILES.current()
+}
+
+/// Perfil con ese `id`, tal como está guardado ahora.
+pub fn get(id: &str) -> Option<NetworkProfile> {
+    PROFILES.read(|profiles| profiles.iter().find(|profile| profile.id == id).cloned())
+}
+
+/// Primer perfil que coincide con la red observada.
+pub fn find_match(observed: &NetworkObservation) -> Option<NetworkProfile> {
+    PROFILES.read(|profiles| {
+        profiles
+            .iter()
+            .find(|profile| profile.matches(observed))
+            .cloned()
+    })
+}
+
+/// Agrega o reemplaza (por `id`) un perfil y lo guarda en disco.
+pub fn save(profile: NetworkProfile) -> Result<Vec<NetworkProfile>, String> {
+    profile.validate()?;
+
+    let mut profiles = list();
+    match profiles.iter_mut().find(|existing| existing.id == profile.id) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+
+    PROFILES.store(profiles)
+}
+
+pub fn delete(id: &str) -> Result<Vec<NetworkProfile>, String> {
+    let mut profiles = list();
+    let before = profiles.len();
+    profiles.retain(|profile| profile.id != id);
+    if profiles.len() == before {
+        return Err("Perfil de red no encontrado".to_string());
+    }
+
+    PROFILES.store(profiles)
+}
@@ -0,0 +1,112 @@
+//! Timeouts adaptativos según el tiempo de respuesta medido.
+//!
+//! Por cada host se lleva un promedio suavizado del tiempo de respuesta y su
+//! variación (SRTT/RTTVAR, como el RTO de TCP en RFC 6298). En redes lentas el
+//! timeout pedido por quien llama se estira hasta `SRTT + 4·RTTVAR`, sin pasar
+//! de `max_adaptive_timeout_secs`; nunca se acorta por debajo del pedido.
+
+use lazy_static::lazy_static;
+use reqwest::{RequestBuilder, Response, Url};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::settings;
+
+lazy_static! {
+    static ref ESTIMATORS: Mutex<HashMap<String, RttEstimator>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RttEstimator {
+    srtt: Option<Duration>,
+    rttvar: Duration,
+}
+
+impl RttEstimator {
+    pub fn observe(&mut self, sample: Duration) {
+        match self.srtt {
+            None => {
+                self.srtt = Some(sample);
+                self.rttvar = sample / 2;
+            }
+            Some(srtt) => {
+                let delta = srtt.abs_diff(sample);
+                self.rttvar = (self.rttvar * 3 + delta) / 4;
+                self.srtt = Some((srtt * 7 + sample) / 8);
+            }
+        }
+    }
+
+    /// Timeout para una petición que pidió `base`, acotado por `max`.
+    pub fn timeout(&self, base: Duration, max: Duration) -> Duration {
+        let Some(srtt) = self.srtt else {
+            return base;
+        };
+        let rto = srtt + self.rttvar * 4;
+        base.max(rto.min(max))
+    }
+
+    pub fn srtt(&self) -> Option<Duration> {
+        self.srtt
+    }
+}
+
+fn host_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+/// Timeout efectivo para `url` cuando quien llama pidió `base`.
+pub fn timeout_for(url: &str, base: Duration) -> Duration {
+    let Some(key) = host_key(url) else {
+        return base;
+    };
+    let max = settings::current().max_adaptive_timeout();
+
+    let estimators = match ESTIMATORS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    estimators
+        .get(&key)
+        .map(|estimator| estimator.timeout(base, max))
+        .unwrap_or(base)
+}
+
+pub fn observe(url: &str, sample: Duration) {
+    let Some(key) = host_key(url) else {
+        return;
+    };
+
+    let mut estimators = match ESTIMATORS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    estimators.entry(key).or_default().observe(sample);
+}
+
+/// Envía `request` (dirigida a `url`) con el timeout adaptado y registra lo
+/// que tardó. Un timeout cuenta como muestra del tiempo agotado, así el
+/// siguiente intento en una red lenta espera más.
+pub async fn send(request: RequestBuilder, url: &str, base: Duration) -> reqwest::Result<Response> {
+    let timeout = timeout_for(url, base);
+    let started = Instant::now();
+
+    match request.timeout(timeout).send().await {
+        Ok(response) => {
+            observe(url, started.elapsed());
+            Ok(response)
+        }
+        Err(e) => {
+            if e.is_timeout() {
+                observe(url, timeout);
+            }
+            Err(e)
+        }
+    }
+}
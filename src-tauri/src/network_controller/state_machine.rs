@@ -0,0 +1,407 @@
+//! Máquina de estados de la red. Cada evaluación entrega el enlace actual y el
+//! resultado de la sonda; la máquina decide si hay transición.
+//!
+//! Un cambio de enlace (otro SSID u otro perfil) se aplica de inmediato. Con
+//! el mismo enlace solo cambia la sonda, y ahí hay histéresis: la caída
+//! (p. ej. `FineConnection` → `HaveCautivePortal`) espera a varios fallos
+//! seguidos, mientras que la recuperación basta con una sonda exitosa.
+//!
+//! El reloj y la sonda son traits para poder probarla sin red ni esperas.
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::network_controller::status::SyncNetworkState;
+use crate::settings::Settings;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Sonda de conectividad; `Report` es lo que el llamador necesita además del
+/// veredicto (en `network_sync`, el estado de la API del portal).
+#[async_trait]
+pub trait ConnectivityProber: Send + Sync {
+    type Report: Send;
+
+    async fn probe(&self) -> Self::Report;
+
+    fn is_online(report: &Self::Report) -> bool;
+}
+
+/// Enlace observado antes de sondear.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Link {
+    pub ssid: Option<String>,
+    /// Perfil con el que coincide la red, si alguno.
+    pub network_profile_id: Option<String>,
+}
+
+impl Link {
+    fn connected(&self) -> bool {
+        self.ssid.is_some()
+    }
+
+    fn is_uabc(&self) -> bool {
+        self.network_profile_id.is_some()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hysteresis {
+    /// Fallos seguidos de la sonda antes de dar por perdida la conexión.
+    pub failures_to_degrade: u32,
+    /// Fallos más cercanos que esto cuentan como uno solo.
+    pub debounce: Duration,
+    /// Espera entre sondeos mientras se confirma una caída.
+    pub recheck_interval: Duration,
+    /// Un fallo más viejo que esto ya no cuenta como seguido.
+    pub failure_window: Duration,
+}
+
+impl Default for Hysteresis {
+    fn default() -> Self {
+        Hysteresis {
+            failures_to_degrade: 3,
+            debounce: Duration::from_secs(2),
+            recheck_interval: Duration::from_secs(5),
+            failure_window: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Hysteresis {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Hysteresis {
+            failures_to_degrade: settings.connectivity_failure_threshold,
+            ..Hysteresis::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionCause {
+    /// Primera evaluación o cambio de SSID/perfil.
+    Link,
+    /// Misma red, la sonda volvió a llegar a internet.
+    ProbeRecovered,
+    /// Misma red, la sonda falló `failures` veces seguidas.
+    ProbeFailed { failures: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    /// `None` en la primera evaluación.
+    pub from: Option<SyncNetworkState>,
+    pub to: SyncNetworkState,
+    pub cause: TransitionCause,
+    pub ssid: Option<String>,
+    /// Tiempo que se estuvo en `from`.
+    pub time_in_previous: Option<Duration>,
+}
+
+/// Resultado de una evaluación.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub state: SyncNetworkState,
+    pub transition: Option<Transition>,
+    /// Cuándo volver a sondear para confirmar una caída pendiente. Solo se
+    /// pide una vez por intervalo aunque lleguen varias evaluaciones.
+    pub recheck_after: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rule {
+    Immediate,
+    AfterFailures,
+}
+
+/// Transiciones con el mismo enlace, que solo dependen de la sonda.
+const PROBE_TRANSITIONS: [(SyncNetworkState, SyncNetworkState, Rule); 6] = [
+    (
+        SyncNetworkState::FineConnection,
+        SyncNetworkState::HaveCautivePortal,
+        Rule::AfterFailures,
+    ),
+    (
+        SyncNetworkState::HaveCautivePortal,
+        SyncNetworkState::FineConnection,
+        Rule::Immediate,
+    ),
+    (
+        SyncNetworkState::MobileConnection,
+        SyncNetworkState::MobileConnectionRequiereAuth,
+        Rule::AfterFailures,
+    ),
+    (
+        SyncNetworkState::MobileConnectionRequiereAuth,
+        SyncNetworkState::MobileConnection,
+        Rule::Immediate,
+    ),
+    (
+        SyncNetworkState::MobileConnection,
+        SyncNetworkState::InvalidConnection,
+        Rule::AfterFailures,
+    ),
+    (
+        SyncNetworkState::InvalidConnection,
+        SyncNetworkState::MobileConnection,
+        Rule::Immediate,
+    ),
+];
+
+fn probe_rule(from: SyncNetworkState, to: SyncNetworkState) -> Option<Rule> {
+    PROBE_TRANSITIONS
+        .iter()
+        .find(|(f, t, _)| *f == from && *t == to)
+        .map(|(_, _, rule)| *rule)
+}
+
+/// Estado que corresponde al enlace y la sonda, sin historia.
+pub fn target_state(link: &Link, online: bool) -> SyncNetworkState {
+    match (link.connected(), link.is_uabc(), online) {
+        (true, true, true) => SyncNetworkState::FineConnection,
+        (true, true, false) => SyncNetworkState::HaveCautivePortal,
+        (false, _, true) => SyncNetworkState::MobileConnection,
+        (false, true, false) => SyncNetworkState::MobileConnectionRequiereAuth,
+        _ => SyncNetworkState::InvalidConnection,
+    }
+}
+
+type Hook = Arc<dyn Fn(&Transition) + Send + Sync>;
+
+enum HookPoint {
+    Exit(SyncNetworkState),
+    Any,
+    Enter(SyncNetworkState),
+}
+
+#[derive(Default)]
+struct Memory {
+    state: Option<SyncNetworkState>,
+    entered_at: Option<Instant>,
+    link: Option<Link>,
+    failures: u32,
+    last_failure_at: Option<Instant>,
+    next_recheck_at: Option<Instant>,
+}
+
+impl Memory {
+    fn clear_failures(&mut self) {
+        self.failures = 0;
+        self.last_failure_at = None;
+        self.next_recheck_at = None;
+    }
+}
+
+pub struct NetworkStateMachine<C = SystemClock> {
+    clock: C,
+    config: Mutex<Hysteresis>,
+    memory: Mutex<Memory>,
+    hooks: Mutex<Vec<(HookPoint, Hook)>>,
+}
+
+impl<C: Clock> NetworkStateMachine<C> {
+    pub fn new(clock: C, config: Hysteresis) -> Self {
+        NetworkStateMachine {
+            clock,
+            config: Mutex::new(config),
+            memory: Mutex::new(Memory::default()),
+            hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Cambia los umbrales; los fallos ya contados se conservan.
+    pub fn set_hysteresis(&self, config: Hysteresis) {
+        match self.config.lock() {
+            Ok(mut guard) => *guard = config,
+            Err(poisoned) => *poisoned.into_inner() = config,
+        }
+    }
+
+    /// Se llama al entrar a `state`, después de los hooks de salida.
+    pub fn on_enter(
+        &self,
+        state: SyncNetworkState,
+        hook: impl Fn(&Transition) + Send + Sync + 'static,
+    ) {
+        self.add_hook(HookPoint::Enter(state), Arc::new(hook));
+    }
+
+    /// Se llama al salir de `state`, antes de entrar al siguiente.
+    pub fn on_exit(
+        &self,
+        state: SyncNetworkState,
+        hook: impl Fn(&Transition) + Send + Sync + 'static,
+    ) {
+        self.add_hook(HookPoint::Exit(state), Arc::new(hook));
+    }
+
+    /// Se llama en cada transición, entre los hooks de salida y los de entrada.
+    pub fn on_transition(&self, hook: impl Fn(&Transition) + Send + Sync + 'static) {
+        self.add_hook(HookPoint::Any, Arc::new(hook));
+    }
+
+    fn add_hook(&self, point: HookPoint, hook: Hook) {
+        match self.hooks.lock() {
+            Ok(mut guard) => guard.push((point, hook)),
+            Err(poisoned) => poisoned.into_inner().push((point, hook)),
+        }
+    }
+
+    /// Estado actual; `None` antes de la primera evaluación.
+    pub fn state(&self) -> Option<SyncNetworkState> {
+        match self.memory.lock() {
+            Ok(guard) => guard.state,
+            Err(poisoned) => poisoned.into_inner().state,
+        }
+    }
+
+    /// Sondea con `prober` y aplica el resultado.
+    pub async fn evaluate<P: ConnectivityProber>(
+        &self,
+        link: Link,
+        prober: &P,
+    ) -> (Step, P::Report) {
+        let report = prober.probe().await;
+        let step = self.observe(link, P::is_online(&report));
+        (step, report)
+    }
+
+    /// Aplica una observación ya sondeada (o validada por el sistema operativo).
+    pub fn observe(&self, link: Link, online: bool) -> Step {
+        let config = match self.config.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        let now = self.clock.now();
+        let target = target_state(&link, online);
+
+        let mut memory = match self.memory.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let same_link = memory.link.as_ref() == Some(&link);
+        let cause = match memory.state {
+            Some(current) if same_link && current == target => {
+                if online {
+                    memory.clear_failures();
+                }
+                None
+            }
+            Some(current) if same_link => match probe_rule(current, target) {
+                Some(Rule::AfterFailures) => count_failure(&mut memory, &config, now),
+                Some(Rule::Immediate) => Some(TransitionCause::ProbeRecovered),
+                None => Some(TransitionCause::Link),
+            },
+            Some(current) if current == target => {
+                memory.clear_failures();
+                None
+            }
+            _ => Some(TransitionCause::Link),
+        };
+        memory.link = Some(link);
+
+        let transition = cause.map(|cause| {
+            let transition = Transition {
+                from: memory.state,
+                to: target,
+                cause,
+                ssid: memory.link.as_ref().and_then(|l| l.ssid.clone()),
+                time_in_previous: memory
+                    .entered_at
+                    .map(|at| now.saturating_duration_since(at)),
+            };
+            memory.state = Some(target);
+            memory.entered_at = Some(now);
+            memory.clear_failures();
+            transition
+        });
+
+        let recheck_after =
+            if memory.failures > 0 && memory.next_recheck_at.is_none_or(|at| now >= at) {
+                memory.next_recheck_at = Some(now + config.recheck_interval);
+                Some(config.recheck_interval)
+            } else {
+                None
+            };
+
+        let step = Step {
+            state: memory.state.unwrap_or(target),
+            transition,
+            recheck_after,
+        };
+        // Los hooks pueden consultar `state()`: se llaman sin el lock
+        drop(memory);
+
+        if let Some(transition) = &step.transition {
+            self.run_hooks(transition);
+        }
+        step
+    }
+
+    /// Copia los hooks que aplican y suelta el lock antes de llamarlos: un
+    /// hook puede registrar otros o usar la máquina sin bloquearse.
+    fn run_hooks(&self, transition: &Transition) {
+        let selected: Vec<Hook> = {
+            let hooks = match self.hooks.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let exits = hooks.iter().filter(
+                |(point, _)| matches!(point, HookPoint::Exit(s) if Some(*s) == transition.from),
+            );
+            let any = hooks
+                .iter()
+                .filter(|(point, _)| matches!(point, HookPoint::Any));
+            let enters = hooks
+                .iter()
+                .filter(|(point, _)| matches!(point, HookPoint::Enter(s) if *s == transition.to));
+            exits
+                .chain(any)
+                .chain(enters)
+                .map(|(_, hook)| Arc::clone(hook))
+                .collect()
+        };
+
+        for hook in selected {
+            hook(transition);
+        }
+    }
+}
+
+/// Cuenta un fallo de la sonda y devuelve la causa si ya alcanza el umbral.
+fn count_failure(
+    memory: &mut Memory,
+    config: &Hysteresis,
+    now: Instant,
+) -> Option<TransitionCause> {
+    match memory
+        .last_failure_at
+        .map(|at| now.saturating_duration_since(at))
+    {
+        Some(elapsed) if elapsed < config.debounce => {}
+        Some(elapsed) if elapsed > config.failure_window => {
+            memory.failures = 1;
+            memory.last_failure_at = Some(now);
+        }
+        _ => {
+            memory.failures += 1;
+            memory.last_failure_at = Some(now);
+        }
+    }
+
+    (memory.failures >= config.failures_to_degrade).then_some(TransitionCause::ProbeFailed {
+        failures: memory.failures,
+    })
+}
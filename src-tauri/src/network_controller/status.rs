@@ -0,0 +1,130 @@
+//! Estado de red que se entrega al frontend en `network-status`, a la CLI y
+//! al daemon. Los datos del enlace son opcionales: cada sistema reporta los
+//! que puede (Android no da IP ni gateway; Linux no da RSSI en dBm).
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::network_controller::prober::Connectivity;
+use crate::network_controller::wifi::WifiInfo;
+
+/// Estado de la red actual respecto a la UABC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncNetworkState {
+    FineConnection,
+    HaveCautivePortal,
+    #[default]
+    InvalidConnection,
+    MobileConnection,
+    MobileConnectionRequiereAuth,
+}
+
+impl SyncNetworkState {
+    pub fn as_key(self) -> &'static str {
+        match self {
+            SyncNetworkState::FineConnection => "fineConnection",
+            SyncNetworkState::HaveCautivePortal => "haveCautivePortal",
+            SyncNetworkState::InvalidConnection => "invalidConnection",
+            SyncNetworkState::MobileConnection => "mobileConnection",
+            SyncNetworkState::MobileConnectionRequiereAuth => "mobileConnectionRequiereAuth",
+        }
+    }
+
+    /// El portal cautivo de la UABC está bloqueando la conexión.
+    pub fn requires_auth(self) -> bool {
+        matches!(
+            self,
+            SyncNetworkState::HaveCautivePortal | SyncNetworkState::MobileConnectionRequiereAuth
+        )
+    }
+
+    /// La red actual es de la UABC (autenticada o no).
+    pub fn is_uabc(self) -> bool {
+        self == SyncNetworkState::FineConnection || self.requires_auth()
+    }
+
+    /// Texto que muestra la interfaz para el estado.
+    pub fn as_status_text(self) -> &'static str {
+        match self {
+            SyncNetworkState::FineConnection => "WI-FI Cimarrón Autenticado",
+            SyncNetworkState::HaveCautivePortal
+            | SyncNetworkState::MobileConnectionRequiereAuth => {
+                "Red UABC disponible, inicia sesión"
+            }
+            SyncNetworkState::InvalidConnection => "WI-FI Cimarrón No Disponible",
+            SyncNetworkState::MobileConnection => "Sin conexión a la red de la universidad",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct NetworkStatus {
+    pub connected: bool,
+    pub ssid: Option<String>,
+    pub is_uabc: bool,
+    pub network_profile_id: Option<String>,
+    pub network_state: SyncNetworkState,
+    pub status_text: String,
+    /// `None` cuando el estado viene del sistema operativo sin sondear.
+    pub connectivity: Option<Connectivity>,
+    pub connectivity_confidence: Option<f64>,
+    pub captive_portal_url: Option<String>,
+    #[ts(type = "number | null")]
+    pub session_seconds_remaining: Option<u64>,
+    pub interface: Option<String>,
+    pub ipv4: Option<String>,
+    pub ipv6: Option<String>,
+    pub gateway: Option<String>,
+    pub bssid: Option<String>,
+    pub rssi_dbm: Option<i32>,
+    pub link_speed_mbps: Option<u32>,
+    pub wifi: Option<WifiInfo>,
+    /// Motivo por el que no se pudo leer el estado; `None` en lecturas normales.
+    pub error: Option<String>,
+    #[ts(type = "number")]
+    pub timestamp_ms: i64,
+}
+
+/// Dirección de la interfaz y salida por defecto.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkAddresses {
+    pub interface: Option<String>,
+    pub ipv4: Option<String>,
+    pub ipv6: Option<String>,
+    pub gateway: Option<String>,
+}
+
+impl NetworkStatus {
+    /// Estado que se emite cuando no se pudo leer la red (p. ej. el monitor
+    /// de interfaces no arrancó).
+    pub fn from_error(error: String, timestamp_ms: i64) -> Self {
+        let network_state = SyncNetworkState::InvalidConnection;
+        NetworkStatus {
+            network_state,
+            status_text: network_state.as_status_text().to_string(),
+            error: Some(error),
+            timestamp_ms,
+            ..NetworkStatus::default()
+        }
+    }
+
+    /// Completa los datos del enlace. BSSID, RSSI y velocidad se toman de
+    /// `wifi` si el backend los dio; `addresses` llena lo que falte de la
+    /// interfaz.
+    pub fn with_link(mut self, addresses: LinkAddresses, wifi: Option<WifiInfo>) -> Self {
+        self.interface = addresses
+            .interface
+            .or_else(|| wifi.as_ref().map(|w| w.interface.clone()))
+            .filter(|name| !name.is_empty());
+        self.ipv4 = addresses.ipv4;
+        self.ipv6 = addresses.ipv6;
+        self.gateway = addresses.gateway;
+        self.bssid = wifi.as_ref().and_then(|w| w.bssid.clone());
+        self.rssi_dbm = wifi.as_ref().and_then(|w| w.rssi_dbm);
+        self.link_speed_mbps = wifi.as_ref().and_then(|w| w.link_speed_mbps);
+        self.wifi = wifi;
+        self
+    }
+}
@@ -0,0 +1,183 @@
+//! Aprendizaje del pin en el primer uso (TOFU) para portales nuevos.
+//!
+//! Un perfil de red con `trust_on_first_use` y sin `cert_sha256` se conecta
+//! con un conjunto de pines vacío: el certificado debe pasar la validación
+//! WebPki, pero `PinnedCertVerifier` lo rechaza y reporta su huella. Esa huella
+//! queda pendiente hasta que el usuario la aprueba; entonces se guarda en el
+//! perfil y se exige en adelante. Antes de aprobarla no se envía ningún dato.
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+use crate::error::AuthError;
+use crate::history;
+use crate::network_controller::client_builder::PinConfig;
+use crate::network_controller::pins::PinSet;
+use crate::network_controller::profiles::{self, NetworkProfile};
+
+type PendingListener = Box<dyn Fn(&PendingPin) + Send>;
+
+lazy_static! {
+    static ref PENDING_PINS: Mutex<Vec<PendingPin>> = Mutex::new(Vec::new());
+    static ref PENDING_LISTENERS: Mutex<Vec<PendingListener>> = Mutex::new(Vec::new());
+    // (perfil, cert_sha256) que el usuario rechazó durante esta ejecución.
+    static ref REJECTED_PINS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+}
+
+/// Evento `pin-approval-required`: huella observada en la primera conexión a
+/// un portal, a la espera de que el usuario la apruebe.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+pub struct PendingPin {
+    pub profile_id: String,
+    pub profile_name: String,
+    pub portal_url: Option<String>,
+    /// SHA-256 (hex) del certificado presentado; es lo que se guarda al aprobar.
+    pub cert_sha256: String,
+    pub spki_sha256: Option<String>,
+    #[ts(type = "number")]
+    pub observed_at_ms: i64,
+}
+
+/// El perfil todavía no tiene pin y pide aprenderlo en la primera conexión.
+pub fn is_learning(profile: &NetworkProfile) -> bool {
+    profile.trust_on_first_use && profile.cert_sha256.is_none()
+}
+
+/// Pines con los que se conecta un perfil en aprendizaje: ninguno, para que
+/// el handshake siempre falle y entregue la huella del certificado.
+pub fn learning_pin_config(profile: &NetworkProfile) -> PinConfig {
+    PinConfig::new(PinSet::new(&profile.id, Vec::new()))
+}
+
+/// Registra una función que recibe cada huella nueva pendiente de aprobación.
+pub fn on_pin_pending(listener: impl Fn(&PendingPin) + Send + 'static) {
+    match PENDING_LISTENERS.lock() {
+        Ok(mut guard) => guard.push(Box::new(listener)),
+        Err(poisoned) => poisoned.into_inner().push(Box::new(listener)),
+    }
+}
+
+/// Si `err` es el rechazo del certificado de un perfil en aprendizaje, guarda
+/// la huella como pendiente y lo convierte en `PinApprovalRequired`. Cualquier
+/// otro error, o una huella que el usuario ya rechazó, se devuelve sin cambios.
+pub fn learn_from_error(profile: Option<&NetworkProfile>, err: AuthError) -> AuthError {
+    let Some(profile) = profile.filter(|p| is_learning(p)) else {
+        return err;
+    };
+    let Some(mismatch) = err.pin_mismatch() else {
+        return err;
+    };
+    if is_rejected(&profile.id, &mismatch.observed_cert_sha256) {
+        return err;
+    }
+
+    let pending = PendingPin {
+        profile_id: profile.id.clone(),
+        profile_name: profile.name.clone(),
+        portal_url: profile.portal_url.clone(),
+        cert_sha256: mismatch.observed_cert_sha256.clone(),
+        spki_sha256: mismatch.observed_spki_sha256.clone(),
+        observed_at_ms: history::now_ms(),
+    };
+
+    let is_new = {
+        let mut pins = lock_pending();
+        let is_new = !pins
+            .iter()
+            .any(|p| p.profile_id == pending.profile_id && p.cert_sha256 == pending.cert_sha256);
+        pins.retain(|p| p.profile_id != pending.profile_id);
+        pins.push(pending.clone());
+        is_new
+    };
+
+    // El monitor reintenta en cada ciclo; solo se avisa la primera vez que se
+    // ve una huella para no repetir la solicitud de aprobación.
+    if is_new {
+        eprintln!(
+            "[tofu] Certificado nuevo para el perfil {}: cert-sha256:{}",
+            pending.profile_id, pending.cert_sha256
+        );
+        let listeners = match PENDING_LISTENERS.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        for listener in listeners.iter() {
+            listener(&pending);
+        }
+    }
+
+    AuthError::PinApprovalRequired {
+        profile_id: pending.profile_id,
+        cert_sha256: pending.cert_sha256,
+    }
+}
+
+/// Huellas observadas que esperan la decisión del usuario.
+pub fn pending() -> Vec<PendingPin> {
+    lock_pending().clone()
+}
+
+/// Guarda en el perfil la huella pendiente y la exige desde ese momento.
+/// `cert_sha256` debe ser la huella que se le mostró al usuario: si el portal
+/// presentó otra después, la aprobación se rechaza.
+pub fn approve(profile_id: &str, cert_sha256: &str) -> Result<NetworkProfile, String> {
+    let pending = lock_pending()
+        .iter()
+        .find(|p| p.profile_id == profile_id)
+        .cloned()
+        .ok_or_else(|| "No hay un certificado pendiente para este perfil".to_string())?;
+
+    if !pending.cert_sha256.eq_ignore_ascii_case(cert_sha256.trim()) {
+        return Err("La huella no coincide con el certificado pendiente".to_string());
+    }
+
+    let mut profile =
+        profiles::get(profile_id).ok_or_else(|| "Perfil de red no encontrado".to_string())?;
+    profile.cert_sha256 = Some(format!("cert-sha256:{}", pending.cert_sha256));
+    profiles::save(profile.clone())?;
+
+    lock_pending().retain(|p| p.profile_id != profile_id);
+    eprintln!("[tofu] Pin aprobado para el perfil {}", profile_id);
+    Ok(profile)
+}
+
+/// Descarta la huella pendiente. Ese certificado se trata como una falla de
+/// pin hasta reiniciar la app; uno distinto volverá a pedir aprobación.
+pub fn reject(profile_id: &str) -> Result<(), String> {
+    let rejected = {
+        let mut pins = lock_pending();
+        let index = pins
+            .iter()
+            .position(|p| p.profile_id == profile_id)
+            .ok_or_else(|| "No hay un certificado pendiente para este perfil".to_string())?;
+        pins.remove(index)
+    };
+
+    eprintln!("[tofu] Certificado rechazado para el perfil {}", profile_id);
+    match REJECTED_PINS.lock() {
+        Ok(mut guard) => guard.push((rejected.profile_id, rejected.cert_sha256)),
+        Err(poisoned) => poisoned
+            .into_inner()
+            .push((rejected.profile_id, rejected.cert_sha256)),
+    }
+    Ok(())
+}
+
+fn is_rejected(profile_id: &str, cert_sha256: &str) -> bool {
+    let rejected = match REJECTED_PINS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    rejected
+        .iter()
+        .any(|(id, cert)| id == profile_id && cert == cert_sha256)
+}
+
+fn lock_pending() -> std::sync::MutexGuard<'static, Vec<PendingPin>> {
+    match PENDING_PINS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
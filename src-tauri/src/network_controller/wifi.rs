@@ -0,0 +1,83 @@
+//! Datos del enlace WiFi actual, comunes a todos los backends del sistema.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+// NM80211ApFlags y NM80211ApSecurityFlags de NetworkManager.
+const NM_AP_FLAGS_PRIVACY: u32 = 0x1;
+const NM_AP_SEC_KEY_MGMT_PSK: u32 = 0x100;
+const NM_AP_SEC_KEY_MGMT_802_1X: u32 = 0x200;
+const NM_AP_SEC_KEY_MGMT_SAE: u32 = 0x400;
+const NM_AP_SEC_KEY_MGMT_OWE: u32 = 0x800;
+const NM_AP_SEC_KEY_MGMT_OWE_TM: u32 = 0x1000;
+const NM_AP_SEC_KEY_MGMT_EAP_SUITE_B_192: u32 = 0x2000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, TS)]
+pub struct WifiInfo {
+    pub interface: String,
+    pub ssid: Option<String>,
+    pub bssid: Option<String>,
+    /// Intensidad de la señal en porcentaje (0–100).
+    pub signal: Option<u8>,
+    pub rssi_dbm: Option<i32>,
+    pub link_speed_mbps: Option<u32>,
+    pub frequency_mhz: Option<u32>,
+    pub security: WifiSecurity,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WifiSecurity {
+    Open,
+    /// Red abierta con cifrado oportunista (Enhanced Open).
+    Owe,
+    Wep,
+    WpaPersonal,
+    Wpa3Personal,
+    /// 802.1X, como eduroam.
+    WpaEnterprise,
+    #[default]
+    Unknown,
+}
+
+impl WifiSecurity {
+    /// Seguridad anunciada por un AccessPoint de NetworkManager a partir de
+    /// sus propiedades `Flags`, `WpaFlags` y `RsnFlags`.
+    pub fn from_nm_flags(flags: u32, wpa_flags: u32, rsn_flags: u32) -> Self {
+        let key_mgmt = wpa_flags | rsn_flags;
+
+        if key_mgmt & (NM_AP_SEC_KEY_MGMT_802_1X | NM_AP_SEC_KEY_MGMT_EAP_SUITE_B_192) != 0 {
+            WifiSecurity::WpaEnterprise
+        } else if key_mgmt & NM_AP_SEC_KEY_MGMT_SAE != 0 {
+            WifiSecurity::Wpa3Personal
+        } else if key_mgmt & NM_AP_SEC_KEY_MGMT_PSK != 0 {
+            WifiSecurity::WpaPersonal
+        } else if key_mgmt & (NM_AP_SEC_KEY_MGMT_OWE | NM_AP_SEC_KEY_MGMT_OWE_TM) != 0 {
+            WifiSecurity::Owe
+        } else if flags & NM_AP_FLAGS_PRIVACY != 0 {
+            WifiSecurity::Wep
+        } else {
+            WifiSecurity::Open
+        }
+    }
+}
+
+/// Frecuencia central de un canal de 2.4 o 5 GHz. Los canales de 6 GHz
+/// reutilizan los mismos números y no se pueden distinguir sin la banda.
+pub fn channel_frequency_mhz(channel: u32) -> Option<u32> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel),
+        14 => Some(2484),
+        32..=177 => Some(5000 + 5 * channel),
+        _ => None,
+    }
+}
+
+/// El SSID son hasta 32 bytes arbitrarios: se interpreta como UTF-8 sin
+/// escapes (admite `:` y `\`), y una red oculta (vacío o solo ceros) no tiene SSID.
+pub fn decode_ssid(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
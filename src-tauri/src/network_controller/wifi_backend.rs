@@ -0,0 +1,209 @@
+//! Fuentes del enlace WiFi actual. Cada sistema tiene un backend nativo
+//! (NetworkManager, WLAN API) y, detrás, las herramientas de línea de
+//! comandos; `network_sync` los prueba en orden hasta que uno responde.
+//!
+//! Los backends de comandos reciben un `CommandRunner`, así que con salidas
+//! grabadas se prueban en cualquier sistema.
+
+use std::process::Command;
+
+use crate::network_controller::profiles;
+use crate::network_controller::wifi::WifiInfo;
+use crate::network_controller::wifi_parse;
+
+pub trait WifiBackend: Send + Sync {
+    /// Nombre para los logs.
+    fn name(&self) -> &'static str;
+
+    /// Enlace de `interface`. `Ok(None)` si el backend sabe que no está
+    /// asociada; `Err` si no pudo averiguarlo y conviene probar el siguiente.
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String>;
+}
+
+/// Ejecuta una herramienta del sistema y devuelve su salida estándar.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String>;
+}
+
+/// Lanza los procesos de verdad.
+pub struct SystemCommands;
+
+impl CommandRunner for SystemCommands {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("No se pudo ejecutar {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!("{} terminó con {}", program, output.status));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Linux sin NetworkManager por D-Bus: `iwgetid` y, si no está, `nmcli`.
+pub struct LinuxCommands<R> {
+    runner: R,
+}
+
+impl<R: CommandRunner> LinuxCommands<R> {
+    pub fn new(runner: R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<R: CommandRunner> WifiBackend for LinuxCommands<R> {
+    fn name(&self) -> &'static str {
+        "iwgetid/nmcli"
+    }
+
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String> {
+        let bssid = self
+            .runner
+            .run("iwgetid", &[interface, "--ap", "--raw"])
+            .ok()
+            .and_then(|out| wifi_parse::parse_iwgetid(&out))
+            .filter(|mac| profiles::normalize_mac(mac).is_some());
+        let info = |ssid: Option<String>| WifiInfo {
+            interface: interface.to_string(),
+            ssid,
+            bssid: bssid.clone(),
+            ..WifiInfo::default()
+        };
+
+        let iwgetid = self.runner.run("iwgetid", &[interface, "--raw"]);
+        if let Some(ssid) = iwgetid.as_deref().ok().and_then(wifi_parse::parse_iwgetid) {
+            return Ok(Some(info(Some(ssid))));
+        }
+
+        let nmcli = self
+            .runner
+            .run("nmcli", &["-t", "-f", "active,ssid,device", "dev", "wifi"])
+            .map_err(|nmcli_err| match &iwgetid {
+                Err(iwgetid_err) => format!("{iwgetid_err}; {nmcli_err}"),
+                Ok(_) => nmcli_err,
+            })?;
+
+        // Una red oculta aparece activa con el SSID vacío
+        Ok(wifi_parse::nmcli_active_line(&nmcli, interface)
+            .map(|line| info(Some(line.ssid).filter(|s| !s.is_empty()))))
+    }
+}
+
+/// Windows sin WLAN API: `netsh` y, como último recurso, el nombre del perfil
+/// de red de PowerShell (que suele coincidir con el SSID).
+pub struct WindowsCommands<R> {
+    runner: R,
+}
+
+impl<R: CommandRunner> WindowsCommands<R> {
+    pub fn new(runner: R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<R: CommandRunner> WifiBackend for WindowsCommands<R> {
+    fn name(&self) -> &'static str {
+        "netsh"
+    }
+
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String> {
+        let netsh = self.runner.run("netsh", &["wlan", "show", "interfaces"]);
+        if let Some(block) = netsh
+            .as_deref()
+            .ok()
+            .and_then(|out| wifi_parse::netsh_interface(out, interface))
+        {
+            let info = WifiInfo {
+                interface: interface.to_string(),
+                ..block.info
+            };
+            return Ok(block.connected.then_some(info));
+        }
+
+        let escaped_name = interface.replace('\'', "''");
+        let command = format!(
+            "(Get-NetConnectionProfile | Where-Object {{ $_.InterfaceAlias -like '*{}*' }}).Name",
+            escaped_name
+        );
+        let ssid = self
+            .runner
+            .run(
+                "powershell",
+                &["-NoProfile", "-NonInteractive", "-Command", &command],
+            )
+            .ok()
+            .and_then(|out| wifi_parse::parse_iwgetid(&out));
+
+        match (ssid, netsh) {
+            (Some(ssid), _) => Ok(Some(WifiInfo {
+                interface: interface.to_string(),
+                ssid: Some(ssid),
+                ..WifiInfo::default()
+            })),
+            (None, Err(err)) => Err(err),
+            (None, Ok(_)) => Err("netsh no lista la interfaz".to_string()),
+        }
+    }
+}
+
+const AIRPORT_PATHS: [&str; 2] = [
+    "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport",
+    "/usr/sbin/airport",
+];
+
+/// macOS: `airport` (retirado en 14.4), `networksetup` y `system_profiler`.
+pub struct MacCommands<R> {
+    runner: R,
+}
+
+impl<R: CommandRunner> MacCommands<R> {
+    pub fn new(runner: R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<R: CommandRunner> WifiBackend for MacCommands<R> {
+    fn name(&self) -> &'static str {
+        "airport/networksetup"
+    }
+
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String> {
+        for airport in AIRPORT_PATHS {
+            if let Some(info) = self
+                .runner
+                .run(airport, &["-I"])
+                .ok()
+                .and_then(|out| wifi_parse::parse_airport_info(&out, interface))
+            {
+                return Ok(Some(info));
+            }
+        }
+
+        let mut candidates = vec![interface];
+        candidates.extend(
+            ["en0", "en1"]
+                .into_iter()
+                .filter(|iface| *iface != interface),
+        );
+        for iface in candidates {
+            if let Some(ssid) = self
+                .runner
+                .run("networksetup", &["-getairportnetwork", iface])
+                .ok()
+                .and_then(|out| wifi_parse::parse_networksetup_network(&out))
+            {
+                return Ok(Some(WifiInfo {
+                    interface: interface.to_string(),
+                    ssid: Some(ssid),
+                    ..WifiInfo::default()
+                }));
+            }
+        }
+
+        let profiler = self.runner.run("system_profiler", &["SPAirPortDataType"])?;
+        wifi_parse::parse_system_profiler(&profiler, interface)
+            .map(Some)
+            .ok_or_else(|| "Sin red actual en system_profiler".to_string())
+    }
+}
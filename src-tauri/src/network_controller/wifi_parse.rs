@@ -0,0 +1,322 @@
+//! Parseo de la salida de las herramientas de línea de comandos que reportan
+//! el enlace WiFi (iwgetid, nmcli, netsh, airport, networksetup y
+//! system_profiler). Solo trabaja con texto, así que se prueba en cualquier
+//! sistema con salidas grabadas en tests/fixtures/wifi.
+
+use crate::network_controller::profiles;
+use crate::network_controller::wifi::{self, WifiInfo, WifiSecurity};
+
+/// Valores con los que netsh indica que no hay dato.
+const NETSH_EMPTY_VALUES: [&str; 3] = ["N/A", "Ninguno", "None"];
+
+/// Bloque de `netsh wlan show interfaces`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetshInterface {
+    pub info: WifiInfo,
+    /// Estado "connected"/"conectado"; una red oculta está conectada sin SSID.
+    pub connected: bool,
+}
+
+/// Línea de `nmcli -t -f active,ssid,device dev wifi`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NmcliWifiLine {
+    pub active: bool,
+    pub ssid: String,
+    pub device: String,
+}
+
+/// Valor que sigue al primer `:` de una línea `Clave: valor`. Solo se corta en
+/// el primero porque SSIDs y MACs pueden contener `:`.
+pub fn extract_ssid_from_line(line: &str) -> Option<String> {
+    line.find(':')
+        .map(|pos| line[pos + 1..].trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn split_field(line: &str) -> Option<(String, &str)> {
+    let pos = line.find(':')?;
+    Some((line[..pos].trim().to_lowercase(), line[pos + 1..].trim()))
+}
+
+/// Salida de `iwgetid <iface> --raw` (o `--ap --raw`).
+pub fn parse_iwgetid(output: &str) -> Option<String> {
+    Some(output.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// En modo `-t` nmcli escapa `:` y `\` dentro de los campos; el SSID queda
+/// entre el primer y el último separador.
+pub fn parse_nmcli_wifi_line(line: &str) -> Option<NmcliWifiLine> {
+    let first = line.find(':')?;
+    let last = line.rfind(':')?;
+    if first == last {
+        return None;
+    }
+
+    let ssid = line[first + 1..last]
+        .replace(r#"\:"#, ":")
+        .replace(r#"\\"#, r#"\"#)
+        .trim()
+        .to_string();
+
+    Some(NmcliWifiLine {
+        active: line[..first].trim() == "yes",
+        ssid,
+        device: line[last + 1..].trim().to_string(),
+    })
+}
+
+/// Punto de acceso activo de `device` en la salida de nmcli, si lo hay.
+pub fn nmcli_active_line(output: &str, device: &str) -> Option<NmcliWifiLine> {
+    output
+        .lines()
+        .filter_map(parse_nmcli_wifi_line)
+        .find(|line| line.active && line.device == device)
+}
+
+/// Seguridad a partir de la etiqueta de netsh o de macOS
+/// ("WPA2-Enterprise", "WPA3 Personal", "Abierta"...).
+pub fn security_from_label(label: &str) -> WifiSecurity {
+    let label = label.to_lowercase();
+
+    if label.contains("enterprise") || label.contains("empresa") || label.contains("802.1x") {
+        WifiSecurity::WpaEnterprise
+    } else if label.contains("wpa3") {
+        WifiSecurity::Wpa3Personal
+    } else if label.contains("wpa") {
+        WifiSecurity::WpaPersonal
+    } else if label.contains("wep") {
+        WifiSecurity::Wep
+    } else if label.contains("owe") || label.contains("enhanced open") {
+        WifiSecurity::Owe
+    } else if matches!(label.trim(), "open" | "abierta" | "none" | "ninguna") {
+        WifiSecurity::Open
+    } else {
+        WifiSecurity::Unknown
+    }
+}
+
+fn leading_number(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+        .map_or(value.len(), |(i, _)| i);
+    value[..end].parse().ok()
+}
+
+/// Frecuencia del canal; macOS indica la banda entre paréntesis, lo que
+/// permite distinguir los canales de 6 GHz.
+fn channel_frequency(value: &str) -> Option<u32> {
+    let channel = u32::try_from(leading_number(value)?).ok()?;
+    if value.contains("6GHz") {
+        Some(5950 + 5 * channel)
+    } else {
+        wifi::channel_frequency_mhz(channel)
+    }
+}
+
+/// Velocidad en Mbps; los decimales de netsh ("866.7") se truncan.
+fn link_speed(value: &str) -> Option<u32> {
+    leading_number(value)
+        .and_then(|n| u32::try_from(n).ok())
+        .filter(|&rate| rate > 0)
+}
+
+fn valid_bssid(value: &str) -> Option<String> {
+    profiles::normalize_mac(value).map(|_| value.to_string())
+}
+
+/// Nombre del campo de netsh en inglés o español. La consola de Windows usa
+/// la página de códigos OEM, así que los acentos llegan como U+FFFD y solo se
+/// compara la parte ASCII.
+fn netsh_key(key: &str) -> Option<&'static str> {
+    let ascii: String = key.chars().filter(char::is_ascii).collect();
+    match ascii.as_str() {
+        "name" | "nombre" => Some("name"),
+        "state" | "estado" => Some("state"),
+        "ssid" => Some("ssid"),
+        "bssid" | "ap bssid" => Some("bssid"),
+        "signal" | "seal" => Some("signal"),
+        "channel" | "canal" => Some("channel"),
+        "authentication" | "autenticacin" => Some("security"),
+        "receive rate (mbps)" | "velocidad de recepcin (mbps)" => Some("rate"),
+        _ => None,
+    }
+}
+
+/// Interfaces de `netsh wlan show interfaces`, una por bloque `Name`/`Nombre`.
+pub fn parse_netsh_interfaces(output: &str) -> Vec<NetshInterface> {
+    let mut interfaces: Vec<NetshInterface> = Vec::new();
+
+    for line in output.lines() {
+        let Some((key, value)) = split_field(line) else {
+            continue;
+        };
+        let Some(field) = netsh_key(&key) else {
+            continue;
+        };
+
+        if field == "name" {
+            interfaces.push(NetshInterface {
+                info: WifiInfo {
+                    interface: value.to_string(),
+                    ..WifiInfo::default()
+                },
+                connected: false,
+            });
+            continue;
+        }
+        let Some(NetshInterface {
+            info: current,
+            connected,
+        }) = interfaces.last_mut()
+        else {
+            continue;
+        };
+
+        match field {
+            "state" => {
+                *connected = value.eq_ignore_ascii_case("connected")
+                    || value.eq_ignore_ascii_case("conectado");
+            }
+            "ssid" => {
+                current.ssid = Some(value.to_string())
+                    .filter(|s| !s.is_empty() && !NETSH_EMPTY_VALUES.contains(&s.as_str()));
+            }
+            "bssid" => current.bssid = valid_bssid(value),
+            "signal" => {
+                current.signal = leading_number(value)
+                    .and_then(|n| u8::try_from(n).ok())
+                    .map(|n| n.min(100));
+            }
+            "channel" => current.frequency_mhz = channel_frequency(value),
+            "security" => current.security = security_from_label(value),
+            "rate" => current.link_speed_mbps = link_speed(value),
+            _ => {}
+        }
+    }
+
+    interfaces
+}
+
+/// Bloque de netsh de `interface_name`, o el primero conectado si ninguno
+/// coincide (el nombre de netwatcher no siempre es el alias de netsh).
+pub fn netsh_interface(output: &str, interface_name: &str) -> Option<NetshInterface> {
+    let interfaces = parse_netsh_interfaces(output);
+    let position = interfaces
+        .iter()
+        .position(|iface| iface.info.interface.eq_ignore_ascii_case(interface_name))
+        .or_else(|| interfaces.iter().position(|iface| iface.connected))?;
+    interfaces.into_iter().nth(position)
+}
+
+/// Salida de `airport -I`. `None` si no está asociado.
+pub fn parse_airport_info(output: &str, interface_name: &str) -> Option<WifiInfo> {
+    let mut info = WifiInfo {
+        interface: interface_name.to_string(),
+        ..WifiInfo::default()
+    };
+
+    for line in output.lines() {
+        let Some((key, value)) = split_field(line) else {
+            continue;
+        };
+        match key.as_str() {
+            "ssid" => info.ssid = Some(value.to_string()).filter(|s| !s.is_empty()),
+            "bssid" => info.bssid = valid_bssid(value),
+            "agrctlrssi" => info.rssi_dbm = leading_number(value).map(|n| n as i32),
+            "lasttxrate" => info.link_speed_mbps = link_speed(value),
+            "channel" => info.frequency_mhz = channel_frequency(value),
+            "link auth" => info.security = security_from_label(value),
+            _ => {}
+        }
+    }
+
+    info.ssid.is_some().then_some(info)
+}
+
+/// Salida de `networksetup -getairportnetwork <iface>`:
+/// "Current Wi-Fi Network: <ssid>" o un aviso de que no está asociado.
+pub fn parse_networksetup_network(output: &str) -> Option<String> {
+    let line = output.lines().next()?;
+    if line.contains("not associated") {
+        return None;
+    }
+    extract_ssid_from_line(line)
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Red actual de `interface_name` en `system_profiler SPAirPortDataType`. El
+/// SSID es la clave del bloque que sigue a "Current Network Information:"; en
+/// macOS 14.4+ aparece como `<redacted>` si la app no tiene permiso de
+/// ubicación.
+pub fn parse_system_profiler(output: &str, interface_name: &str) -> Option<WifiInfo> {
+    let mut interface_indent = None;
+    let mut current_indent = None;
+    let mut network_indent = None;
+    let mut info: Option<WifiInfo> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = indentation(line);
+
+        // Al volver a un nivel igual o menor se cierra el bloque abierto
+        if network_indent.is_some_and(|level| indent <= level) {
+            break;
+        }
+        if current_indent.is_some_and(|level| indent <= level) {
+            current_indent = None;
+        }
+        if interface_indent.is_some_and(|level| indent <= level) {
+            interface_indent = None;
+        }
+
+        if interface_indent.is_none() {
+            if trimmed.strip_suffix(':') == Some(interface_name) {
+                interface_indent = Some(indent);
+            }
+            continue;
+        }
+
+        if current_indent.is_none() {
+            if trimmed == "Current Network Information:" {
+                current_indent = Some(indent);
+            }
+            continue;
+        }
+
+        match (&mut info, trimmed.strip_suffix(':')) {
+            (None, Some(ssid)) => {
+                network_indent = Some(indent);
+                info = Some(WifiInfo {
+                    interface: interface_name.to_string(),
+                    ssid: Some(ssid.to_string()).filter(|s| s != "<redacted>"),
+                    ..WifiInfo::default()
+                });
+            }
+            (Some(info), _) => {
+                let Some((key, value)) = split_field(trimmed) else {
+                    continue;
+                };
+                match key.as_str() {
+                    "channel" => info.frequency_mhz = channel_frequency(value),
+                    "security" => info.security = security_from_label(value),
+                    "transmit rate" => info.link_speed_mbps = link_speed(value),
+                    "signal / noise" => {
+                        info.rssi_dbm = leading_number(value).map(|n| n as i32);
+                    }
+                    _ => {}
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    info
+}
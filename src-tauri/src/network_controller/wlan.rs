@@ -0,0 +1,173 @@
+//! Lectura del enlace WiFi a partir de la WLAN API de Windows.
+//!
+//! Las llamadas a `wlanapi.dll` quedan detrás de `WlanSource`; aquí solo se
+//! interpretan los datos crudos que devuelven (`WLAN_CONNECTION_ATTRIBUTES`,
+//! RSSI y canal), así que este módulo compila y se prueba en cualquier sistema
+//! con capturas guardadas como fixtures.
+
+use crate::network_controller::wifi::{self, WifiInfo, WifiSecurity};
+
+/// `wlan_interface_state_connected`
+const INTERFACE_STATE_CONNECTED: u32 = 1;
+
+// Desplazamientos dentro de WLAN_CONNECTION_ATTRIBUTES. Todos sus campos son
+// de 4 bytes o arreglos de bytes, así que el layout es igual en x86 y x64.
+const STATE_OFFSET: usize = 0;
+const ASSOCIATION_OFFSET: usize = 8 + 256 * 2;
+const SSID_LENGTH_OFFSET: usize = ASSOCIATION_OFFSET;
+const SSID_OFFSET: usize = ASSOCIATION_OFFSET + 4;
+const SSID_MAX_LEN: usize = 32;
+const BSSID_OFFSET: usize = ASSOCIATION_OFFSET + 40;
+const SIGNAL_QUALITY_OFFSET: usize = ASSOCIATION_OFFSET + 56;
+const RX_RATE_OFFSET: usize = ASSOCIATION_OFFSET + 60;
+const SECURITY_OFFSET: usize = ASSOCIATION_OFFSET + 68;
+pub const CONNECTION_ATTRIBUTES_SIZE: usize = SECURITY_OFFSET + 16;
+
+// DOT11_AUTH_ALGORITHM
+const AUTH_80211_OPEN: u32 = 1;
+const AUTH_80211_SHARED_KEY: u32 = 2;
+const AUTH_WPA: u32 = 3;
+const AUTH_WPA_PSK: u32 = 4;
+const AUTH_RSNA: u32 = 6;
+const AUTH_RSNA_PSK: u32 = 7;
+const AUTH_WPA3_ENT_192: u32 = 8;
+const AUTH_WPA3_SAE: u32 = 9;
+const AUTH_OWE: u32 = 10;
+const AUTH_WPA3_ENT: u32 = 11;
+
+/// Adaptador WLAN reportado por `WlanEnumInterfaces`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WlanInterface {
+    pub guid: String,
+    pub description: String,
+}
+
+/// Datos crudos de la WLAN API. El backend real llama a `wlanapi.dll`; las
+/// pruebas reproducen capturas grabadas.
+pub trait WlanSource {
+    fn interfaces(&self) -> Result<Vec<WlanInterface>, String>;
+
+    /// Bytes de `WLAN_CONNECTION_ATTRIBUTES` (`wlan_intf_opcode_current_connection`),
+    /// o `None` si la interfaz no está conectada.
+    fn connection_attributes(&self, interface: &WlanInterface) -> Result<Option<Vec<u8>>, String>;
+
+    /// `wlan_intf_opcode_rssi`, en dBm.
+    fn rssi(&self, interface: &WlanInterface) -> Result<Option<i32>, String>;
+
+    /// `wlan_intf_opcode_channel_number`.
+    fn channel(&self, interface: &WlanInterface) -> Result<Option<u32>, String>;
+}
+
+/// Conexión decodificada de `WLAN_CONNECTION_ATTRIBUTES`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WlanConnection {
+    pub ssid: Option<String>,
+    pub bssid: String,
+    /// `wlanSignalQuality`, 0–100.
+    pub signal_quality: u8,
+    /// `ulRxRate`, convertido de kbps a Mbps.
+    pub rx_rate_mbps: u32,
+    pub security: WifiSecurity,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(word)
+}
+
+/// Decodifica `WLAN_CONNECTION_ATTRIBUTES`; `Ok(None)` si la interfaz no
+/// está en estado conectado.
+pub fn parse_connection_attributes(bytes: &[u8]) -> Result<Option<WlanConnection>, String> {
+    if bytes.len() < CONNECTION_ATTRIBUTES_SIZE {
+        return Err(format!(
+            "WLAN_CONNECTION_ATTRIBUTES incompleto: {} de {} bytes",
+            bytes.len(),
+            CONNECTION_ATTRIBUTES_SIZE
+        ));
+    }
+
+    if read_u32(bytes, STATE_OFFSET) != INTERFACE_STATE_CONNECTED {
+        return Ok(None);
+    }
+
+    let ssid_len = read_u32(bytes, SSID_LENGTH_OFFSET) as usize;
+    if ssid_len > SSID_MAX_LEN {
+        return Err(format!("Longitud de SSID inválida: {}", ssid_len));
+    }
+    let ssid = wifi::decode_ssid(&bytes[SSID_OFFSET..SSID_OFFSET + ssid_len]);
+
+    let bssid = bytes[BSSID_OFFSET..BSSID_OFFSET + 6]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    let security_enabled = read_u32(bytes, SECURITY_OFFSET) != 0;
+    let auth_algorithm = read_u32(bytes, SECURITY_OFFSET + 8);
+
+    Ok(Some(WlanConnection {
+        ssid,
+        bssid,
+        signal_quality: read_u32(bytes, SIGNAL_QUALITY_OFFSET).min(100) as u8,
+        rx_rate_mbps: read_u32(bytes, RX_RATE_OFFSET) / 1000,
+        security: security_from_auth(auth_algorithm, security_enabled),
+    }))
+}
+
+fn security_from_auth(auth_algorithm: u32, security_enabled: bool) -> WifiSecurity {
+    match auth_algorithm {
+        AUTH_80211_OPEN if security_enabled => WifiSecurity::Wep,
+        AUTH_80211_OPEN => WifiSecurity::Open,
+        AUTH_80211_SHARED_KEY => WifiSecurity::Wep,
+        AUTH_WPA | AUTH_RSNA | AUTH_WPA3_ENT_192 | AUTH_WPA3_ENT => WifiSecurity::WpaEnterprise,
+        AUTH_WPA_PSK | AUTH_RSNA_PSK => WifiSecurity::WpaPersonal,
+        AUTH_WPA3_SAE => WifiSecurity::Wpa3Personal,
+        AUTH_OWE => WifiSecurity::Owe,
+        _ => WifiSecurity::Unknown,
+    }
+}
+
+/// Enlace actual de la interfaz conectada. `interface_name` es el nombre que
+/// ve netwatcher; la WLAN API solo da GUID y descripción, así que se prefiere
+/// el adaptador que coincida y si no el primero conectado.
+pub fn read_wifi_info(
+    source: &dyn WlanSource,
+    interface_name: &str,
+) -> Result<Option<WifiInfo>, String> {
+    let mut interfaces = source.interfaces()?;
+    interfaces.sort_by_key(|iface| {
+        !(iface.guid.eq_ignore_ascii_case(interface_name)
+            || iface.description.eq_ignore_ascii_case(interface_name))
+    });
+
+    for interface in &interfaces {
+        let Some(bytes) = source.connection_attributes(interface)? else {
+            continue;
+        };
+        let Some(connection) = parse_connection_attributes(&bytes)? else {
+            continue;
+        };
+
+        // RSSI y canal son opcionales: algunos controladores no los reportan
+        let rssi_dbm = source.rssi(interface).ok().flatten();
+        let frequency_mhz = source
+            .channel(interface)
+            .ok()
+            .flatten()
+            .and_then(wifi::channel_frequency_mhz);
+
+        return Ok(Some(WifiInfo {
+            interface: interface_name.to_string(),
+            ssid: connection.ssid,
+            bssid: Some(connection.bssid),
+            signal: Some(connection.signal_quality),
+            rssi_dbm,
+            link_speed_mbps: Some(connection.rx_rate_mbps).filter(|&rate| rate > 0),
+            frequency_mhz,
+            security: connection.security,
+        }));
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,238 @@
+//! Backend de Windows: consulta el enlace WiFi a la WLAN API nativa.
+//!
+//! Sustituye el parseo de `netsh wlan show interfaces`, que depende del idioma
+//! del sistema y no da el RSSI, y permite recibir avisos de conexión y
+//! desconexión en lugar de esperar a un cambio de IP. La interpretación de los
+//! datos vive en `wlan` para poder probarla fuera de Windows.
+
+use std::ffi::c_void;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{ERROR_INVALID_STATE, ERROR_SUCCESS, HANDLE};
+use windows_sys::Win32::NetworkManagement::WiFi::{
+    L2_NOTIFICATION_DATA, WLAN_INTERFACE_INFO, WLAN_INTERFACE_INFO_LIST, WLAN_INTF_OPCODE,
+    WLAN_NOTIFICATION_SOURCE_ACM, WlanCloseHandle, WlanEnumInterfaces, WlanFreeMemory,
+    WlanOpenHandle, WlanQueryInterface, WlanRegisterNotification, wlan_intf_opcode_channel_number,
+    wlan_intf_opcode_current_connection, wlan_intf_opcode_rssi,
+    wlan_notification_acm_connection_complete, wlan_notification_acm_disconnected,
+};
+use windows_sys::core::GUID;
+
+use crate::network_controller::wifi::WifiInfo;
+use crate::network_controller::wifi_backend::WifiBackend;
+use crate::network_controller::wlan::{self, WlanInterface, WlanSource};
+
+/// Versión del cliente para Windows Vista y posteriores.
+const CLIENT_VERSION: u32 = 2;
+
+/// Sesión abierta con el servicio WLAN AutoConfig.
+pub struct NativeWlan {
+    handle: HANDLE,
+}
+
+impl NativeWlan {
+    pub fn open() -> Result<Self, String> {
+        let mut negotiated = 0u32;
+        let mut handle: HANDLE = ptr::null_mut();
+        let status =
+            unsafe { WlanOpenHandle(CLIENT_VERSION, ptr::null(), &mut negotiated, &mut handle) };
+        if status != ERROR_SUCCESS {
+            return Err(format!("WlanOpenHandle falló con código {}", status));
+        }
+        Ok(Self { handle })
+    }
+
+    fn query(
+        &self,
+        interface: &WlanInterface,
+        opcode: WLAN_INTF_OPCODE,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let guid = parse_guid(&interface.guid)?;
+        let mut size = 0u32;
+        let mut data: *mut c_void = ptr::null_mut();
+        let status = unsafe {
+            WlanQueryInterface(
+                self.handle,
+                &guid,
+                opcode,
+                ptr::null(),
+                &mut size,
+                &mut data,
+                ptr::null_mut(),
+            )
+        };
+
+        // ERROR_INVALID_STATE: la interfaz no está conectada y no hay datos
+        if status == ERROR_INVALID_STATE {
+            return Ok(None);
+        }
+        if status != ERROR_SUCCESS {
+            return Err(format!(
+                "WlanQueryInterface({}) falló con código {}",
+                opcode, status
+            ));
+        }
+
+        let bytes =
+            unsafe { std::slice::from_raw_parts(data as *const u8, size as usize).to_vec() };
+        unsafe { WlanFreeMemory(data) };
+        Ok(Some(bytes))
+    }
+
+    fn query_u32(
+        &self,
+        interface: &WlanInterface,
+        opcode: WLAN_INTF_OPCODE,
+    ) -> Result<Option<u32>, String> {
+        Ok(self
+            .query(interface, opcode)?
+            .filter(|bytes| bytes.len() >= 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+    }
+}
+
+impl Drop for NativeWlan {
+    fn drop(&mut self) {
+        unsafe { WlanCloseHandle(self.handle, ptr::null()) };
+    }
+}
+
+impl WlanSource for NativeWlan {
+    fn interfaces(&self) -> Result<Vec<WlanInterface>, String> {
+        let mut list: *mut WLAN_INTERFACE_INFO_LIST = ptr::null_mut();
+        let status = unsafe { WlanEnumInterfaces(self.handle, ptr::null(), &mut list) };
+        if status != ERROR_SUCCESS {
+            return Err(format!("WlanEnumInterfaces falló con código {}", status));
+        }
+
+        let interfaces = unsafe {
+            let count = (*list).dwNumberOfItems as usize;
+            let first = ptr::addr_of!((*list).InterfaceInfo) as *const WLAN_INTERFACE_INFO;
+            std::slice::from_raw_parts(first, count)
+                .iter()
+                .map(|info| WlanInterface {
+                    guid: format_guid(&info.InterfaceGuid),
+                    description: utf16_until_nul(&info.strInterfaceDescription),
+                })
+                .collect()
+        };
+        unsafe { WlanFreeMemory(list as *const c_void) };
+        Ok(interfaces)
+    }
+
+    fn connection_attributes(&self, interface: &WlanInterface) -> Result<Option<Vec<u8>>, String> {
+        self.query(interface, wlan_intf_opcode_current_connection)
+    }
+
+    fn rssi(&self, interface: &WlanInterface) -> Result<Option<i32>, String> {
+        Ok(self
+            .query_u32(interface, wlan_intf_opcode_rssi)?
+            .map(|raw| raw as i32))
+    }
+
+    fn channel(&self, interface: &WlanInterface) -> Result<Option<u32>, String> {
+        self.query_u32(interface, wlan_intf_opcode_channel_number)
+    }
+}
+
+/// Enlace WiFi actual según la WLAN API. `Ok(None)` si ningún adaptador
+/// está conectado.
+pub fn wifi_info(interface_name: &str) -> Result<Option<WifiInfo>, String> {
+    let wlan = NativeWlan::open()?;
+    wlan::read_wifi_info(&wlan, interface_name)
+}
+
+/// Backend nativo de Windows; abre una sesión con el servicio en cada consulta.
+pub struct WlanApiBackend;
+
+impl WifiBackend for WlanApiBackend {
+    fn name(&self) -> &'static str {
+        "WLAN API"
+    }
+
+    fn link(&self, interface: &str) -> Result<Option<WifiInfo>, String> {
+        wifi_info(interface)
+    }
+}
+
+type ConnectionCallback = Box<dyn Fn() + Send + Sync + 'static>;
+
+unsafe extern "system" fn on_notification(data: *mut L2_NOTIFICATION_DATA, context: *mut c_void) {
+    if data.is_null() || context.is_null() {
+        return;
+    }
+    let data = unsafe { &*data };
+    if data.NotificationSource != WLAN_NOTIFICATION_SOURCE_ACM {
+        return;
+    }
+
+    let code = data.NotificationCode as i32;
+    if code == wlan_notification_acm_connection_complete
+        || code == wlan_notification_acm_disconnected
+    {
+        let on_change = unsafe { &*(context as *const ConnectionCallback) };
+        on_change();
+    }
+}
+
+/// Llama a `on_change` cuando un adaptador termina de conectarse o se
+/// desconecta. El aviso llega en un hilo del servicio WLAN y dura lo que el
+/// proceso: la sesión y el callback se liberan al salir.
+pub fn watch_connections(on_change: impl Fn() + Send + Sync + 'static) -> Result<(), String> {
+    let wlan = NativeWlan::open()?;
+    let context: *mut ConnectionCallback = Box::into_raw(Box::new(Box::new(on_change)));
+
+    let status = unsafe {
+        WlanRegisterNotification(
+            wlan.handle,
+            WLAN_NOTIFICATION_SOURCE_ACM,
+            1,
+            Some(on_notification),
+            context as *const c_void,
+            ptr::null(),
+            ptr::null_mut(),
+        )
+    };
+    if status != ERROR_SUCCESS {
+        drop(unsafe { Box::from_raw(context) });
+        return Err(format!(
+            "WlanRegisterNotification falló con código {}",
+            status
+        ));
+    }
+
+    // Cerrar el handle cancelaría el registro
+    std::mem::forget(wlan);
+    Ok(())
+}
+
+fn utf16_until_nul(units: &[u16]) -> String {
+    let len = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+    String::from_utf16_lossy(&units[..len])
+}
+
+fn format_guid(guid: &GUID) -> String {
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        guid.data1,
+        guid.data2,
+        guid.data3,
+        guid.data4[0],
+        guid.data4[1],
+        guid.data4[2],
+        guid.data4[3],
+        guid.data4[4],
+        guid.data4[5],
+        guid.data4[6],
+        guid.data4[7],
+    )
+}
+
+fn parse_guid(text: &str) -> Result<GUID, String> {
+    let hex: String = text.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let value = u128::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 32)
+        .ok_or_else(|| format!("GUID de interfaz inválido: {}", text))?;
+    Ok(GUID::from_u128(value))
+}
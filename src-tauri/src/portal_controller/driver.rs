@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use crate::error::{AuthError, AuthResult};
+use crate::network_controller::client_builder::PinConfig;
+use crate::network_controller::profiles::NetworkProfile;
+use crate::network_controller::tofu;
+use crate::portal_controller::uabc::UabcDriver;
+use crate::settings;
+
+/// Resultado de la fase de detección de un portal cautivo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortalPresence {
+    /// El portal responde como si la sesión ya estuviera autenticada.
+    DirectAccess,
+    /// El portal está interceptando el tráfico y requiere iniciar sesión.
+    LoginRequired,
+}
+
+/// Flujo de autenticación de un portal cautivo concreto.
+///
+/// Cada implementación encapsula las URLs, el formato del formulario y los
+/// marcadores de éxito de un portal. `Auth` solo orquesta los pasos:
+/// `detect` → `extract_session` → `submit` → `verify`. Los pasos son
+/// asíncronos y corren en el runtime de Tauri.
+#[async_trait]
+pub trait PortalDriver: Send + Sync {
+    /// Identificador corto del driver (usado en logs).
+    fn name(&self) -> &'static str;
+
+    /// Indica si este driver atiende la red con el SSID dado.
+    fn matches_ssid(&self, ssid: &str) -> bool;
+
+    /// Comprueba si el portal está presente o si ya hay acceso directo.
+    async fn detect(&self) -> AuthResult<PortalPresence>;
+
+    /// Obtiene el identificador de sesión que el portal entrega al redirigir.
+    async fn extract_session(&self) -> AuthResult<String>;
+
+    /// Envía las credenciales al portal. Falla con `InvalidCredentials` si el
+    /// portal rechazó el inicio de sesión. Devuelve la duración de la sesión
+    /// cuando el portal la anuncia en su respuesta.
+    async fn submit(
+        &self,
+        username: &str,
+        password: &str,
+        session: &str,
+    ) -> AuthResult<Option<Duration>>;
+
+    /// Verifica que haya salida a internet después del inicio de sesión.
+    async fn verify(&self) -> AuthResult<()>;
+}
+
+lazy_static! {
+    static ref DRIVERS: RwLock<Vec<Arc<dyn PortalDriver>>> =
+        RwLock::new(vec![Arc::new(UabcDriver::new())]);
+    // Drivers construidos para perfiles de red con portal o pin propios. Se
+    // reutilizan mientras el perfil no cambie para no recrear los clientes TLS.
+    static ref PROFILE_DRIVERS: Mutex<Vec<(NetworkProfile, Arc<dyn PortalDriver>)>> =
+        Mutex::new(Vec::new());
+}
+
+/// Registra un driver adicional. Los drivers registrados después tienen
+/// prioridad sobre los anteriores cuando varios coinciden con el mismo SSID.
+pub fn register_driver(driver: Arc<dyn PortalDriver>) {
+    let mut drivers = match DRIVERS.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    drivers.insert(0, driver);
+}
+
+/// Selecciona el driver para el SSID actual. Si ninguno coincide (o no se
+/// conoce el SSID) se usa el driver por defecto de UABC.
+pub fn driver_for_ssid(ssid: Option<&str>) -> Arc<dyn PortalDriver> {
+    let drivers = match DRIVERS.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if let Some(ssid) = ssid
+        && let Some(driver) = drivers.iter().find(|d| d.matches_ssid(ssid))
+    {
+        return Arc::clone(driver);
+    }
+
+    drivers
+        .last()
+        .map(Arc::clone)
+        .unwrap_or_else(|| Arc::new(UabcDriver::new()))
+}
+
+/// Selecciona el driver para la red actual. Un perfil con `portal_url` o
+/// `cert_sha256` propios usa el flujo de UABC contra ese portal y con ese pin
+/// (o sin pines, si está aprendiendo el suyo); en otro caso se elige por SSID
+/// como en `driver_for_ssid`.
+pub fn driver_for_network(
+    profile: Option<&NetworkProfile>,
+    ssid: Option<&str>,
+) -> AuthResult<Arc<dyn PortalDriver>> {
+    let Some(profile) =
+        profile.filter(|p| p.portal_url.is_some() || p.cert_sha256.is_some())
+    else {
+        return Ok(driver_for_ssid(ssid));
+    };
+
+    let mut cache = match PROFILE_DRIVERS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if let Some((_, driver)) = cache.iter().find(|(cached, _)| cached == profile) {
+        return Ok(Arc::clone(driver));
+    }
+
+    let pins = match &profile.cert_sha256 {
+        Some(pin) => PinConfig::from_hex(pin).map_err(|e| {
+            AuthError::InvalidInput(format!("Pin del perfil de red {}: {}", profile.id, e))
+        })?,
+        None if tofu::is_learning(profile) => tofu::learning_pin_config(profile),
+        None => PinConfig::uabc(),
+    };
+    let config = settings::current();
+    let portal_url = profile.portal_url.as_deref().unwrap_or(&config.portal_url);
+    let driver: Arc<dyn PortalDriver> =
+        Arc::new(UabcDriver::with_endpoints(portal_url, config.verify_urls, &pins));
+
+    cache.retain(|(cached, _)| cached.id != profile.id);
+    cache.push((profile.clone(), Arc::clone(&driver)));
+    Ok(driver)
+}
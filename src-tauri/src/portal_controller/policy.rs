@@ -0,0 +1,165 @@
+//! Política estricta antes de enviar credenciales al portal.
+//!
+//! Las credenciales solo viajan por HTTPS al mismo host configurado y por una
+//! conexión cuyo certificado coincide con el pin (lo garantiza el cliente de
+//! `build_pinned_client`: el handshake falla antes de enviar el formulario).
+//! Cualquier desviación bloquea el inicio de sesión y genera una alerta.
+
+use lazy_static::lazy_static;
+use reqwest::Url;
+use serde::Serialize;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+use crate::error::{AuthError, AuthResult};
+use crate::history;
+
+type AlertListener = Box<dyn Fn(&SecurityAlert) + Send>;
+
+lazy_static! {
+    static ref ALERT_LISTENERS: Mutex<Vec<AlertListener>> = Mutex::new(Vec::new());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityAlertKind {
+    /// El certificado del portal no coincide con el pin.
+    PinMismatch,
+    /// El portal redirigió a un host distinto del configurado.
+    UnexpectedRedirectHost,
+    /// El portal redirigió de HTTPS a HTTP.
+    HttpDowngrade,
+}
+
+impl SecurityAlertKind {
+    pub fn as_key(self) -> &'static str {
+        match self {
+            SecurityAlertKind::PinMismatch => "pin_mismatch",
+            SecurityAlertKind::UnexpectedRedirectHost => "unexpected_redirect_host",
+            SecurityAlertKind::HttpDowngrade => "http_downgrade",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "pin_mismatch" => Some(SecurityAlertKind::PinMismatch),
+            "unexpected_redirect_host" => Some(SecurityAlertKind::UnexpectedRedirectHost),
+            "http_downgrade" => Some(SecurityAlertKind::HttpDowngrade),
+            _ => None,
+        }
+    }
+
+    /// Motivo legible del bloqueo, mostrado en la interfaz.
+    pub fn description(self) -> &'static str {
+        match self {
+            SecurityAlertKind::PinMismatch => {
+                "El certificado del portal no coincide con el esperado"
+            }
+            SecurityAlertKind::UnexpectedRedirectHost => {
+                "El portal redirigió a un servidor desconocido"
+            }
+            SecurityAlertKind::HttpDowngrade => "El portal intentó usar una conexión sin cifrar",
+        }
+    }
+
+    /// Tipo de alerta que corresponde a un error del flujo, si lo hay.
+    pub fn from_error(err: &AuthError) -> Option<Self> {
+        match err {
+            AuthError::TlsPinMismatch(_) => Some(SecurityAlertKind::PinMismatch),
+            AuthError::UnsafePortal { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+}
+
+/// Evento `security-alert`: por qué se bloqueó el envío de credenciales.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+pub struct SecurityAlert {
+    #[ts(type = "number")]
+    pub timestamp_ms: i64,
+    pub kind: SecurityAlertKind,
+    pub ssid: Option<String>,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+/// Registra una función que recibe cada alerta (ventana, daemon o CLI).
+pub fn on_security_alert(listener: impl Fn(&SecurityAlert) + Send + 'static) {
+    match ALERT_LISTENERS.lock() {
+        Ok(mut guard) => guard.push(Box::new(listener)),
+        Err(poisoned) => poisoned.into_inner().push(Box::new(listener)),
+    }
+}
+
+/// Si `err` es una violación de la política, la guarda en el historial y la
+/// notifica a los listeners.
+pub fn report(err: &AuthError, ssid: Option<&str>) {
+    let Some(kind) = SecurityAlertKind::from_error(err) else {
+        return;
+    };
+
+    let alert = SecurityAlert {
+        timestamp_ms: history::now_ms(),
+        kind,
+        ssid: ssid.map(str::to_string),
+        message: kind.description().to_string(),
+        details: err.details(),
+    };
+
+    eprintln!(
+        "[policy] Inicio de sesión bloqueado ({}): {}",
+        kind.as_key(),
+        alert.details.as_deref().unwrap_or("-")
+    );
+    history::record_security_alert(&alert);
+
+    let listeners = match ALERT_LISTENERS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    for listener in listeners.iter() {
+        listener(&alert);
+    }
+}
+
+/// Solo se envían credenciales por HTTPS.
+pub fn require_https(portal_url: &str) -> AuthResult<Url> {
+    let url = Url::parse(portal_url)
+        .map_err(|e| AuthError::InvalidInput(format!("URL del portal inválida: {}", e)))?;
+
+    if url.scheme() != "https" {
+        return Err(AuthError::UnsafePortal {
+            kind: SecurityAlertKind::HttpDowngrade,
+            observed: portal_url.to_string(),
+        });
+    }
+    Ok(url)
+}
+
+/// Verifica que una redirección (o la URL final de una respuesta) siga en el
+/// mismo host del portal y por HTTPS. `target` puede ser relativo.
+pub fn check_redirect(portal_url: &str, target: &str) -> AuthResult<()> {
+    let portal = require_https(portal_url)?;
+    let target_url = portal.join(target).map_err(|_| AuthError::UnsafePortal {
+        kind: SecurityAlertKind::UnexpectedRedirectHost,
+        observed: target.to_string(),
+    })?;
+
+    if target_url.scheme() != "https" {
+        return Err(AuthError::UnsafePortal {
+            kind: SecurityAlertKind::HttpDowngrade,
+            observed: target_url.to_string(),
+        });
+    }
+
+    if target_url.host_str() != portal.host_str()
+        || target_url.port_or_known_default() != portal.port_or_known_default()
+    {
+        return Err(AuthError::UnsafePortal {
+            kind: SecurityAlertKind::UnexpectedRedirectHost,
+            observed: target_url.to_string(),
+        });
+    }
+
+    Ok(())
+}
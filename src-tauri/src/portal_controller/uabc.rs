@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::Client;
+use tokio::task::JoinSet;
+
+use crate::error::{AuthError, AuthResult};
+use crate::network_controller::client_builder::{
+    build_client, get_simple_client, pinned_client, PinConfig,
+};
+use crate::network_controller::rtt;
+use crate::portal_controller::driver::{PortalDriver, PortalPresence};
+use crate::portal_controller::policy::{self, SecurityAlertKind};
+use crate::settings;
+
+const UABC_SSID_MARKER: &str = "UABC";
+const UABC_PORTAL_MARKER: &str = "Universidad Autónoma de Baja California";
+const UABC_LOGIN_SUCCESS_MARKER: &str = "<title>Login Successful</title>";
+const UABC_SESSION_PARAM: &str = "url=";
+const MIN_SESSION_TIMEOUT_SECS: u64 = 60;
+const MAX_SESSION_TIMEOUT_SECS: u64 = 7 * 24 * 60 * 60;
+/// Detección y captura del redirect: el portal responde sin procesar nada.
+const DETECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Envío del formulario: el portal valida las credenciales antes de responder.
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(5);
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(3);
+/// Redirecciones que se siguen tras enviar el formulario (página de éxito).
+const MAX_SUBMIT_REDIRECTS: usize = 3;
+
+lazy_static! {
+    // `sessionTimeout: 3600`, `session_timeout=3600` o
+    // `<input name="session-timeout" value="3600">`, siempre en segundos.
+    static ref SESSION_TIMEOUT_REGEX: Regex =
+        Regex::new(r#"(?i)session[_-]?timeout["']?\s*(?:[:=]|\svalue\s*=)\s*["']?(\d+)"#)
+            .expect("Regex de session timeout inválido");
+}
+
+/// Portal cautivo de la red WiFi de la UABC (pcw.uabc.mx).
+///
+/// Sin endpoints explícitos, la URL del portal y las de verificación se leen
+/// de la configuración vigente en cada intento.
+pub struct UabcDriver {
+    portal_url: Option<String>,
+    verify_urls: Option<Vec<String>>,
+    client_no_redirect: Client,
+    simple_client: Client,
+}
+
+impl UabcDriver {
+    pub fn new() -> Self {
+        UabcDriver {
+            portal_url: None,
+            verify_urls: None,
+            client_no_redirect: build_client(DETECT_TIMEOUT, true),
+            simple_client: get_simple_client().clone(),
+        }
+    }
+
+    /// Driver con el mismo flujo de la UABC pero contra otro servidor
+    /// (réplica del portal, portal simulado en pruebas, etc.).
+    pub fn with_endpoints(portal_url: &str, verify_urls: Vec<String>, pins: &PinConfig) -> Self {
+        UabcDriver {
+            portal_url: Some(portal_url.to_string()),
+            verify_urls: Some(verify_urls),
+            client_no_redirect: pinned_client(DETECT_TIMEOUT, true, pins),
+            simple_client: get_simple_client().clone(),
+        }
+    }
+
+    fn portal_url(&self) -> String {
+        self.portal_url
+            .clone()
+            .unwrap_or_else(|| settings::current().portal_url)
+    }
+
+    fn verify_urls(&self) -> Vec<String> {
+        self.verify_urls
+            .clone()
+            .unwrap_or_else(|| settings::current().verify_urls)
+    }
+
+    async fn check_redirect(&self) -> AuthResult<(bool, Option<String>)> {
+        let portal_url = self.portal_url();
+        let request = self.client_no_redirect.get(&portal_url);
+        let response = rtt::send(request, &portal_url, DETECT_TIMEOUT).await?;
+
+        let status = response.status();
+        let is_redirect = status.is_redirection();
+
+        let redirect_url = if is_redirect {
+            response
+                .headers()
+                .get("Location")
+                .or(response.headers().get("location"))
+                .map(|h| h.to_str().unwrap_or_default().to_string())
+        } else {
+            None
+        };
+
+        Ok((is_redirect, redirect_url))
+    }
+}
+
+impl Default for UabcDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PortalDriver for UabcDriver {
+    fn name(&self) -> &'static str {
+        "uabc"
+    }
+
+    fn matches_ssid(&self, ssid: &str) -> bool {
+        ssid.contains(UABC_SSID_MARKER)
+    }
+
+    async fn detect(&self) -> AuthResult<PortalPresence> {
+        let portal_url = self.portal_url();
+        policy::require_https(&portal_url)?;
+
+        // Un error TLS (incluido un pin que no coincide) ya no se interpreta
+        // como "portal presente": se propaga y bloquea el inicio de sesión.
+        // Las redirecciones no se siguen para validar el destino antes de
+        // conectarse a él.
+        let request = self.client_no_redirect.get(&portal_url);
+        let response = rtt::send(request, &portal_url, DETECT_TIMEOUT).await?;
+
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or_default();
+            policy::check_redirect(&portal_url, location)?;
+            return Ok(PortalPresence::LoginRequired);
+        }
+
+        if response.status().is_success() {
+            let body = response.text().await?;
+            if body.contains(UABC_PORTAL_MARKER) && !body.contains("login") {
+                return Ok(PortalPresence::DirectAccess);
+            }
+        }
+
+        Ok(PortalPresence::LoginRequired)
+    }
+
+    async fn extract_session(&self) -> AuthResult<String> {
+        let (redirected, redirect_url) = self.check_redirect().await?;
+
+        if !redirected {
+            return Err(AuthError::PortalNotPresent {
+                reason: "El portal no redirigió la petición".to_string(),
+            });
+        }
+
+        let url = redirect_url.ok_or_else(|| AuthError::PortalNotPresent {
+            reason: "Redirección sin encabezado Location".to_string(),
+        })?;
+        policy::check_redirect(&self.portal_url(), &url)?;
+
+        match url.find(UABC_SESSION_PARAM) {
+            Some(pos) => Ok(url[(pos + UABC_SESSION_PARAM.len())..].to_string()),
+            None => Err(AuthError::PortalNotPresent {
+                reason: format!("Redirección sin identificador de sesión: {}", url),
+            }),
+        }
+    }
+
+    async fn submit(
+        &self,
+        username: &str,
+        password: &str,
+        session: &str,
+    ) -> AuthResult<Option<Duration>> {
+        let mut form = HashMap::new();
+        form.insert("url", session);
+        form.insert("username", username);
+        form.insert("password", password);
+
+        let portal_url = self.portal_url();
+        policy::require_https(&portal_url)?;
+
+        // El formulario lleva la contraseña: se envía una sola vez y sin seguir
+        // redirecciones. Cada destino se valida antes de pedirlo, ya con GET.
+        let mut url = portal_url.clone();
+        let mut request = self.client_no_redirect.post(&portal_url).form(&form);
+        let mut redirects = 0;
+        let res = loop {
+            let res = rtt::send(request, &url, SUBMIT_TIMEOUT).await?;
+            if !res.status().is_redirection() {
+                break res;
+            }
+            if redirects == MAX_SUBMIT_REDIRECTS {
+                return Err(AuthError::PortalHttp {
+                    status: Some(res.status()),
+                    source: None,
+                });
+            }
+            redirects += 1;
+
+            let location = res
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or_default();
+            policy::check_redirect(&portal_url, location)?;
+            url = res
+                .url()
+                .join(location)
+                .map_err(|_| AuthError::UnsafePortal {
+                    kind: SecurityAlertKind::UnexpectedRedirectHost,
+                    observed: location.to_string(),
+                })?
+                .to_string();
+            request = self.client_no_redirect.get(&url);
+        };
+        let status = res.status();
+        let body = res.text().await?;
+
+        if !status.is_success() {
+            return Err(AuthError::PortalHttp {
+                status: Some(status),
+                source: None,
+            });
+        }
+
+        if body.contains(UABC_LOGIN_SUCCESS_MARKER) {
+            Ok(parse_session_timeout(&body))
+        } else {
+            Err(AuthError::InvalidCredentials)
+        }
+    }
+
+    async fn verify(&self) -> AuthResult<()> {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        // Todas las URLs se consultan a la vez; basta la primera que responda.
+        // Al salir, `JoinSet` cancela las que sigan en curso.
+        let mut probes = JoinSet::new();
+        for url in self.verify_urls() {
+            let client = self.simple_client.clone();
+            probes.spawn(async move { rtt::send(client.get(&url), &url, VERIFY_TIMEOUT).await });
+        }
+
+        let mut last_error = None;
+        while let Some(probe) = probes.join_next().await {
+            match probe {
+                Ok(Ok(response)) if response.status().is_success() => return Ok(()),
+                Ok(Ok(response)) => last_error = response.error_for_status().err().or(last_error),
+                Ok(Err(e)) => last_error = Some(e),
+                Err(e) => eprintln!("[uabc] Verificación interrumpida: {}", e),
+            }
+        }
+
+        Err(AuthError::VerificationFailed { source: last_error })
+    }
+}
+
+/// Duración de sesión anunciada en la página de éxito, si es razonable.
+fn parse_session_timeout(body: &str) -> Option<Duration> {
+    SESSION_TIMEOUT_REGEX
+        .captures(body)
+        .and_then(|caps| caps[1].parse::<u64>().ok())
+        .filter(|secs| (MIN_SESSION_TIMEOUT_SECS..=MAX_SESSION_TIMEOUT_SECS).contains(secs))
+        .map(Duration::from_secs)
+}
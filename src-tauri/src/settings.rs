@@ -0,0 +1,208 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::json_store::JsonStore;
+use crate::network_controller::prober::{self, Probe};
+
+const SETTINGS_FILE: &str = "settings.json";
+const APP_IDENTIFIER: &str = "app.cimasync";
+
+const MIN_INTERVAL_SECS: u64 = 5;
+const MAX_INTERVAL_SECS: u64 = 60 * 60;
+const MAX_BACKOFF_LIMIT_SECS: u64 = 6 * 60 * 60;
+const MAX_TIMEOUT_SECS: u64 = 60;
+const MAX_FAILURE_THRESHOLD: u64 = 10;
+
+lazy_static! {
+    static ref SETTINGS: JsonStore<Settings> =
+        JsonStore::new(SETTINGS_FILE, "settings", "configuración", Settings::default);
+}
+
+/// Parámetros de monitoreo y endpoints ajustables sin recompilar.
+/// Se guardan como JSON en el directorio de configuración de la app.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub monitoring_interval_secs: u64,
+    pub success_interval_secs: u64,
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub backoff_multiplier: f64,
+    pub max_consecutive_failures: u32,
+    pub portal_url: String,
+    pub connectivity_probe_url: String,
+    pub connectivity_timeout_secs: u64,
+    /// Sondas que se lanzan junto con `connectivity_probe_url` (ver `prober`).
+    pub connectivity_probes: Vec<Probe>,
+    /// Sondas fallidas seguidas en la misma red antes de dar por perdida la
+    /// conexión (ver `state_machine`).
+    pub connectivity_failure_threshold: u32,
+    /// Tope para los timeouts que se estiran en redes lentas (ver `rtt`).
+    pub max_adaptive_timeout_secs: u64,
+    pub verify_urls: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            monitoring_interval_secs: 60,
+            success_interval_secs: 20,
+            initial_backoff_secs: 5,
+            max_backoff_secs: 5 * 60,
+            backoff_multiplier: 2.0,
+            max_consecutive_failures: 10,
+            portal_url: "https://pcw.uabc.mx/".to_string(),
+            connectivity_probe_url: "http://clients3.google.com/generate_204".to_string(),
+            connectivity_timeout_secs: 3,
+            connectivity_probes: prober::default_probes(),
+            connectivity_failure_threshold: 3,
+            max_adaptive_timeout_secs: 20,
+            verify_urls: vec![
+                "https://www.google.com".to_string(),
+                "https://www.cloudflare.com".to_string(),
+            ],
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        check_range(
+            "monitoring_interval_secs",
+            self.monitoring_interval_secs,
+            MIN_INTERVAL_SECS,
+            MAX_INTERVAL_SECS,
+        )?;
+        check_range(
+            "success_interval_secs",
+            self.success_interval_secs,
+            MIN_INTERVAL_SECS,
+            MAX_INTERVAL_SECS,
+        )?;
+        check_range(
+            "initial_backoff_secs",
+            self.initial_backoff_secs,
+            1,
+            MAX_BACKOFF_LIMIT_SECS,
+        )?;
+        check_range(
+            "max_backoff_secs",
+            self.max_backoff_secs,
+            self.initial_backoff_secs,
+            MAX_BACKOFF_LIMIT_SECS,
+        )?;
+        check_range(
+            "connectivity_timeout_secs",
+            self.connectivity_timeout_secs,
+            1,
+            MAX_TIMEOUT_SECS,
+        )?;
+        check_range(
+            "max_adaptive_timeout_secs",
+            self.max_adaptive_timeout_secs,
+            1,
+            MAX_TIMEOUT_SECS,
+        )?;
+
+        if !(1.0..=10.0).contains(&self.backoff_multiplier) {
+            return Err("backoff_multiplier debe estar entre 1.0 y 10.0".to_string());
+        }
+
+        if self.max_consecutive_failures == 0 {
+            return Err("max_consecutive_failures debe ser mayor a 0".to_string());
+        }
+        check_range(
+            "connectivity_failure_threshold",
+            u64::from(self.connectivity_failure_threshold),
+            1,
+            MAX_FAILURE_THRESHOLD,
+        )?;
+
+        check_url("portal_url", &self.portal_url, true)?;
+        check_url("connectivity_probe_url", &self.connectivity_probe_url, false)?;
+        for probe in &self.connectivity_probes {
+            probe
+                .validate()
+                .map_err(|e| format!("connectivity_probes: {}", e))?;
+        }
+
+        if self.verify_urls.is_empty() {
+            return Err("verify_urls no puede estar vacío".to_string());
+        }
+        for url in &self.verify_urls {
+            check_url("verify_urls", url, false)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn monitoring_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring_interval_secs)
+    }
+
+    pub fn success_interval(&self) -> Duration {
+        Duration::from_secs(self.success_interval_secs)
+    }
+
+    pub fn connectivity_timeout(&self) -> Duration {
+        Duration::from_secs(self.connectivity_timeout_secs)
+    }
+
+    pub fn max_adaptive_timeout(&self) -> Duration {
+        Duration::from_secs(self.max_adaptive_timeout_secs)
+    }
+}
+
+fn check_range(field: &str, value: u64, min: u64, max: u64) -> Result<(), String> {
+    if value < min || value > max {
+        return Err(format!("{} debe estar entre {} y {}", field, min, max));
+    }
+    Ok(())
+}
+
+fn check_url(field: &str, value: &str, require_https: bool) -> Result<(), String> {
+    let url = reqwest::Url::parse(value).map_err(|e| format!("{} inválida: {}", field, e))?;
+
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" if !require_https => Ok(()),
+        scheme => Err(format!("{} usa un esquema no permitido: {}", field, scheme)),
+    }
+}
+
+/// Directorio de configuración equivalente a `app_config_dir()` de Tauri, para
+/// los binarios que corren sin `AppHandle` (daemon y CLI).
+pub fn default_config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library").join("Application Support"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Carga `settings.json` desde `config_dir` (ver `JsonStore::init`).
+pub fn init(config_dir: &Path) {
+    SETTINGS.init(config_dir, Settings::validate);
+}
+
+/// Copia de la configuración vigente.
+pub fn current() -> Settings {
+    SETTINGS.current()
+}
+
+/// Valida, guarda en disco y aplica una nueva configuración.
+pub fn update(settings: Settings) -> Result<Settings, String> {
+    settings.validate()?;
+    SETTINGS.store(settings)
+}
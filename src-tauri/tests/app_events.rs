@@ -0,0 +1,59 @@
+//! Forma de los eventos que recibe la ventana.
+
+use cima_sync_lib::events::{AppEvent, EVENT_SCHEMA_VERSION, UabcDetected};
+use cima_sync_lib::network_controller::status::{NetworkStatus, SyncNetworkState};
+use cima_sync_lib::portal_controller::policy::{SecurityAlert, SecurityAlertKind};
+
+#[test]
+fn every_event_carries_version_and_type() {
+    let events = [
+        AppEvent::NetworkStatus(Box::default()),
+        AppEvent::UabcDetected(UabcDetected {
+            ssid: Some("UABC".to_string()),
+            network_profile_id: "uabc".to_string(),
+        }),
+        AppEvent::SecurityAlert(SecurityAlert {
+            timestamp_ms: 1_760_000_000_000,
+            kind: SecurityAlertKind::HttpDowngrade,
+            ssid: None,
+            message: "El portal redirigió a HTTP".to_string(),
+            details: None,
+        }),
+    ];
+
+    for event in &events {
+        let json = serde_json::to_value(event.envelope()).unwrap();
+        assert_eq!(json["schema_version"], EVENT_SCHEMA_VERSION);
+        assert_eq!(json["type"], event.name());
+    }
+}
+
+#[test]
+fn network_status_fields_stay_at_the_top_level() {
+    let status = NetworkStatus {
+        connected: true,
+        ssid: Some("UABC".to_string()),
+        network_state: SyncNetworkState::HaveCautivePortal,
+        ..NetworkStatus::default()
+    };
+    let event = AppEvent::NetworkStatus(Box::new(status));
+    let json = serde_json::to_value(event.envelope()).unwrap();
+
+    assert_eq!(json["type"], "network-status");
+    assert_eq!(json["connected"], true);
+    assert_eq!(json["ssid"], "UABC");
+    assert_eq!(json["network_state"], "haveCautivePortal");
+    assert!(json["error"].is_null());
+}
+
+#[test]
+fn monitor_errors_use_the_same_shape() {
+    let status = NetworkStatus::from_error("sin netlink".to_string(), 1_760_000_000_000);
+    let json = serde_json::to_value(AppEvent::NetworkStatus(Box::new(status)).envelope()).unwrap();
+
+    assert_eq!(json["connected"], false);
+    assert_eq!(json["network_state"], "invalidConnection");
+    assert_eq!(json["status_text"], "WI-FI Cimarrón No Disponible");
+    assert_eq!(json["error"], "sin netlink");
+    assert!(json["wifi"].is_null());
+}
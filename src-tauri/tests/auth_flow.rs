@@ -0,0 +1,318 @@
+//! Pruebas de extremo a extremo del flujo de autenticación contra el portal simulado.
+
+mod common;
+
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime};
+
+use cima_sync_lib::auth::{Auth, LifetimeSource};
+use cima_sync_lib::error::AuthError;
+use cima_sync_lib::network_controller::client_builder::PinConfig;
+use cima_sync_lib::network_controller::pins::Pin;
+use cima_sync_lib::portal_controller::policy::{self, SecurityAlertKind};
+use cima_sync_lib::portal_controller::uabc::UabcDriver;
+
+use common::mock_portal::{MockPortal, MockPortalOptions};
+
+fn auth_for(portal: &MockPortal, pins: &PinConfig, username: &str, password: &str) -> Auth {
+    let driver = UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        pins,
+    );
+    Auth::with_driver(username, password, Arc::new(driver))
+}
+
+#[tokio::test]
+async fn login_succeeds_with_valid_credentials() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(matches!(result, Ok(true)), "resultado inesperado: {:?}", result);
+    assert_eq!(portal.login_attempts(), 1);
+    assert!(portal.is_authenticated());
+}
+
+#[tokio::test]
+async fn login_is_skipped_when_session_is_already_active() {
+    let portal = MockPortal::start(MockPortalOptions {
+        authenticated: true,
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(matches!(result, Ok(true)), "resultado inesperado: {:?}", result);
+    assert_eq!(portal.login_attempts(), 0);
+}
+
+#[tokio::test]
+async fn login_fails_with_wrong_password() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "incorrecta");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(result, Err(AuthError::InvalidCredentials)),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 1);
+    assert!(!portal.is_authenticated());
+}
+
+#[tokio::test]
+async fn login_fails_when_portal_does_not_redirect() {
+    let portal = MockPortal::start(MockPortalOptions {
+        redirect: false,
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(result, Err(AuthError::PortalNotPresent { .. })),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 0);
+}
+
+#[tokio::test]
+async fn login_fails_on_certificate_pin_mismatch() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal, &portal.wrong_pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(result, Err(AuthError::TlsPinMismatch(_))),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 0);
+}
+
+#[tokio::test]
+async fn login_succeeds_with_a_backup_spki_pin() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    // El pin principal ya no corresponde (certificado renovado); el de respaldo sí
+    let pins = portal
+        .wrong_pin_config()
+        .with_pin(Pin::parse(&portal.spki_pin()).expect("pin SPKI"));
+    let auth = auth_for(&portal, &pins, "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(matches!(result, Ok(true)), "resultado inesperado: {:?}", result);
+    assert_eq!(portal.login_attempts(), 1);
+}
+
+#[tokio::test]
+async fn pin_mismatch_reports_the_pin_set_and_observed_fingerprint() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal, &portal.wrong_pin_config(), "alumno", "secreto");
+
+    let err = auth.login().await.expect_err("el pin no coincide");
+    let details = err.details().unwrap_or_default();
+
+    assert!(matches!(err, AuthError::TlsPinMismatch(_)));
+    assert!(details.contains("'personalizado'"), "detalles: {}", details);
+    assert!(details.contains(&"00".repeat(32)), "detalles: {}", details);
+    assert!(details.contains(portal.cert_sha256()), "detalles: {}", details);
+}
+
+#[tokio::test]
+async fn login_is_blocked_when_portal_redirects_to_another_host() {
+    let (alert_tx, alert_rx) = mpsc::channel();
+    policy::on_security_alert(move |alert| {
+        let _ = alert_tx.send(alert.kind);
+    });
+
+    let portal = MockPortal::start(MockPortalOptions {
+        redirect_location: Some("https://portal-falso.example/login.html".to_string()),
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(
+            result,
+            Err(AuthError::UnsafePortal {
+                kind: SecurityAlertKind::UnexpectedRedirectHost,
+                ..
+            })
+        ),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 0);
+    // Otras pruebas en paralelo pueden emitir alertas de otro tipo
+    let alerted = alert_rx
+        .try_iter()
+        .any(|kind| kind == SecurityAlertKind::UnexpectedRedirectHost);
+    assert!(alerted, "no se emitió la alerta de seguridad");
+}
+
+#[tokio::test]
+async fn login_is_blocked_when_portal_downgrades_to_http() {
+    let portal = MockPortal::start(MockPortalOptions {
+        redirect_location: Some("http://127.0.0.1/login.html".to_string()),
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(
+            result,
+            Err(AuthError::UnsafePortal {
+                kind: SecurityAlertKind::HttpDowngrade,
+                ..
+            })
+        ),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 0);
+}
+
+#[tokio::test]
+async fn login_follows_the_success_redirect_without_resending_the_form() {
+    let portal = MockPortal::start(MockPortalOptions {
+        submit_redirect: Some("/bienvenida".to_string()),
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    auth.login().await.expect("login exitoso tras la redirección");
+
+    // Un 307 seguido por reqwest habría repetido el POST
+    assert_eq!(portal.login_attempts(), 1);
+}
+
+#[tokio::test]
+async fn credentials_are_not_replayed_to_an_http_redirect_after_submit() {
+    let portal = MockPortal::start(MockPortalOptions {
+        submit_redirect: Some("http://127.0.0.1/robar".to_string()),
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(
+            result,
+            Err(AuthError::UnsafePortal {
+                kind: SecurityAlertKind::HttpDowngrade,
+                ..
+            })
+        ),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 1);
+}
+
+#[tokio::test]
+async fn login_fails_when_connectivity_is_missing_after_login() {
+    let portal = MockPortal::start(MockPortalOptions {
+        connectivity_after_login: false,
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    let result = auth.login().await;
+
+    assert!(
+        matches!(result, Err(AuthError::VerificationFailed { .. })),
+        "resultado inesperado: {:?}",
+        result
+    );
+    assert_eq!(portal.login_attempts(), 1);
+}
+
+#[tokio::test]
+async fn stop_monitoring_wakes_the_worker_during_its_wait() {
+    let portal = MockPortal::start(MockPortalOptions {
+        authenticated: true,
+        ..MockPortalOptions::default()
+    });
+    let auth = Arc::new(auth_for(&portal, &portal.pin_config(), "alumno", "secreto"));
+
+    // Tras la primera verificación el monitor espera `success_interval` (20 s).
+    assert_monitor_stops_promptly(auth).await;
+}
+
+#[tokio::test]
+async fn stop_monitoring_abandons_a_login_in_progress() {
+    let portal = MockPortal::start(MockPortalOptions {
+        response_delay: Some(Duration::from_millis(2500)),
+        ..MockPortalOptions::default()
+    });
+    let auth = Arc::new(auth_for(&portal, &portal.pin_config(), "alumno", "secreto"));
+
+    // Cada petición tarda 2.5 s: el login completo tomaría varios segundos más.
+    assert_monitor_stops_promptly(auth).await;
+    assert_eq!(portal.login_attempts(), 0);
+}
+
+async fn assert_monitor_stops_promptly(auth: Arc<Auth>) {
+    let worker = {
+        let auth = Arc::clone(&auth);
+        tokio::spawn(async move { auth.start_monitoring().await })
+    };
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let stop_requested = Instant::now();
+    auth.stop_monitoring();
+    let result = worker.await.expect("la tarea de monitoreo entró en pánico");
+
+    assert!(result.is_ok(), "resultado inesperado: {:?}", result);
+    assert!(
+        stop_requested.elapsed() < Duration::from_secs(1),
+        "el monitor tardó {:?} en detenerse",
+        stop_requested.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn monitoring_cannot_be_started_twice() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+
+    auth.stop_monitoring();
+    assert!(auth.start_monitoring().await.is_ok());
+    assert!(matches!(auth.start_monitoring().await, Err(AuthError::Internal(_))));
+}
+
+#[tokio::test]
+async fn session_expiry_uses_the_timeout_announced_by_the_portal() {
+    let portal = MockPortal::start(MockPortalOptions {
+        session_timeout_secs: Some(3600),
+        ..MockPortalOptions::default()
+    });
+    let auth = auth_for(&portal, &portal.pin_config(), "alumno", "secreto");
+    assert!(auth.session_expiry().is_none());
+
+    let before = SystemTime::now();
+    assert!(auth.login().await.is_ok());
+
+    let (expires_at, source) = auth.session_expiry().expect("vencimiento previsto");
+    assert_eq!(source, LifetimeSource::Portal);
+    let lifetime = expires_at.duration_since(before).expect("vencimiento en el futuro");
+    assert!(
+        lifetime >= Duration::from_secs(3600) && lifetime < Duration::from_secs(3610),
+        "duración inesperada: {:?}",
+        lifetime
+    );
+}
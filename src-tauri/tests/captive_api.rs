@@ -0,0 +1,119 @@
+//! Detección del portal por los mecanismos estándar (RFC 8910 y RFC 8908).
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cima_sync_lib::auth::Auth;
+use cima_sync_lib::network_controller::captive_api::{self, CaptiveApiStatus, UNRESTRICTED_URN};
+use cima_sync_lib::network_controller::prober::{self, Connectivity, ProbeOutcome, ProbeResult};
+use cima_sync_lib::portal_controller::uabc::UabcDriver;
+
+use common::mock_portal::{MockPortal, MockPortalOptions};
+
+#[test]
+fn parses_rfc_8908_status() {
+    let status = captive_api::parse_status(
+        r#"{
+            "captive": false,
+            "user-portal-url": "https://pcw.uabc.mx/portal",
+            "venue-info-url": "https://uabc.mx/",
+            "can-extend-session": true,
+            "seconds-remaining": 326,
+            "bytes-remaining": 65536
+        }"#,
+    )
+    .unwrap();
+
+    assert!(!status.captive);
+    assert_eq!(status.user_portal_url.as_deref(), Some("https://pcw.uabc.mx/portal"));
+    assert_eq!(status.session_remaining(), Some(Duration::from_secs(326)));
+}
+
+#[test]
+fn captive_status_has_no_session_time() {
+    let status = captive_api::parse_status(r#"{ "captive": true, "seconds-remaining": 0 }"#).unwrap();
+
+    assert!(status.captive);
+    assert_eq!(status.session_remaining(), None);
+    assert!(captive_api::parse_status(r#"{ "user-portal-url": "https://x/" }"#).is_err());
+}
+
+#[test]
+fn reads_dhcp_option_114_from_networkmanager() {
+    let options = [
+        ("broadcast_address", "10.0.255.255"),
+        ("captive_portal", "https://capport.uabc.mx/api"),
+        ("domain_name_servers", "10.0.0.1"),
+    ];
+
+    assert_eq!(
+        captive_api::find_dhcp_captive_uri(options).as_deref(),
+        Some("https://capport.uabc.mx/api")
+    );
+    assert_eq!(captive_api::find_dhcp_captive_uri([("routers", "10.0.0.1")]), None);
+    assert_eq!(captive_api::find_dhcp_captive_uri([("captive_portal", " ")]), None);
+}
+
+#[test]
+fn maps_networkmanager_connectivity() {
+    assert_eq!(captive_api::from_nm_connectivity(2), Some(Connectivity::CaptivePortal));
+    assert_eq!(captive_api::from_nm_connectivity(4), Some(Connectivity::Online));
+    assert_eq!(captive_api::from_nm_connectivity(3), None);
+}
+
+fn online_verdict() -> prober::Verdict {
+    let probes = prober::default_probes();
+    prober::tally(
+        probes
+            .into_iter()
+            .map(|probe| ProbeResult {
+                probe,
+                outcome: ProbeOutcome::Reachable,
+                elapsed_ms: 5,
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn captive_api_overrides_probes() {
+    let captive = CaptiveApiStatus {
+        captive: true,
+        ..CaptiveApiStatus::default()
+    };
+
+    let verdict = captive_api::apply(online_verdict(), Some(&captive), None);
+    assert_eq!(verdict.connectivity, Connectivity::CaptivePortal);
+
+    // El sistema operativo no contradice sondas que sí llegaron a internet.
+    let verdict = captive_api::apply(online_verdict(), None, Some(Connectivity::CaptivePortal));
+    assert_eq!(verdict.connectivity, Connectivity::Online);
+}
+
+#[tokio::test]
+async fn rejects_api_without_https() {
+    assert!(captive_api::fetch("http://capport.test/api").await.is_err());
+
+    let unrestricted = captive_api::fetch(UNRESTRICTED_URN).await.unwrap();
+    assert!(!unrestricted.captive);
+}
+
+#[tokio::test]
+async fn login_is_skipped_when_api_reports_open_network() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let driver = UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        &portal.pin_config(),
+    );
+    let auth = Auth::with_driver("alumno", "secreto", Arc::new(driver));
+
+    captive_api::remember_uri(Some(UNRESTRICTED_URN.to_string()));
+    let result = auth.login().await;
+    captive_api::remember_uri(None);
+
+    assert!(result.is_ok());
+    assert_eq!(portal.login_attempts(), 0);
+}
@@ -0,0 +1,400 @@
+//! Portal cautivo simulado para pruebas de extremo a extremo de `auth.rs`.
+//!
+//! Emula el comportamiento de pcw.uabc.mx: redirección 302 con
+//! `Location: ...?url=<id>`, formulario de inicio de sesión, páginas de éxito y
+//! de error, y un endpoint de conectividad que solo responde después de iniciar
+//! sesión. El portal usa HTTPS con una CA de pruebas autofirmada.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use cima_sync_lib::network_controller::client_builder::PinConfig;
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use sha2::{Digest, Sha256};
+
+pub const SESSION_ID: &str = "c2Vzc2lvbi1kZS1wcnVlYmE";
+const LOGIN_PAGE: &str = "<html><head><title>Universidad Autónoma de Baja California</title></head>\
+<body><form method=\"post\" action=\"/\">login</form></body></html>";
+const AUTHENTICATED_PAGE: &str =
+    "<html><head><title>Universidad Autónoma de Baja California</title></head><body>Bienvenido</body></html>";
+const SUCCESS_PAGE: &str = "<html><head><title>Login Successful</title></head><body></body></html>";
+const FAILURE_PAGE: &str = "<html><head><title>Login Failed</title></head><body></body></html>";
+
+/// Comportamiento configurable del portal simulado.
+#[derive(Clone, Debug)]
+pub struct MockPortalOptions {
+    pub username: String,
+    pub password: String,
+    /// Si es `false`, el portal sirve el formulario directamente sin redirigir.
+    pub redirect: bool,
+    /// Si es `true`, la sesión ya está autenticada al arrancar.
+    pub authenticated: bool,
+    /// Si es `false`, el endpoint de conectividad falla aun después de iniciar sesión.
+    pub connectivity_after_login: bool,
+    /// Duración de sesión anunciada en la página de éxito, en segundos.
+    pub session_timeout_secs: Option<u64>,
+    /// Destino de la redirección en lugar de `<portal>/login.html` (portal falso).
+    pub redirect_location: Option<String>,
+    /// Si se indica, un inicio de sesión válido responde 307 hacia este destino
+    /// en lugar de la página de éxito.
+    pub submit_redirect: Option<String>,
+    /// Espera antes de responder cada petición al portal (portal lento).
+    pub response_delay: Option<Duration>,
+}
+
+impl Default for MockPortalOptions {
+    fn default() -> Self {
+        MockPortalOptions {
+            username: "alumno".to_string(),
+            password: "secreto".to_string(),
+            redirect: true,
+            authenticated: false,
+            connectivity_after_login: true,
+            session_timeout_secs: None,
+            redirect_location: None,
+            submit_redirect: None,
+            response_delay: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PortalState {
+    authenticated: bool,
+    login_attempts: usize,
+}
+
+pub struct MockPortal {
+    /// URL HTTPS del portal (equivalente a https://pcw.uabc.mx/).
+    pub portal_url: String,
+    /// URL HTTP que simula la salida a internet (equivalente a google.com).
+    pub connectivity_url: String,
+    ca_cert: CertificateDer<'static>,
+    leaf_cert_sha256: String,
+    leaf_spki_sha256: String,
+    state: Arc<Mutex<PortalState>>,
+}
+
+impl MockPortal {
+    pub fn start(options: MockPortalOptions) -> Self {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let (ca_cert, leaf_cert, leaf_key, leaf_spki) = generate_certificates();
+        let leaf_cert_sha256 = hex::encode(Sha256::digest(leaf_cert.as_ref()));
+        let leaf_spki_sha256 = hex::encode(Sha256::digest(&leaf_spki));
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![leaf_cert], leaf_key)
+            .expect("configuración TLS del portal simulado");
+        let server_config = Arc::new(server_config);
+
+        let state = Arc::new(Mutex::new(PortalState {
+            authenticated: options.authenticated,
+            login_attempts: 0,
+        }));
+
+        let portal_listener = TcpListener::bind("127.0.0.1:0").expect("bind del portal");
+        let portal_addr = portal_listener.local_addr().expect("dirección del portal");
+        let portal_url = format!("https://127.0.0.1:{}/", portal_addr.port());
+
+        let connectivity_listener =
+            TcpListener::bind("127.0.0.1:0").expect("bind de conectividad");
+        let connectivity_addr = connectivity_listener
+            .local_addr()
+            .expect("dirección de conectividad");
+        let connectivity_url = format!("http://127.0.0.1:{}/connectivity", connectivity_addr.port());
+
+        {
+            let state = Arc::clone(&state);
+            let options = options.clone();
+            let portal_url = portal_url.clone();
+            thread::spawn(move || {
+                for stream in portal_listener.incoming().flatten() {
+                    let state = Arc::clone(&state);
+                    let options = options.clone();
+                    let portal_url = portal_url.clone();
+                    let config = Arc::clone(&server_config);
+                    thread::spawn(move || {
+                        let Ok(conn) = ServerConnection::new(config) else {
+                            return;
+                        };
+                        let mut tls = StreamOwned::new(conn, stream);
+                        if let Some(request) = read_request(&mut tls) {
+                            if let Some(delay) = options.response_delay {
+                                thread::sleep(delay);
+                            }
+                            let response = portal_response(&request, &options, &portal_url, &state);
+                            let _ = tls.write_all(response.as_bytes());
+                            let _ = tls.flush();
+                            tls.conn.send_close_notify();
+                            let _ = tls.flush();
+                        }
+                    });
+                }
+            });
+        }
+
+        {
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                for mut stream in connectivity_listener.incoming().flatten() {
+                    if read_request(&mut stream).is_some() {
+                        let online = state.lock().map(|s| s.authenticated).unwrap_or(false)
+                            && options.connectivity_after_login;
+                        let response = if online {
+                            http_response(200, "OK", &[], "ok")
+                        } else {
+                            http_response(511, "Network Authentication Required", &[], "")
+                        };
+                        let _ = stream.write_all(response.as_bytes());
+                    }
+                }
+            });
+        }
+
+        MockPortal {
+            portal_url,
+            connectivity_url,
+            ca_cert,
+            leaf_cert_sha256,
+            leaf_spki_sha256,
+            state,
+        }
+    }
+
+    /// Pin correcto del portal simulado (SHA-256 del certificado hoja) más su CA.
+    pub fn pin_config(&self) -> PinConfig {
+        PinConfig::from_hex(&self.leaf_cert_sha256)
+            .expect("pin del portal simulado")
+            .with_root(self.ca_cert.clone())
+    }
+
+    /// Pin que no corresponde al certificado presentado, con la misma CA de confianza.
+    pub fn wrong_pin_config(&self) -> PinConfig {
+        PinConfig::from_hex(&"00".repeat(32))
+            .expect("pin inválido de prueba")
+            .with_root(self.ca_cert.clone())
+    }
+
+    /// Agrega la CA de pruebas a `pins` para que el certificado pase WebPki.
+    pub fn trusting_ca(&self, pins: PinConfig) -> PinConfig {
+        pins.with_root(self.ca_cert.clone())
+    }
+
+    /// CA de pruebas que firmó el certificado del portal.
+    pub fn ca_cert(&self) -> CertificateDer<'static> {
+        self.ca_cert.clone()
+    }
+
+    /// Pin `spki-sha256:` de la llave pública del certificado hoja.
+    pub fn spki_pin(&self) -> String {
+        format!("spki-sha256:{}", self.leaf_spki_sha256)
+    }
+
+    /// SHA-256 (hex) del certificado hoja, tal como lo reporta un `PinMismatch`.
+    pub fn cert_sha256(&self) -> &str {
+        &self.leaf_cert_sha256
+    }
+
+    pub fn login_attempts(&self) -> usize {
+        self.state.lock().map(|s| s.login_attempts).unwrap_or(0)
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.state.lock().map(|s| s.authenticated).unwrap_or(false)
+    }
+
+    /// Cierra la sesión como lo haría el portal al vencer su tiempo.
+    pub fn expire_session(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.authenticated = false;
+        }
+    }
+}
+
+fn generate_certificates() -> (
+    CertificateDer<'static>,
+    CertificateDer<'static>,
+    PrivateKeyDer<'static>,
+    Vec<u8>,
+) {
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).expect("parámetros de la CA");
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca_key = KeyPair::generate().expect("llave de la CA");
+    let ca_cert = ca_params.self_signed(&ca_key).expect("CA autofirmada");
+
+    let leaf_params = CertificateParams::new(vec!["127.0.0.1".to_string(), "localhost".to_string()])
+        .expect("parámetros del certificado hoja");
+    let leaf_key = KeyPair::generate().expect("llave del certificado hoja");
+    let leaf_cert = leaf_params
+        .signed_by(&leaf_key, &ca_cert, &ca_key)
+        .expect("certificado hoja firmado por la CA");
+
+    let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(leaf_key.serialize_der()));
+
+    (
+        ca_cert.der().clone(),
+        leaf_cert.der().clone(),
+        key_der,
+        leaf_key.public_key_der(),
+    )
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+fn read_request<S: Read>(stream: &mut S) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn portal_response(
+    request: &Request,
+    options: &MockPortalOptions,
+    portal_url: &str,
+    state: &Mutex<PortalState>,
+) -> String {
+    let mut state = state.lock().expect("estado del portal simulado");
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") if state.authenticated => http_response(200, "OK", &[], AUTHENTICATED_PAGE),
+        ("GET", "/") if options.redirect => {
+            let location = match &options.redirect_location {
+                Some(target) => format!("{}?url={}", target, SESSION_ID),
+                None => format!("{}login.html?url={}", portal_url, SESSION_ID),
+            };
+            http_response(302, "Found", &[("Location", location.as_str())], "")
+        }
+        ("GET", "/") => http_response(200, "OK", &[], LOGIN_PAGE),
+        ("GET", path) if path.starts_with("/login.html") => {
+            http_response(200, "OK", &[], LOGIN_PAGE)
+        }
+        ("GET", "/bienvenida") if state.authenticated => {
+            http_response(200, "OK", &[], SUCCESS_PAGE)
+        }
+        ("POST", "/") => {
+            state.login_attempts += 1;
+            let form = parse_form(&request.body);
+            let valid = form.get("url").map(String::as_str) == Some(SESSION_ID)
+                && form.get("username") == Some(&options.username)
+                && form.get("password") == Some(&options.password);
+
+            if valid {
+                state.authenticated = true;
+                if let Some(location) = &options.submit_redirect {
+                    return http_response(
+                        307,
+                        "Temporary Redirect",
+                        &[("Location", location.as_str())],
+                        "",
+                    );
+                }
+                match options.session_timeout_secs {
+                    Some(secs) => {
+                        let page = SUCCESS_PAGE.replace(
+                            "<body>",
+                            &format!(
+                                "<body><input type=\"hidden\" name=\"session_timeout\" value=\"{}\">",
+                                secs
+                            ),
+                        );
+                        http_response(200, "OK", &[], &page)
+                    }
+                    None => http_response(200, "OK", &[], SUCCESS_PAGE),
+                }
+            } else {
+                http_response(200, "OK", &[], FAILURE_PAGE)
+            }
+        }
+        _ => http_response(404, "Not Found", &[], ""),
+    }
+}
+
+fn http_response(status: u16, reason: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    response
+}
+
+fn parse_form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (url_decode(key), url_decode(value)))
+        .collect()
+}
+
+fn url_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,100 @@
+//! Historial de intentos de login guardado en SQLite.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cima_sync_lib::auth::Auth;
+use cima_sync_lib::history::{self, AttemptOutcome, AuthAttempt};
+use cima_sync_lib::portal_controller::uabc::UabcDriver;
+
+use common::mock_portal::{MockPortal, MockPortalOptions};
+
+// La conexión es global, así que todo el flujo vive en una sola prueba.
+#[tokio::test]
+async fn login_attempts_are_recorded_with_timings_and_stats() {
+    let dir = std::env::temp_dir().join(format!("cima-sync-history-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    history::init(&dir);
+
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let driver = Arc::new(UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        &portal.pin_config(),
+    ));
+
+    let rejected = Auth::with_driver("alumno", "incorrecta", driver.clone());
+    assert!(rejected.login().await.is_err());
+
+    // Falla en la detección, antes de enviar el formulario
+    let mismatched = Arc::new(UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        &portal.wrong_pin_config(),
+    ));
+    let spoofed = Auth::with_driver("alumno", "secreto", mismatched);
+    assert!(spoofed.login().await.is_err());
+
+    let accepted = Auth::with_driver("alumno", "secreto", driver.clone());
+    assert!(accepted.login().await.is_ok());
+    // La verificación con la sesión ya activa no se guarda
+    assert!(accepted.login().await.is_ok());
+
+    let first_page = history::page(0, 2, None).expect("consulta de historial");
+    assert_eq!(first_page.total, 3);
+    assert_eq!(first_page.entries.len(), 2);
+
+    let authenticated = &first_page.entries[0];
+    assert_eq!(authenticated.outcome, "authenticated");
+    assert!(authenticated.detect_ms.is_some());
+    assert!(authenticated.submit_ms.is_some());
+    assert!(authenticated.verify_ms.is_some());
+
+    let pin_mismatch = &first_page.entries[1];
+    assert_eq!(pin_mismatch.outcome, "failed");
+    assert_eq!(pin_mismatch.error_code.as_deref(), Some("tls_pin_mismatch"));
+    assert_eq!(pin_mismatch.submit_ms, None);
+
+    let second_page = history::page(1, 2, None).expect("consulta de historial");
+    assert_eq!(second_page.entries.len(), 1);
+    let failed = &second_page.entries[0];
+    assert_eq!(failed.outcome, "failed");
+    assert_eq!(failed.error_code.as_deref(), Some("invalid_credentials"));
+    assert_eq!(failed.verify_ms, None);
+
+    // Los fallos antes de enviar el formulario también cuentan como intentos
+    let stats = &first_page.stats;
+    assert_eq!(stats.attempts, 3);
+    assert_eq!(stats.successes, 1);
+    assert!((stats.success_rate.unwrap() - 1.0 / 3.0).abs() < f64::EPSILON);
+    assert_eq!(stats.mean_time_to_auth_ms, Some(authenticated.total_ms as f64));
+
+    let future = history::page(0, 10, Some(i64::MAX)).expect("consulta de historial");
+    assert!(future.entries.is_empty());
+    assert_eq!(future.stats.success_rate, None);
+
+    // La tabla no crece sin límite
+    let failure = AuthAttempt {
+        timestamp_ms: history::now_ms(),
+        ssid: None,
+        outcome: AttemptOutcome::Failed,
+        error_code: Some("timeout".to_string()),
+        error_message: None,
+        detect: None,
+        submit: None,
+        verify: None,
+        total: Duration::from_millis(5),
+        backoff: Some(Duration::from_secs(5)),
+    };
+    for _ in 0..history::MAX_HISTORY_ROWS {
+        history::record(&failure);
+    }
+    let pruned = history::page(0, 10, None).expect("consulta de historial");
+    assert_eq!(pruned.total, u64::from(history::MAX_HISTORY_ROWS));
+    assert_eq!(pruned.stats.attempts, u64::from(history::MAX_HISTORY_ROWS));
+    assert_eq!(pruned.stats.successes, 0);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,113 @@
+//! Coincidencia y persistencia de los perfiles de red.
+
+use cima_sync_lib::network_controller::profiles::{self, NetworkObservation, NetworkProfile};
+
+fn observe(ssid: &str) -> NetworkObservation<'_> {
+    NetworkObservation {
+        ssid: Some(ssid),
+        ..NetworkObservation::default()
+    }
+}
+
+#[test]
+fn default_profile_matches_only_the_campus_ssids() {
+    let uabc = NetworkProfile::uabc();
+
+    assert!(uabc.matches(&observe("UABC_5G")));
+    assert!(uabc.matches(&observe("UABC_2.4G")));
+    assert!(!uabc.matches(&observe("UABC-invitados-falso")));
+    assert!(!uabc.matches(&observe("Cafe UABC")));
+    assert!(!uabc.matches(&NetworkObservation::default()));
+}
+
+#[test]
+fn wildcard_patterns_match_ssid_families() {
+    let profile = NetworkProfile {
+        ssid_patterns: vec!["Lab-?-*".to_string()],
+        ..NetworkProfile::uabc()
+    };
+
+    assert!(profile.matches(&observe("Lab-A-Piso2")));
+    assert!(profile.matches(&observe("Lab-B-")));
+    assert!(!profile.matches(&observe("Lab-AB-Piso2")));
+}
+
+#[test]
+fn pinned_bssid_rejects_other_access_points() {
+    let profile = NetworkProfile {
+        bssids: vec!["AA:BB:CC:00:11:22".to_string()],
+        ..NetworkProfile::uabc()
+    };
+
+    let trusted = NetworkObservation {
+        bssid: Some("aa-bb-cc-00-11-22"),
+        ..observe("UABC_5G")
+    };
+    let rogue = NetworkObservation {
+        bssid: Some("de:ad:be:ef:00:01"),
+        ..observe("UABC_5G")
+    };
+
+    assert!(profile.matches(&trusted));
+    assert!(!profile.matches(&rogue));
+    // Sin BSSID observable no se puede confirmar el punto de acceso
+    assert!(!profile.matches(&observe("UABC_5G")));
+}
+
+#[test]
+fn validation_rejects_unsafe_profiles() {
+    let catch_all = NetworkProfile {
+        ssid_patterns: vec!["*".to_string()],
+        ..NetworkProfile::uabc()
+    };
+    let plain_http = NetworkProfile {
+        portal_url: Some("http://pcw.uabc.mx/".to_string()),
+        ..NetworkProfile::uabc()
+    };
+    let short_pin = NetworkProfile {
+        cert_sha256: Some("abcd".to_string()),
+        ..NetworkProfile::uabc()
+    };
+    let unpinned_portal = NetworkProfile {
+        portal_url: Some("https://portal.lab.example/".to_string()),
+        ..NetworkProfile::uabc()
+    };
+
+    assert_eq!(NetworkProfile::uabc().validate(), Ok(()));
+    assert!(catch_all.validate().is_err());
+    assert!(plain_http.validate().is_err());
+    assert!(short_pin.validate().is_err());
+    assert!(unpinned_portal.validate().is_err());
+}
+
+#[test]
+fn save_persists_and_init_reloads() {
+    let dir = std::env::temp_dir().join(format!("cima-sync-profiles-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    profiles::init(&dir);
+    assert!(dir.join("network-profiles.json").exists());
+    assert_eq!(profiles::list(), vec![NetworkProfile::uabc()]);
+
+    let lab = NetworkProfile {
+        id: "lab".to_string(),
+        name: "Laboratorio".to_string(),
+        ssid_patterns: vec!["LAB-IOT".to_string()],
+        portal_url: Some("https://portal.lab.example/".to_string()),
+        cert_sha256: Some(format!("cert-sha256:{}", "ab".repeat(32))),
+        ..NetworkProfile::default()
+    };
+    profiles::save(lab.clone()).expect("perfil válido");
+
+    let matched = profiles::find_match(&observe("LAB-IOT")).expect("perfil del laboratorio");
+    assert_eq!(matched.id, "lab");
+
+    profiles::init(&dir);
+    assert_eq!(profiles::list(), vec![NetworkProfile::uabc(), lab]);
+
+    profiles::delete("lab").expect("perfil existente");
+    assert!(profiles::find_match(&observe("LAB-IOT")).is_none());
+    assert!(profiles::delete("lab").is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
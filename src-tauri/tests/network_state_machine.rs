@@ -0,0 +1,261 @@
+//! Transiciones e histéresis de la máquina de estados de la red, con reloj y
+//! sonda falsos.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use cima_sync_lib::network_controller::state_machine::{
+    Clock, ConnectivityProber, Hysteresis, Link, NetworkStateMachine, Transition, TransitionCause,
+};
+use cima_sync_lib::network_controller::status::SyncNetworkState;
+
+#[derive(Clone)]
+struct FakeClock(Arc<Mutex<Instant>>);
+
+impl FakeClock {
+    fn new() -> Self {
+        FakeClock(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Devuelve los resultados en orden; sin más guion responde "en línea".
+struct FakeProber(Mutex<VecDeque<bool>>);
+
+impl FakeProber {
+    fn new(script: &[bool]) -> Self {
+        FakeProber(Mutex::new(script.iter().copied().collect()))
+    }
+}
+
+#[async_trait]
+impl ConnectivityProber for FakeProber {
+    type Report = bool;
+
+    async fn probe(&self) -> bool {
+        self.0.lock().unwrap().pop_front().unwrap_or(true)
+    }
+
+    fn is_online(report: &bool) -> bool {
+        *report
+    }
+}
+
+fn uabc() -> Link {
+    Link {
+        ssid: Some("UABC".to_string()),
+        network_profile_id: Some("uabc".to_string()),
+    }
+}
+
+fn machine(clock: &FakeClock) -> NetworkStateMachine<FakeClock> {
+    NetworkStateMachine::new(clock.clone(), Hysteresis::default())
+}
+
+#[tokio::test]
+async fn portal_needs_consecutive_failures_on_the_same_network() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    let prober = FakeProber::new(&[true, false, false, false]);
+
+    let (step, _) = machine.evaluate(uabc(), &prober).await;
+    assert_eq!(step.state, SyncNetworkState::FineConnection);
+
+    // Los dos primeros fallos solo piden volver a sondear
+    for _ in 0..2 {
+        clock.advance(Duration::from_secs(5));
+        let (step, online) = machine.evaluate(uabc(), &prober).await;
+        assert!(!online);
+        assert_eq!(step.state, SyncNetworkState::FineConnection);
+        assert_eq!(step.transition, None);
+        assert_eq!(step.recheck_after, Some(Duration::from_secs(5)));
+    }
+
+    clock.advance(Duration::from_secs(5));
+    let (step, _) = machine.evaluate(uabc(), &prober).await;
+    let transition = step.transition.unwrap();
+    assert_eq!(step.state, SyncNetworkState::HaveCautivePortal);
+    assert_eq!(
+        transition.cause,
+        TransitionCause::ProbeFailed { failures: 3 }
+    );
+    assert_eq!(transition.time_in_previous, Some(Duration::from_secs(15)));
+    assert_eq!(step.recheck_after, None);
+}
+
+#[tokio::test]
+async fn a_success_resets_the_failure_count() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    let prober = FakeProber::new(&[true, false, false, true, false, false]);
+
+    for _ in 0..6 {
+        let (step, _) = machine.evaluate(uabc(), &prober).await;
+        assert_eq!(step.state, SyncNetworkState::FineConnection);
+        clock.advance(Duration::from_secs(5));
+    }
+}
+
+#[test]
+fn failures_inside_the_debounce_count_once() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    machine.observe(uabc(), true);
+
+    for _ in 0..5 {
+        clock.advance(Duration::from_millis(300));
+        let step = machine.observe(uabc(), false);
+        assert_eq!(step.state, SyncNetworkState::FineConnection);
+    }
+
+    // Solo la primera ráfaga pidió volver a sondear
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(
+        machine.observe(uabc(), false).recheck_after,
+        Some(Duration::from_secs(5))
+    );
+    clock.advance(Duration::from_secs(5));
+    let step = machine.observe(uabc(), false);
+    assert_eq!(step.state, SyncNetworkState::HaveCautivePortal);
+}
+
+#[test]
+fn stale_failures_start_a_new_count() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    machine.observe(uabc(), true);
+
+    for _ in 0..4 {
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(
+            machine.observe(uabc(), false).state,
+            SyncNetworkState::FineConnection
+        );
+    }
+}
+
+#[test]
+fn recovery_and_link_changes_are_immediate() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+
+    let first = machine.observe(uabc(), false);
+    assert_eq!(first.state, SyncNetworkState::HaveCautivePortal);
+    assert_eq!(first.transition.unwrap().from, None);
+
+    let recovered = machine.observe(uabc(), true).transition.unwrap();
+    assert_eq!(recovered.to, SyncNetworkState::FineConnection);
+    assert_eq!(recovered.cause, TransitionCause::ProbeRecovered);
+
+    // Otra red ajena a la UABC
+    let other = Link {
+        ssid: Some("Starbucks".to_string()),
+        network_profile_id: None,
+    };
+    let step = machine.observe(other, false);
+    assert_eq!(step.state, SyncNetworkState::InvalidConnection);
+    assert_eq!(step.transition.unwrap().cause, TransitionCause::Link);
+
+    // Sin WiFi pero con datos móviles
+    let step = machine.observe(Link::default(), true);
+    assert_eq!(step.state, SyncNetworkState::MobileConnection);
+    assert_eq!(step.transition.unwrap().cause, TransitionCause::Link);
+}
+
+#[test]
+fn threshold_follows_the_settings() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    machine.observe(uabc(), true);
+    machine.set_hysteresis(Hysteresis {
+        failures_to_degrade: 1,
+        ..Hysteresis::default()
+    });
+
+    assert_eq!(
+        machine.observe(uabc(), false).state,
+        SyncNetworkState::HaveCautivePortal
+    );
+}
+
+#[test]
+fn hooks_run_exit_then_transition_then_enter() {
+    let clock = FakeClock::new();
+    let machine = machine(&clock);
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let log = |label: &'static str| {
+        let calls = Arc::clone(&calls);
+        move |t: &Transition| {
+            calls.lock().unwrap().push(format!("{label}:{:?}", t.to));
+        }
+    };
+    machine.on_enter(SyncNetworkState::HaveCautivePortal, log("enter"));
+    machine.on_exit(SyncNetworkState::FineConnection, log("exit"));
+    machine.on_transition(log("any"));
+
+    machine.set_hysteresis(Hysteresis {
+        failures_to_degrade: 1,
+        ..Hysteresis::default()
+    });
+    machine.observe(uabc(), true);
+    machine.observe(uabc(), false);
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "any:FineConnection",
+            "exit:HaveCautivePortal",
+            "any:HaveCautivePortal",
+            "enter:HaveCautivePortal",
+        ]
+    );
+}
+
+#[test]
+fn hooks_can_use_the_machine_without_deadlocking() {
+    let clock = FakeClock::new();
+    let machine = Arc::new(machine(&clock));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let nested = Arc::new(Mutex::new(0));
+
+    {
+        let inner = Arc::clone(&machine);
+        let seen = Arc::clone(&seen);
+        let nested = Arc::clone(&nested);
+        machine.on_transition(move |_| {
+            seen.lock().unwrap().push(inner.state());
+            let nested = Arc::clone(&nested);
+            inner.on_transition(move |_| *nested.lock().unwrap() += 1);
+        });
+    }
+
+    machine.set_hysteresis(Hysteresis {
+        failures_to_degrade: 1,
+        ..Hysteresis::default()
+    });
+    machine.observe(uabc(), true);
+    // Los hooks registrados durante una transición corren desde la siguiente
+    assert_eq!(*nested.lock().unwrap(), 0);
+    machine.observe(uabc(), false);
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            Some(SyncNetworkState::FineConnection),
+            Some(SyncNetworkState::HaveCautivePortal),
+        ]
+    );
+    assert_eq!(*nested.lock().unwrap(), 1);
+}
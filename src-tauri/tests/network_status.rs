@@ -0,0 +1,76 @@
+//! Payload tipado de `network-status`.
+
+use cima_sync_lib::network_controller::prober::Connectivity;
+use cima_sync_lib::network_controller::status::{LinkAddresses, NetworkStatus, SyncNetworkState};
+use cima_sync_lib::network_controller::wifi::{WifiInfo, WifiSecurity};
+
+fn uabc_status() -> NetworkStatus {
+    NetworkStatus {
+        connected: true,
+        ssid: Some("UABC".to_string()),
+        is_uabc: true,
+        network_profile_id: Some("uabc".to_string()),
+        network_state: SyncNetworkState::FineConnection,
+        status_text: "WI-FI Cimarrón Autenticado".to_string(),
+        connectivity: Some(Connectivity::Online),
+        connectivity_confidence: Some(1.0),
+        timestamp_ms: 1_760_000_000_000,
+        ..NetworkStatus::default()
+    }
+}
+
+#[test]
+fn link_details_come_from_wifi_and_interface() {
+    let wifi = WifiInfo {
+        interface: "wlp2s0".to_string(),
+        ssid: Some("UABC".to_string()),
+        bssid: Some("a4:6c:2a:5f:10:e2".to_string()),
+        signal: Some(78),
+        rssi_dbm: Some(-61),
+        link_speed_mbps: Some(866),
+        frequency_mhz: Some(5220),
+        security: WifiSecurity::WpaEnterprise,
+    };
+    let addresses = LinkAddresses {
+        interface: Some("wlp2s0".to_string()),
+        ipv4: Some("10.12.34.56".to_string()),
+        ipv6: Some("2001:db8::1a".to_string()),
+        gateway: Some("10.12.0.1".to_string()),
+    };
+
+    let status = uabc_status().with_link(addresses, Some(wifi));
+    let json = serde_json::to_value(&status).unwrap();
+
+    assert_eq!(json["interface"], "wlp2s0");
+    assert_eq!(json["ipv4"], "10.12.34.56");
+    assert_eq!(json["ipv6"], "2001:db8::1a");
+    assert_eq!(json["gateway"], "10.12.0.1");
+    assert_eq!(json["bssid"], "a4:6c:2a:5f:10:e2");
+    assert_eq!(json["rssi_dbm"], -61);
+    assert_eq!(json["link_speed_mbps"], 866);
+    assert_eq!(json["wifi"]["security"], "wpa_enterprise");
+    assert_eq!(json["connectivity"], "online");
+    assert_eq!(json["network_state"], "fineConnection");
+    assert_eq!(json["timestamp_ms"], 1_760_000_000_000i64);
+}
+
+#[test]
+fn missing_link_details_serialize_as_null() {
+    // Android: sin IP ni gateway, solo lo que reporta el observer
+    let wifi = WifiInfo {
+        ssid: Some("UABC".to_string()),
+        rssi_dbm: Some(-70),
+        link_speed_mbps: Some(72),
+        ..WifiInfo::default()
+    };
+
+    let status = uabc_status().with_link(LinkAddresses::default(), Some(wifi));
+    let json = serde_json::to_value(&status).unwrap();
+
+    assert!(json["interface"].is_null());
+    assert!(json["ipv4"].is_null());
+    assert!(json["gateway"].is_null());
+    assert!(json["bssid"].is_null());
+    assert_eq!(json["rssi_dbm"], -70);
+    assert_eq!(json["link_speed_mbps"], 72);
+}
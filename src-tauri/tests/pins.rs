@@ -0,0 +1,134 @@
+//! Conjuntos de pines: respaldo, SPKI, vencimiento y `pins.json` firmado.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use cima_sync_lib::network_controller::pins::{verify_signed_pins, Pin, PinSet};
+use rcgen::{CertificateParams, KeyPair};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair as _};
+use sha2::{Digest, Sha256};
+
+const NOW: i64 = 1_800_000_000;
+
+struct TestCert {
+    der: Vec<u8>,
+    cert_pin: String,
+    spki_pin: String,
+}
+
+fn test_cert() -> TestCert {
+    let key = KeyPair::generate().expect("llave");
+    let cert = CertificateParams::new(vec!["pcw.uabc.mx".to_string()])
+        .expect("parámetros")
+        .self_signed(&key)
+        .expect("certificado");
+
+    TestCert {
+        der: cert.der().to_vec(),
+        cert_pin: format!("cert-sha256:{}", hex::encode(Sha256::digest(cert.der()))),
+        spki_pin: format!("spki-sha256:{}", hex::encode(Sha256::digest(key.public_key_der()))),
+    }
+}
+
+fn pin(value: &str) -> Pin {
+    Pin::parse(value).expect("pin válido")
+}
+
+#[test]
+fn spki_pin_matches_the_certificate_public_key() {
+    let cert = test_cert();
+    let set = PinSet::new("uabc", vec![pin(&cert.spki_pin)]);
+
+    assert_eq!(set.check(&cert.der, NOW), Ok(()));
+}
+
+#[test]
+fn a_backup_pin_is_enough() {
+    let cert = test_cert();
+    let set = PinSet::new("uabc", vec![pin(&"11".repeat(32)), pin(&cert.cert_pin)]);
+
+    assert_eq!(set.check(&cert.der, NOW), Ok(()));
+}
+
+#[test]
+fn expired_pins_are_ignored_and_reported() {
+    let cert = test_cert();
+    let set = PinSet::new("uabc", vec![pin(&cert.spki_pin).expiring_at(NOW - 1)]);
+
+    let mismatch = set.check(&cert.der, NOW).expect_err("el único pin venció");
+    assert_eq!(mismatch.pin_set, "uabc");
+    assert!(mismatch.tried[0].ends_with("[vencido]"));
+    assert_eq!(
+        format!("spki-sha256:{}", mismatch.observed_spki_sha256.expect("SPKI observado")),
+        cert.spki_pin
+    );
+}
+
+#[test]
+fn rejects_malformed_pins() {
+    assert!(Pin::parse("sha1:abcd").is_err());
+    assert!(Pin::parse("spki-sha256:zz").is_err());
+    assert!(Pin::parse(&"ab".repeat(31)).is_err());
+}
+
+fn signed_file(key: &Ed25519KeyPair, payload: &serde_json::Value) -> String {
+    let payload = serde_json::to_vec(payload).expect("payload");
+    serde_json::json!({
+        "payload": BASE64.encode(&payload),
+        "signature": BASE64.encode(key.sign(&payload).as_ref()),
+    })
+    .to_string()
+}
+
+fn signing_key() -> Ed25519KeyPair {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("llave Ed25519");
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("llave Ed25519")
+}
+
+#[test]
+fn signed_pin_file_is_verified_and_expires() {
+    let key = signing_key();
+    let cert = test_cert();
+    let payload = serde_json::json!({
+        "version": 1,
+        "expires_at": NOW + 3600,
+        "pin_sets": [{
+            "name": "uabc",
+            "pins": [
+                { "pin": cert.spki_pin },
+                { "pin": "11".repeat(32), "expires_at": NOW + 60 },
+            ],
+        }],
+    });
+    let contents = signed_file(&key, &payload);
+    let public_key = key.public_key().as_ref();
+
+    let sets = verify_signed_pins(&contents, public_key, NOW).expect("archivo firmado válido");
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].name, "uabc");
+    assert_eq!(sets[0].pins[1].expires_at, Some(NOW + 60));
+    assert_eq!(sets[0].check(&cert.der, NOW), Ok(()));
+
+    assert!(verify_signed_pins(&contents, public_key, NOW + 3600).is_err());
+    assert!(verify_signed_pins(&contents, signing_key().public_key().as_ref(), NOW).is_err());
+}
+
+#[test]
+fn tampered_pin_file_is_rejected() {
+    let key = signing_key();
+    let original = serde_json::json!({
+        "version": 1,
+        "expires_at": NOW + 3600,
+        "pin_sets": [{ "name": "uabc", "pins": [{ "pin": "11".repeat(32) }] }],
+    });
+    let contents = signed_file(&key, &original);
+
+    let mut file: serde_json::Value = serde_json::from_str(&contents).expect("JSON");
+    let forged = serde_json::json!({
+        "version": 1,
+        "expires_at": NOW + 3600,
+        "pin_sets": [{ "name": "uabc", "pins": [{ "pin": "22".repeat(32) }] }],
+    });
+    file["payload"] = BASE64.encode(serde_json::to_vec(&forged).expect("payload")).into();
+
+    assert!(verify_signed_pins(&file.to_string(), key.public_key().as_ref(), NOW).is_err());
+}
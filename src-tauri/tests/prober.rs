@@ -0,0 +1,193 @@
+//! Diagnóstico de conectividad por voto ponderado de varias sondas.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use cima_sync_lib::network_controller::prober::{
+    self, Connectivity, Probe, ProbeKind, ProbeOutcome, ProbeResult,
+};
+use cima_sync_lib::settings::Settings;
+
+fn http_204() -> Probe {
+    Probe::new(
+        ProbeKind::Http204 {
+            url: "http://probe.test/generate_204".to_string(),
+        },
+        3.0,
+    )
+}
+
+fn result(probe: Probe, outcome: ProbeOutcome) -> ProbeResult {
+    ProbeResult {
+        probe,
+        outcome,
+        elapsed_ms: 10,
+    }
+}
+
+fn failed() -> ProbeOutcome {
+    ProbeOutcome::Failed {
+        reason: "sin respuesta".to_string(),
+    }
+}
+
+/// Resultados de las sondas por defecto: (HTTP 204, contenido, DNS, TCP).
+fn scenario(outcomes: [ProbeOutcome; 4]) -> Vec<ProbeResult> {
+    let probes = std::iter::once(http_204()).chain(prober::default_probes());
+    probes.zip(outcomes).map(|(p, o)| result(p, o)).collect()
+}
+
+#[test]
+fn all_probes_reachable_is_online() {
+    use ProbeOutcome::Reachable;
+    let verdict = prober::tally(scenario([Reachable, Reachable, Reachable, Reachable]));
+
+    assert_eq!(verdict.connectivity, Connectivity::Online);
+    assert_eq!(verdict.confidence, 1.0);
+}
+
+#[test]
+fn intercepted_http_is_captive_portal() {
+    use ProbeOutcome::{Intercepted, Reachable};
+    // El portal responde el DNS pero bloquea TCP hacia fuera.
+    let verdict = prober::tally(scenario([Intercepted, Intercepted, Reachable, failed()]));
+
+    assert_eq!(verdict.connectivity, Connectivity::CaptivePortal);
+    assert_eq!(verdict.confidence, 1.0);
+}
+
+#[test]
+fn reachable_tcp_without_dns_is_dns_failure() {
+    let verdict = prober::tally(scenario([
+        failed(),
+        failed(),
+        failed(),
+        ProbeOutcome::Reachable,
+    ]));
+
+    assert_eq!(verdict.connectivity, Connectivity::DnsFailure);
+}
+
+#[test]
+fn nothing_reachable_is_offline() {
+    let verdict = prober::tally(scenario([failed(), failed(), failed(), failed()]));
+
+    assert_eq!(verdict.connectivity, Connectivity::Offline);
+    assert!(!verdict.is_online());
+}
+
+#[test]
+fn one_broken_probe_lowers_confidence_only() {
+    use ProbeOutcome::Reachable;
+    let verdict = prober::tally(scenario([Reachable, failed(), Reachable, Reachable]));
+
+    assert_eq!(verdict.connectivity, Connectivity::Online);
+    assert!(verdict.confidence < 1.0 && verdict.confidence > 0.5);
+}
+
+#[test]
+fn rejects_invalid_probes() {
+    let invalid = [
+        Probe::new(
+            ProbeKind::Tcp {
+                address: "example.com:443".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Dns {
+                host: "http://x".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Content {
+                url: "http://probe.test/".to_string(),
+                expected: " ".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Http204 {
+                url: "ftp://probe.test/".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Tcp {
+                address: "1.1.1.1:443".to_string(),
+            },
+            0.0,
+        ),
+    ];
+
+    for probe in invalid {
+        let config = Settings {
+            connectivity_probes: vec![probe.clone()],
+            ..Settings::default()
+        };
+        assert!(config.validate().is_err(), "aceptó {:?}", probe);
+    }
+}
+
+#[test]
+fn probes_round_trip_through_settings_json() {
+    let json = r#"{ "connectivity_probes": [
+        { "kind": "http_204", "url": "http://probe.test/generate_204", "weight": 2.5 },
+        { "kind": "tcp", "address": "9.9.9.9:53" }
+    ] }"#;
+    let config: Settings = serde_json::from_str(json).unwrap();
+
+    assert_eq!(config.validate(), Ok(()));
+    assert_eq!(config.connectivity_probes[0].weight, 2.5);
+    assert_eq!(config.connectivity_probes[1].weight, 1.0);
+}
+
+/// Servidor HTTP de un solo uso que contesta siempre `response`.
+fn serve(response: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(1) {
+            let mut stream = stream.unwrap();
+            let _ = stream.read(&mut [0u8; 1024]);
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+#[tokio::test]
+async fn portal_page_instead_of_204_is_detected_concurrently() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let portal = serve(
+        "HTTP/1.1 200 OK\r\nContent-Length: 14\r\nConnection: close\r\n\r\n<html>Login</html>",
+    );
+    let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+    let probes = vec![
+        Probe::new(ProbeKind::Http204 { url: portal }, 3.0),
+        Probe::new(
+            ProbeKind::Dns {
+                host: "localhost".to_string(),
+            },
+            1.0,
+        ),
+        Probe::new(
+            ProbeKind::Tcp {
+                address: tcp.local_addr().unwrap().to_string(),
+            },
+            1.0,
+        ),
+    ];
+
+    let verdict = prober::run(&probes, Duration::from_secs(2)).await;
+
+    assert_eq!(verdict.results.len(), 3);
+    assert_eq!(verdict.results[0].outcome, ProbeOutcome::Intercepted);
+    assert_eq!(verdict.results[1].outcome, ProbeOutcome::Reachable);
+    assert_eq!(verdict.results[2].outcome, ProbeOutcome::Reachable);
+    assert_eq!(verdict.connectivity, Connectivity::CaptivePortal);
+}
@@ -0,0 +1,86 @@
+//! Timeouts por llamada y estimación adaptativa del tiempo de respuesta.
+
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use cima_sync_lib::network_controller::client_builder::{PinConfig, pinned_client};
+use cima_sync_lib::network_controller::rtt::{self, RttEstimator};
+use cima_sync_lib::settings::Settings;
+
+const MAX: Duration = Duration::from_secs(20);
+
+#[test]
+fn fast_samples_keep_requested_timeout() {
+    let mut estimator = RttEstimator::default();
+    for _ in 0..5 {
+        estimator.observe(Duration::from_millis(40));
+    }
+
+    assert_eq!(
+        estimator.timeout(Duration::from_secs(3), MAX),
+        Duration::from_secs(3)
+    );
+}
+
+#[test]
+fn slow_samples_stretch_timeout_up_to_max() {
+    let mut estimator = RttEstimator::default();
+    estimator.observe(Duration::from_secs(4));
+
+    let stretched = estimator.timeout(Duration::from_secs(3), MAX);
+    assert!(stretched > Duration::from_secs(4), "timeout: {stretched:?}");
+    assert!(stretched <= MAX);
+
+    estimator.observe(Duration::from_secs(60));
+    assert_eq!(estimator.timeout(Duration::from_secs(3), MAX), MAX);
+}
+
+#[test]
+fn never_shorter_than_requested() {
+    let mut estimator = RttEstimator::default();
+    estimator.observe(Duration::from_secs(2));
+
+    // Un máximo por debajo de lo pedido no recorta el timeout del llamador.
+    let requested = Duration::from_secs(10);
+    assert_eq!(
+        estimator.timeout(requested, Duration::from_secs(5)),
+        requested
+    );
+}
+
+#[test]
+fn unknown_host_uses_requested_timeout() {
+    let base = Duration::from_millis(750);
+    assert_eq!(
+        rtt::timeout_for("https://sin-muestras.invalid/", base),
+        base
+    );
+    assert_eq!(rtt::timeout_for("no es una url", base), base);
+}
+
+#[test]
+fn rejects_adaptive_max_out_of_range() {
+    let config = Settings {
+        max_adaptive_timeout_secs: 0,
+        ..Settings::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[tokio::test]
+async fn client_honors_requested_timeout() {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    // El servidor acepta la conexión pero nunca responde.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let client = pinned_client(Duration::from_millis(300), true, &PinConfig::uabc());
+    let started = Instant::now();
+    let err = client.get(&url).send().await.unwrap_err();
+
+    assert!(err.is_timeout(), "error: {err}");
+    assert!(started.elapsed() < Duration::from_secs(2));
+    drop(listener);
+}
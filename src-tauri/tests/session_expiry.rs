@@ -0,0 +1,102 @@
+//! Duración de sesión aprendida a partir de los cierres observados.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cima_sync_lib::auth::{Auth, LifetimeSource};
+use cima_sync_lib::history;
+use cima_sync_lib::network_controller::network_sync::publish_network_state;
+use cima_sync_lib::network_controller::status::SyncNetworkState;
+use cima_sync_lib::portal_controller::uabc::UabcDriver;
+
+use common::mock_portal::{MockPortal, MockPortalOptions};
+
+// El historial es global: las pruebas que lo inicializan no corren a la vez
+static HISTORY: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn auth_for(portal: &MockPortal) -> Auth {
+    let driver = UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        &portal.pin_config(),
+    );
+    Auth::with_driver("alumno", "secreto", Arc::new(driver))
+}
+
+async fn wait_until(what: &str, done: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("el monitor no llegó a: {}", what);
+}
+
+fn recorded_attempts() -> u64 {
+    history::page(1, 1, None).map(|page| page.total).unwrap_or(0)
+}
+
+#[tokio::test]
+async fn session_lifetime_is_learned_from_observed_logouts() {
+    let _history = HISTORY.lock().await;
+    let dir = std::env::temp_dir().join(format!("cima-sync-session-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    history::init(&dir);
+
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = auth_for(&portal);
+
+    assert!(auth.login().await.is_ok());
+    assert!(auth.session_expiry().is_none(), "sin observaciones no hay predicción");
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    portal.expire_session();
+    assert!(auth.login().await.is_ok());
+
+    let learned = history::learned_session_lifetime(None).expect("duración aprendida");
+    assert_eq!(learned, Duration::from_secs(1));
+
+    let (_, source) = auth.session_expiry().expect("vencimiento previsto");
+    assert_eq!(source, LifetimeSource::Learned);
+    assert_eq!(portal.login_attempts(), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn monitor_learns_the_lifetime_when_the_portal_closes_the_session() {
+    let _history = HISTORY.lock().await;
+    let dir = std::env::temp_dir().join(format!("cima-sync-session-monitor-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    history::init(&dir);
+
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let auth = Arc::new(auth_for(&portal));
+    let worker = {
+        let auth = Arc::clone(&auth);
+        tokio::spawn(async move { auth.start_monitoring().await })
+    };
+
+    // La sesión empieza al terminar la verificación, no al enviar el formulario
+    wait_until("guardar el primer login", || recorded_attempts() >= 1).await;
+    assert_eq!(portal.login_attempts(), 1);
+    publish_network_state(SyncNetworkState::FineConnection, None);
+
+    // En la misma red el portal vuelve a pedir login: ese cierre es el que se mide
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    portal.expire_session();
+    publish_network_state(SyncNetworkState::HaveCautivePortal, None);
+    wait_until("volver a iniciar sesión", || portal.login_attempts() >= 2).await;
+
+    auth.stop_monitoring();
+    let result = worker.await.expect("la tarea de monitoreo entró en pánico");
+    assert!(result.is_ok(), "resultado inesperado: {:?}", result);
+
+    let learned = history::learned_session_lifetime(None).expect("duración aprendida");
+    assert_eq!(learned, Duration::from_secs(1));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,77 @@
+//! Validación y persistencia de la configuración en tiempo de ejecución.
+
+use cima_sync_lib::settings::{self, Settings};
+
+#[test]
+fn default_settings_are_valid() {
+    assert_eq!(Settings::default().validate(), Ok(()));
+}
+
+#[test]
+fn rejects_non_https_portal_url() {
+    let config = Settings {
+        portal_url: "http://pcw.uabc.mx/".to_string(),
+        ..Settings::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn rejects_backoff_limits_out_of_order() {
+    let config = Settings {
+        initial_backoff_secs: 120,
+        max_backoff_secs: 60,
+        ..Settings::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn rejects_empty_verify_urls() {
+    let config = Settings {
+        verify_urls: Vec::new(),
+        ..Settings::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn rejects_zero_connectivity_failure_threshold() {
+    let config = Settings {
+        connectivity_failure_threshold: 0,
+        ..Settings::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn update_persists_and_init_reloads() {
+    let dir = std::env::temp_dir().join(format!("cima-sync-settings-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    settings::init(&dir);
+    assert!(dir.join("settings.json").exists());
+
+    let updated = Settings {
+        success_interval_secs: 45,
+        ..Settings::default()
+    };
+    settings::update(updated.clone()).expect("configuración válida");
+
+    let invalid = Settings {
+        success_interval_secs: 0,
+        ..Settings::default()
+    };
+    assert!(settings::update(invalid).is_err());
+    assert_eq!(settings::current(), updated);
+
+    let stored = std::fs::read_to_string(dir.join("settings.json")).expect("archivo guardado");
+    let reloaded: Settings = serde_json::from_str(&stored).expect("JSON válido");
+    assert_eq!(reloaded, updated);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,156 @@
+//! Aprendizaje del pin en el primer uso contra el portal simulado.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use cima_sync_lib::auth::Auth;
+use cima_sync_lib::error::AuthError;
+use cima_sync_lib::network_controller::client_builder::{self, PinConfig};
+use cima_sync_lib::network_controller::profiles::{self, NetworkProfile};
+use cima_sync_lib::network_controller::tofu::{self, PendingPin};
+use cima_sync_lib::portal_controller::driver::{driver_for_network, PortalDriver};
+use cima_sync_lib::portal_controller::uabc::UabcDriver;
+use cima_sync_lib::settings::{self, Settings};
+
+use common::mock_portal::{MockPortal, MockPortalOptions};
+
+fn learning_profile(portal: &MockPortal) -> NetworkProfile {
+    NetworkProfile {
+        id: "lab".to_string(),
+        name: "Laboratorio".to_string(),
+        ssid_patterns: vec!["LAB-IOT".to_string()],
+        portal_url: Some(portal.portal_url.clone()),
+        trust_on_first_use: true,
+        ..NetworkProfile::default()
+    }
+}
+
+fn pending_for(profile_id: &str) -> Vec<PendingPin> {
+    tofu::pending()
+        .into_iter()
+        .filter(|p| p.profile_id == profile_id)
+        .collect()
+}
+
+fn driver(portal: &MockPortal, pins: PinConfig) -> UabcDriver {
+    UabcDriver::with_endpoints(
+        &portal.portal_url,
+        vec![portal.connectivity_url.clone()],
+        &portal.trusting_ca(pins),
+    )
+}
+
+#[tokio::test]
+async fn first_connection_learns_the_pin_after_approval() {
+    let dir = std::env::temp_dir().join(format!("cima-sync-tofu-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    profiles::init(&dir);
+
+    let portal = MockPortal::start(MockPortalOptions::default());
+    client_builder::trust_root(portal.ca_cert());
+    settings::update(Settings {
+        verify_urls: vec![portal.connectivity_url.clone()],
+        ..settings::current()
+    })
+    .expect("configuración válida");
+
+    let profile = learning_profile(&portal);
+    profiles::save(profile.clone()).expect("perfil válido");
+    assert!(tofu::is_learning(&profile));
+
+    let announced: Arc<Mutex<Vec<PendingPin>>> = Arc::new(Mutex::new(Vec::new()));
+    {
+        let announced = Arc::clone(&announced);
+        tofu::on_pin_pending(move |pending| announced.lock().unwrap().push(pending.clone()));
+    }
+
+    // Primera conexión: sin pines el handshake falla y la huella queda pendiente
+    let learning = driver_for_network(Some(&profile), Some("LAB-IOT")).expect("driver del perfil");
+    for _ in 0..2 {
+        let err = learning.detect().await.expect_err("el perfil aún no tiene pin");
+        let err = tofu::learn_from_error(Some(&profile), err);
+        assert!(
+            matches!(&err, AuthError::PinApprovalRequired { cert_sha256, .. } if cert_sha256 == portal.cert_sha256()),
+            "error inesperado: {:?}",
+            err
+        );
+    }
+    assert_eq!(portal.login_attempts(), 0);
+
+    // Otras pruebas del mismo binario también dejan huellas pendientes
+    let pending = pending_for("lab");
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].cert_sha256, portal.cert_sha256());
+    // Reintentar con la misma huella no vuelve a pedir aprobación
+    let announced_lab = announced
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|p| p.profile_id == "lab")
+        .count();
+    assert_eq!(announced_lab, 1);
+
+    assert!(tofu::approve("lab", &"00".repeat(32)).is_err());
+    let approved = tofu::approve("lab", portal.cert_sha256()).expect("huella pendiente");
+    let stored = format!("cert-sha256:{}", portal.cert_sha256());
+    assert_eq!(approved.cert_sha256.as_deref(), Some(stored.as_str()));
+    assert!(!tofu::is_learning(&approved));
+    assert!(pending_for("lab").is_empty());
+
+    // El perfil vigente ya no reutiliza el driver en aprendizaje: el login
+    // pasa con el pin aprobado y no se vuelve a pedir la huella
+    let current = profiles::get("lab").expect("perfil guardado");
+    let pinned = driver_for_network(Some(&current), Some("LAB-IOT")).expect("driver del perfil");
+    assert!(!Arc::ptr_eq(&learning, &pinned));
+    let auth = Auth::with_driver("alumno", "secreto", pinned);
+    assert!(matches!(auth.login().await, Ok(true)));
+    assert_eq!(portal.login_attempts(), 1);
+    assert!(pending_for("lab").is_empty());
+
+    // El pin aprobado queda en disco
+    profiles::init(&dir);
+    let saved = profiles::get("lab").expect("perfil guardado");
+    assert_eq!(saved.cert_sha256.as_deref(), Some(stored.as_str()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn rejected_certificate_is_treated_as_a_pin_mismatch() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let profile = NetworkProfile {
+        id: "biblioteca".to_string(),
+        ..learning_profile(&portal)
+    };
+    let learning = driver(&portal, tofu::learning_pin_config(&profile));
+
+    let err = tofu::learn_from_error(Some(&profile), learning.detect().await.expect_err("sin pin"));
+    assert!(matches!(err, AuthError::PinApprovalRequired { .. }));
+
+    tofu::reject("biblioteca").expect("huella pendiente");
+    assert!(tofu::reject("biblioteca").is_err());
+
+    let err = tofu::learn_from_error(Some(&profile), learning.detect().await.expect_err("sin pin"));
+    assert!(matches!(err, AuthError::TlsPinMismatch(_)), "error inesperado: {:?}", err);
+    assert!(tofu::pending().iter().all(|p| p.profile_id != "biblioteca"));
+}
+
+#[tokio::test]
+async fn profiles_with_a_pin_are_not_learning() {
+    let portal = MockPortal::start(MockPortalOptions::default());
+    let profile = NetworkProfile {
+        cert_sha256: Some("00".repeat(32)),
+        ..learning_profile(&portal)
+    };
+    let strict = driver(&portal, portal.wrong_pin_config());
+
+    let err = tofu::learn_from_error(Some(&profile), strict.detect().await.expect_err("pin incorrecto"));
+    assert!(matches!(err, AuthError::TlsPinMismatch(_)));
+
+    let without_portal = NetworkProfile {
+        portal_url: None,
+        ..learning_profile(&portal)
+    };
+    assert!(without_portal.validate().is_err());
+}
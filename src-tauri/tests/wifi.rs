@@ -0,0 +1,43 @@
+//! Datos estructurados del enlace WiFi (backend de NetworkManager por D-Bus).
+
+use cima_sync_lib::network_controller::wifi::{self, WifiSecurity};
+
+#[test]
+fn decodes_networkmanager_security_flags() {
+    // Flags, WpaFlags y RsnFlags tal como los publica org.freedesktop.NetworkManager.AccessPoint
+    let cases = [
+        ((0x0, 0x0, 0x0), WifiSecurity::Open),
+        ((0x1, 0x0, 0x0), WifiSecurity::Wep),
+        ((0x1, 0x0, 0x188), WifiSecurity::WpaPersonal),
+        ((0x1, 0x0, 0x488), WifiSecurity::Wpa3Personal),
+        ((0x1, 0x0, 0x588), WifiSecurity::Wpa3Personal),
+        ((0x1, 0x388, 0x388), WifiSecurity::WpaEnterprise),
+        ((0x0, 0x0, 0x888), WifiSecurity::Owe),
+    ];
+
+    for ((flags, wpa, rsn), expected) in cases {
+        assert_eq!(
+            WifiSecurity::from_nm_flags(flags, wpa, rsn),
+            expected,
+            "flags={flags:#x} wpa={wpa:#x} rsn={rsn:#x}"
+        );
+    }
+}
+
+#[test]
+fn ssid_bytes_keep_colons_and_backslashes() {
+    assert_eq!(
+        wifi::decode_ssid(br"Cimarron:Lab\2").as_deref(),
+        Some(r"Cimarron:Lab\2")
+    );
+    assert_eq!(
+        wifi::decode_ssid("Biblioteca Cimarrón".as_bytes()).as_deref(),
+        Some("Biblioteca Cimarrón")
+    );
+}
+
+#[test]
+fn hidden_network_has_no_ssid() {
+    assert_eq!(wifi::decode_ssid(b""), None);
+    assert_eq!(wifi::decode_ssid(&[0u8; 8]), None);
+}
@@ -0,0 +1,214 @@
+//! Backends de línea de comandos del enlace WiFi, con salidas grabadas de
+//! nmcli, netsh, airport, networksetup y system_profiler en
+//! tests/fixtures/wifi.
+
+use std::collections::HashMap;
+
+use cima_sync_lib::network_controller::wifi::WifiSecurity;
+use cima_sync_lib::network_controller::wifi_backend::{
+    CommandRunner, LinuxCommands, MacCommands, WifiBackend, WindowsCommands,
+};
+use cima_sync_lib::network_controller::wifi_parse;
+
+/// Salida tal como la entrega `SystemCommands` (UTF-8 con pérdida).
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/wifi/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    String::from_utf8_lossy(&std::fs::read(path).unwrap()).into_owned()
+}
+
+/// Responde a cada programa con un fixture; los demás "no están instalados".
+#[derive(Default)]
+struct RecordedCommands(HashMap<&'static str, String>);
+
+impl RecordedCommands {
+    fn with(mut self, program: &'static str, fixture_name: &str) -> Self {
+        self.0.insert(program, fixture(fixture_name));
+        self
+    }
+}
+
+impl CommandRunner for RecordedCommands {
+    fn run(&self, program: &str, _args: &[&str]) -> Result<String, String> {
+        self.0
+            .get(program)
+            .cloned()
+            .ok_or_else(|| format!("{program}: no encontrado"))
+    }
+}
+
+#[test]
+fn nmcli_lines_unescape_colons_and_backslashes() {
+    let line = wifi_parse::parse_nmcli_wifi_line(r"yes:Cimarron\:Lab\\2:wlp2s0").unwrap();
+
+    assert!(line.active);
+    assert_eq!(line.ssid, r"Cimarron:Lab\2");
+    assert_eq!(line.device, "wlp2s0");
+    assert_eq!(wifi_parse::parse_nmcli_wifi_line("sin separadores"), None);
+}
+
+#[test]
+fn nmcli_picks_the_active_line_of_the_requested_device() {
+    let output = fixture("nmcli_multiple.txt");
+
+    let line = wifi_parse::nmcli_active_line(&output, "wlp2s0").unwrap();
+    assert_eq!(line.ssid, r"Cimarron:Lab\2");
+
+    let usb = wifi_parse::nmcli_active_line(&output, "wlx00c0ca5a1b2c").unwrap();
+    assert_eq!(usb.ssid, "UABC-Invitados");
+
+    assert_eq!(wifi_parse::nmcli_active_line(&output, "wlan0"), None);
+}
+
+#[test]
+fn linux_falls_back_to_nmcli_without_iwgetid() {
+    let backend =
+        LinuxCommands::new(RecordedCommands::default().with("nmcli", "nmcli_multiple.txt"));
+
+    let info = backend.link("wlp2s0").unwrap().unwrap();
+    assert_eq!(info.ssid.as_deref(), Some(r"Cimarron:Lab\2"));
+    assert_eq!(info.bssid, None);
+
+    // nmcli responde pero la interfaz no tiene punto de acceso activo
+    assert_eq!(backend.link("wlan0"), Ok(None));
+}
+
+#[test]
+fn linux_hidden_network_is_associated_without_ssid() {
+    let backend = LinuxCommands::new(RecordedCommands::default().with("nmcli", "nmcli_hidden.txt"));
+
+    let info = backend.link("wlan0").unwrap().unwrap();
+    assert_eq!(info.ssid, None);
+}
+
+#[test]
+fn netsh_english_output() {
+    let iface = wifi_parse::netsh_interface(&fixture("netsh_en.txt"), "Wi-Fi").unwrap();
+
+    assert!(iface.connected);
+    assert_eq!(iface.info.ssid.as_deref(), Some("UABC"));
+    assert_eq!(iface.info.bssid.as_deref(), Some("a4:6c:2a:5f:10:e2"));
+    assert_eq!(iface.info.signal, Some(78));
+    assert_eq!(iface.info.link_speed_mbps, Some(866));
+    assert_eq!(iface.info.frequency_mhz, Some(5220));
+    assert_eq!(iface.info.security, WifiSecurity::WpaEnterprise);
+}
+
+#[test]
+fn netsh_spanish_output_in_oem_code_page() {
+    let iface = wifi_parse::netsh_interface(&fixture("netsh_es_cp850.txt"), "Wi-Fi").unwrap();
+
+    // Los acentos no sobreviven a la página de códigos OEM, pero los campos sí
+    assert!(iface.connected);
+    assert!(iface.info.ssid.unwrap().starts_with("Biblioteca Cimarr"));
+    assert_eq!(iface.info.bssid.as_deref(), Some("5c:5b:35:8e:21:7a"));
+    assert_eq!(iface.info.signal, Some(91));
+    assert_eq!(iface.info.frequency_mhz, Some(5180));
+    assert_eq!(iface.info.security, WifiSecurity::WpaPersonal);
+}
+
+#[test]
+fn netsh_multiple_adapters_on_windows_11() {
+    let output = fixture("netsh_multiple_win11.txt");
+    let interfaces = wifi_parse::parse_netsh_interfaces(&output);
+
+    assert_eq!(interfaces.len(), 2);
+    assert!(!interfaces[0].connected);
+    assert_eq!(interfaces[0].info.ssid, None);
+
+    let usb = &interfaces[1];
+    assert!(usb.connected);
+    assert_eq!(usb.info.interface, "Wi-Fi 2");
+    assert_eq!(usb.info.ssid.as_deref(), Some("UABC-Invitados"));
+    assert_eq!(usb.info.bssid.as_deref(), Some("00:1a:1e:03:bc:40"));
+    assert_eq!(usb.info.frequency_mhz, Some(2437));
+    assert_eq!(usb.info.security, WifiSecurity::Open);
+
+    // Sin coincidencia de nombre se usa el adaptador conectado
+    let fallback = wifi_parse::netsh_interface(&output, "Ethernet 3").unwrap();
+    assert_eq!(fallback.info.interface, "Wi-Fi 2");
+}
+
+#[test]
+fn windows_backend_reports_disconnected_and_hidden_networks() {
+    let multiple =
+        WindowsCommands::new(RecordedCommands::default().with("netsh", "netsh_multiple_win11.txt"));
+    assert_eq!(multiple.link("Wi-Fi"), Ok(None));
+
+    let hidden =
+        WindowsCommands::new(RecordedCommands::default().with("netsh", "netsh_hidden.txt"));
+    let info = hidden.link("Wi-Fi").unwrap().unwrap();
+    assert_eq!(info.ssid, None);
+    assert_eq!(info.bssid.as_deref(), Some("f0:9f:c2:71:aa:03"));
+    assert_eq!(info.frequency_mhz, Some(5745));
+
+    let missing = WindowsCommands::new(RecordedCommands::default());
+    assert!(missing.link("Wi-Fi").is_err());
+}
+
+#[test]
+fn airport_info_with_unicode_ssid() {
+    let info = wifi_parse::parse_airport_info(&fixture("airport_unicode.txt"), "en0").unwrap();
+
+    assert_eq!(info.ssid.as_deref(), Some("Biblioteca Cimarrón"));
+    assert_eq!(info.bssid.as_deref(), Some("5c:5b:35:8e:21:7a"));
+    assert_eq!(info.rssi_dbm, Some(-58));
+    assert_eq!(info.link_speed_mbps, Some(585));
+    assert_eq!(info.frequency_mhz, Some(5180));
+    assert_eq!(info.security, WifiSecurity::WpaPersonal);
+
+    assert_eq!(
+        wifi_parse::parse_airport_info(&fixture("airport_off.txt"), "en0"),
+        None
+    );
+}
+
+#[test]
+fn networksetup_keeps_colons_and_detects_no_association() {
+    assert_eq!(
+        wifi_parse::parse_networksetup_network(&fixture("networksetup_colon.txt")).as_deref(),
+        Some(r"Cimarron:Lab\2")
+    );
+    assert_eq!(
+        wifi_parse::parse_networksetup_network(&fixture("networksetup_not_associated.txt")),
+        None
+    );
+}
+
+#[test]
+fn system_profiler_reads_the_requested_interface() {
+    let output = fixture("system_profiler_multi.txt");
+
+    let en0 = wifi_parse::parse_system_profiler(&output, "en0").unwrap();
+    assert_eq!(en0.ssid.as_deref(), Some("UABC"));
+    assert_eq!(en0.rssi_dbm, Some(-61));
+    assert_eq!(en0.link_speed_mbps, Some(1201));
+    assert_eq!(en0.frequency_mhz, Some(6135));
+    assert_eq!(en0.security, WifiSecurity::WpaEnterprise);
+
+    let en1 = wifi_parse::parse_system_profiler(&output, "en1").unwrap();
+    assert_eq!(en1.ssid.as_deref(), Some("Cimarron:Lab"));
+    assert_eq!(en1.frequency_mhz, Some(5745));
+    assert_eq!(en1.security, WifiSecurity::Wpa3Personal);
+
+    assert_eq!(wifi_parse::parse_system_profiler(&output, "awdl0"), None);
+}
+
+#[test]
+fn mac_backend_falls_through_to_system_profiler() {
+    let backend = MacCommands::new(
+        RecordedCommands::default()
+            .with("/usr/sbin/airport", "airport_off.txt")
+            .with("networksetup", "networksetup_not_associated.txt")
+            .with("system_profiler", "system_profiler_redacted.txt"),
+    );
+
+    // Sin permiso de ubicación macOS oculta el SSID, pero el enlace existe
+    let info = backend.link("en0").unwrap().unwrap();
+    assert_eq!(info.ssid, None);
+    assert_eq!(info.rssi_dbm, Some(-55));
+    assert_eq!(info.security, WifiSecurity::WpaPersonal);
+}
@@ -0,0 +1,114 @@
+//! Enlace WiFi desde la WLAN API de Windows, con respuestas grabadas de
+//! `WlanEnumInterfaces` y `WlanQueryInterface` en tests/fixtures/wlan.
+
+use cima_sync_lib::network_controller::wifi::WifiSecurity;
+use cima_sync_lib::network_controller::wlan::{self, WlanInterface, WlanSource};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct FixtureInterface {
+    guid: String,
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct FixtureWlan {
+    interfaces: Vec<FixtureInterface>,
+    connection_attributes: String,
+    rssi: Option<i32>,
+    channel: Option<u32>,
+}
+
+impl FixtureWlan {
+    fn load(name: &str) -> Self {
+        let path = format!(
+            "{}/tests/fixtures/wlan/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let text = std::fs::read_to_string(&path).unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    fn attributes(&self) -> Vec<u8> {
+        let hex = self.connection_attributes.as_bytes();
+        hex.chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+}
+
+impl WlanSource for FixtureWlan {
+    fn interfaces(&self) -> Result<Vec<WlanInterface>, String> {
+        Ok(self
+            .interfaces
+            .iter()
+            .map(|iface| WlanInterface {
+                guid: iface.guid.clone(),
+                description: iface.description.clone(),
+            })
+            .collect())
+    }
+
+    fn connection_attributes(&self, _: &WlanInterface) -> Result<Option<Vec<u8>>, String> {
+        Ok(Some(self.attributes()))
+    }
+
+    fn rssi(&self, _: &WlanInterface) -> Result<Option<i32>, String> {
+        Ok(self.rssi)
+    }
+
+    fn channel(&self, _: &WlanInterface) -> Result<Option<u32>, String> {
+        Ok(self.channel)
+    }
+}
+
+#[test]
+fn reads_enterprise_network_with_rssi_and_channel() {
+    let source = FixtureWlan::load("uabc_wpa2_enterprise");
+    let info = wlan::read_wifi_info(&source, "Wi-Fi").unwrap().unwrap();
+
+    assert_eq!(info.interface, "Wi-Fi");
+    assert_eq!(info.ssid.as_deref(), Some("UABC"));
+    assert_eq!(info.bssid.as_deref(), Some("a4:6c:2a:5f:10:e2"));
+    assert_eq!(info.signal, Some(78));
+    assert_eq!(info.rssi_dbm, Some(-61));
+    assert_eq!(info.link_speed_mbps, Some(866));
+    assert_eq!(info.frequency_mhz, Some(5220));
+    assert_eq!(info.security, WifiSecurity::WpaEnterprise);
+}
+
+#[test]
+fn reads_open_network_on_2_4_ghz() {
+    let source = FixtureWlan::load("open_guest");
+    let info = wlan::read_wifi_info(&source, "Wi-Fi").unwrap().unwrap();
+
+    assert_eq!(info.ssid.as_deref(), Some("UABC-Invitados"));
+    assert_eq!(info.frequency_mhz, Some(2437));
+    assert_eq!(info.security, WifiSecurity::Open);
+}
+
+#[test]
+fn ssid_with_colons_is_kept_verbatim() {
+    let source = FixtureWlan::load("ssid_with_colons");
+    let info = wlan::read_wifi_info(&source, "Wi-Fi").unwrap().unwrap();
+
+    assert_eq!(info.ssid.as_deref(), Some(r"Cimarron:Lab\2"));
+    assert_eq!(info.rssi_dbm, None);
+    assert_eq!(info.security, WifiSecurity::Wpa3Personal);
+}
+
+#[test]
+fn disconnected_interface_has_no_link() {
+    let source = FixtureWlan::load("disconnected");
+
+    assert_eq!(wlan::read_wifi_info(&source, "Wi-Fi"), Ok(None));
+}
+
+#[test]
+fn truncated_attributes_are_rejected() {
+    let bytes = FixtureWlan::load("uabc_wpa2_enterprise").attributes();
+
+    assert!(wlan::parse_connection_attributes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(wlan::parse_connection_attributes(&[]).is_err());
+}
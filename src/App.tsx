@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useCallback, useEffect, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { useTour } from "@reactour/tour";
@@ -11,7 +12,13 @@ import { useTourAutoOpen } from "./hooks/useTourAutoOpen";
 import { useDeviceStore } from "./store/deviceStore";
 import { useUiStore } from "./store/uiStore";
 import { useSessionStore } from "./store/sessionStore";
-import type { AppProps, AppState } from "./types";
+import type {
+	AppProps,
+	AppState,
+	EventPayload,
+	NetworkSyncState,
+} from "./types";
+import { isAuthError } from "./types";
 
 import { BugModal } from "./components/BugModal";
 import { CertificateAlert } from "./components/CertificateAlert";
@@ -35,13 +42,6 @@ import {
 	stopBackgroundService,
 } from "./controller/backgroundService";
 
-type NetworkSyncState =
-	| "fineConnection"
-	| "haveCautivePortal"
-	| "invalidConnection"
-	| "mobileConnection"
-	| "mobileConnectionRequiereAuth";
-
 const STATUS_CONFIG: Record<
 	NetworkSyncState,
 	{
@@ -158,6 +158,61 @@ function App({ showTourFirstTime = false }: AppProps) {
 		void refreshAuthStatus();
 	}, [refreshAuthStatus]);
 
+	useEffect(() => {
+		const unlisten = listen<EventPayload<"security-alert">>(
+			"security-alert",
+			(event) => {
+				console.warn("Security alert:", event.payload);
+				setAppState((prev) => ({
+					...prev,
+					error: t(`SecurityAlert.${event.payload.kind}`, {
+						defaultValue: event.payload.message,
+					}),
+				}));
+			},
+		);
+		return () => {
+			void unlisten.then((fn) => fn());
+		};
+	}, [t]);
+
+	useEffect(() => {
+		const unlisten = listen<EventPayload<"pin-approval-required">>(
+			"pin-approval-required",
+			async (event) => {
+				const pending = event.payload;
+				const approved = window.confirm(
+					t("PinApproval.prompt", {
+						name: pending.profile_name,
+						portal: pending.portal_url ?? "",
+						fingerprint: pending.cert_sha256,
+					}),
+				);
+				try {
+					if (approved) {
+						await invoke("approve_network_pin", {
+							profileId: pending.profile_id,
+							certSha256: pending.cert_sha256,
+						});
+					} else {
+						await invoke("reject_network_pin", {
+							profileId: pending.profile_id,
+						});
+					}
+				} catch (error) {
+					console.error("Error resolving pending pin:", error);
+					setAppState((prev) => ({
+						...prev,
+						error: t("PinApproval.error"),
+					}));
+				}
+			},
+		);
+		return () => {
+			void unlisten.then((fn) => fn());
+		};
+	}, [t]);
+
 	const handleLogin = useCallback(async () => {
 		if (!credentials.email || !credentials.password) {
 			setPendingSource("login");
@@ -203,16 +258,16 @@ function App({ showTourFirstTime = false }: AppProps) {
 			}
 		} catch (error) {
 			console.error("Login error:", error);
-			const errorStr = String(error).toLowerCase();
 			if (
-				errorStr.includes("certificate") ||
-				errorStr.includes("ssl") ||
-				errorStr.includes("tls") ||
-				errorStr.includes("expired")
+				isAuthError(error) &&
+				(error.code === "tls_pin_mismatch" || error.code === "tls_error")
 			) {
 				openCertificateAlert();
 			}
-			setAppState((prev) => ({ ...prev, error: String(error) }));
+			setAppState((prev) => ({
+				...prev,
+				error: isAuthError(error) ? error.message : String(error),
+			}));
 		} finally {
 			setAppState((prev) => ({ ...prev, loading: false }));
 		}
@@ -252,7 +307,10 @@ function App({ showTourFirstTime = false }: AppProps) {
 			}
 		} catch (error) {
 			console.error("Activate mode error:", error);
-			setAppState((prev) => ({ ...prev, error: String(error) }));
+			setAppState((prev) => ({
+				...prev,
+				error: isAuthError(error) ? error.message : String(error),
+			}));
 		} finally {
 			setAppState((prev) => ({ ...prev, loading: false }));
 		}
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NetworkStatus } from "./NetworkStatus";
+import type { PendingPin } from "./PendingPin";
+import type { SecurityAlert } from "./SecurityAlert";
+import type { UabcDetected } from "./UabcDetected";
+
+/**
+ * Payload de cada evento: los campos del evento quedan al nivel superior
+ * junto a `schema_version` y `type`.
+ */
+export type AppEventPayload = { schema_version: number, } & ({ "type": "network-status" } & NetworkStatus | { "type": "uabc-detected" } & UabcDetected | { "type": "security-alert" } & SecurityAlert | { "type": "pin-approval-required" } & PendingPin);
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Connectivity } from "./Connectivity";
+import type { SyncNetworkState } from "./SyncNetworkState";
+import type { WifiInfo } from "./WifiInfo";
+
+export type NetworkStatus = { connected: boolean, ssid: string | null, is_uabc: boolean, network_profile_id: string | null, network_state: SyncNetworkState, status_text: string, 
+/**
+ * `None` cuando el estado viene del sistema operativo sin sondear.
+ */
+connectivity: Connectivity | null, connectivity_confidence: number | null, captive_portal_url: string | null, session_seconds_remaining: number | null, interface: string | null, ipv4: string | null, ipv6: string | null, gateway: string | null, bssid: string | null, rssi_dbm: number | null, link_speed_mbps: number | null, wifi: WifiInfo | null, 
+/**
+ * Motivo por el que no se pudo leer el estado; `None` en lecturas normales.
+ */
+error: string | null, timestamp_ms: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Evento `pin-approval-required`: huella observada en la primera conexión a
+ * un portal, a la espera de que el usuario la apruebe.
+ */
+export type PendingPin = { profile_id: string, profile_name: string, portal_url: string | null, 
+/**
+ * SHA-256 (hex) del certificado presentado; es lo que se guarda al aprobar.
+ */
+cert_sha256: string, spki_sha256: string | null, observed_at_ms: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SecurityAlertKind } from "./SecurityAlertKind";
+
+/**
+ * Evento `security-alert`: por qué se bloqueó el envío de credenciales.
+ */
+export type SecurityAlert = { timestamp_ms: number, kind: SecurityAlertKind, ssid: string | null, message: string, details: string | null, };
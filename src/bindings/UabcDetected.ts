@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Evento `uabc-detected`: la red actual coincide con un perfil.
+ */
+export type UabcDetected = { ssid: string | null, network_profile_id: string, };
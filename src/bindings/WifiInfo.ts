@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WifiSecurity } from "./WifiSecurity";
+
+export type WifiInfo = { interface: string, ssid: string | null, bssid: string | null, 
+/**
+ * Intensidad de la señal en porcentaje (0–100).
+ */
+signal: number | null, rssi_dbm: number | null, link_speed_mbps: number | null, frequency_mhz: number | null, security: WifiSecurity, };
@@ -1,7 +1,12 @@
 import { addPluginListener, invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { create } from "zustand";
-import type { NetworkStatusPayload } from "../types";
+import { EVENT_SCHEMA_VERSION } from "../types";
+import type {
+	Connectivity,
+	EventPayload,
+	NetworkStatusPayload,
+} from "../types";
 
 interface WifiEvent {
 	event: "available" | "lost" | "capabilitiesChanged" | "unavailable";
@@ -32,10 +37,38 @@ const DEFAULT_NETWORK_STATUS: NetworkStatusPayload = {
 	connected: false,
 	ssid: null,
 	is_uabc: false,
+	network_profile_id: null,
 	network_state: "invalidConnection",
 	status_text: "WI-FI Cimarrón No Disponible",
+	connectivity: null,
+	connectivity_confidence: null,
+	captive_portal_url: null,
+	session_seconds_remaining: null,
+	interface: null,
+	ipv4: null,
+	ipv6: null,
+	gateway: null,
+	bssid: null,
+	rssi_dbm: null,
+	link_speed_mbps: null,
+	wifi: null,
+	error: null,
+	timestamp_ms: 0,
 };
 
+const stringOrNull = (value: unknown): string | null =>
+	typeof value === "string" ? value : null;
+
+const numberOrNull = (value: unknown): number | null =>
+	typeof value === "number" ? value : null;
+
+const CONNECTIVITY_VALUES: Connectivity[] = [
+	"online",
+	"captivePortal",
+	"dnsFailure",
+	"offline",
+];
+
 const normalizeNetworkState = (
 	value: unknown,
 ): NetworkStatusPayload["network_state"] => {
@@ -71,11 +104,43 @@ const toNetworkStatusPayload = (payload: unknown): NetworkStatusPayload => {
 		connected: Boolean(value.connected),
 		ssid: typeof value.ssid === "string" ? value.ssid : null,
 		is_uabc: Boolean(value.is_uabc),
+		network_profile_id:
+			typeof value.network_profile_id === "string"
+				? value.network_profile_id
+				: null,
 		network_state: normalizeNetworkState(value.network_state),
 		status_text:
 			typeof value.status_text === "string"
 				? value.status_text
 				: DEFAULT_NETWORK_STATUS.status_text,
+		connectivity: CONNECTIVITY_VALUES.includes(
+			value.connectivity as Connectivity,
+		)
+			? (value.connectivity as Connectivity)
+			: null,
+		connectivity_confidence:
+			typeof value.connectivity_confidence === "number"
+				? value.connectivity_confidence
+				: null,
+		captive_portal_url:
+			typeof value.captive_portal_url === "string"
+				? value.captive_portal_url
+				: null,
+		session_seconds_remaining:
+			typeof value.session_seconds_remaining === "number"
+				? value.session_seconds_remaining
+				: null,
+		interface: stringOrNull(value.interface),
+		ipv4: stringOrNull(value.ipv4),
+		ipv6: stringOrNull(value.ipv6),
+		gateway: stringOrNull(value.gateway),
+		bssid: stringOrNull(value.bssid),
+		rssi_dbm: numberOrNull(value.rssi_dbm),
+		link_speed_mbps: numberOrNull(value.link_speed_mbps),
+		wifi: value.wifi && typeof value.wifi === "object" ? value.wifi : null,
+		error: stringOrNull(value.error),
+		timestamp_ms:
+			typeof value.timestamp_ms === "number" ? value.timestamp_ms : Date.now(),
 	};
 };
 
@@ -90,6 +155,7 @@ const applyNetworkStatus = (
 		isUabcConnected: status.is_uabc,
 		networkState: status.network_state,
 		statusText: status.status_text,
+		networkError: status.error,
 	});
 };
 
@@ -108,9 +174,15 @@ export const useNetworkStore = create<NetworkState>((set, get) => ({
 
 		try {
 			// Escucha los eventos de estado de red emitidos por Rust
-			const unlistenStatus = await listen<NetworkStatusPayload>(
+			const unlistenStatus = await listen<EventPayload<"network-status">>(
 				"network-status",
 				(event) => {
+					if (event.payload.schema_version !== EVENT_SCHEMA_VERSION) {
+						console.warn(
+							"Unexpected network-status schema version:",
+							event.payload.schema_version,
+						);
+					}
 					applyNetworkStatus(set, event.payload);
 				},
 			);
@@ -8,17 +8,108 @@ export interface AppState {
 	success: boolean;
 }
 
-export type NetworkSyncState =
-	| "fineConnection"
-	| "haveCautivePortal"
-	| "invalidConnection"
-	| "mobileConnection"
-	| "mobileConnectionRequiereAuth";
-
-export interface NetworkStatusPayload {
-	connected: boolean;
+export type { AppEventPayload } from "./bindings/AppEventPayload";
+export type { Connectivity } from "./bindings/Connectivity";
+export type { NetworkStatus } from "./bindings/NetworkStatus";
+export type { PendingPin } from "./bindings/PendingPin";
+export type { SecurityAlert } from "./bindings/SecurityAlert";
+export type { SecurityAlertKind } from "./bindings/SecurityAlertKind";
+export type { SyncNetworkState as NetworkSyncState } from "./bindings/SyncNetworkState";
+export type { UabcDetected } from "./bindings/UabcDetected";
+export type { WifiInfo } from "./bindings/WifiInfo";
+export type { WifiSecurity } from "./bindings/WifiSecurity";
+
+import type { AppEventPayload } from "./bindings/AppEventPayload";
+import type { NetworkStatus } from "./bindings/NetworkStatus";
+
+/** Debe coincidir con `events::EVENT_SCHEMA_VERSION` en Rust. */
+export const EVENT_SCHEMA_VERSION = 1;
+
+export type AppEventName = AppEventPayload["type"];
+
+/** Payload de `listen(name)`: campos del evento más `schema_version` y `type`. */
+export type EventPayload<Name extends AppEventName> = Extract<
+	AppEventPayload,
+	{ type: Name }
+>;
+
+export type NetworkStatusPayload = NetworkStatus;
+
+export type AuthErrorCode =
+	| "dns_failure"
+	| "tls_pin_mismatch"
+	| "tls_error"
+	| "portal_not_present"
+	| "invalid_credentials"
+	| "portal_http_error"
+	| "verification_failed"
+	| "timeout"
+	| "network_unreachable"
+	| "unsafe_portal"
+	| "pin_approval_required"
+	| "invalid_input"
+	| "internal";
+
+export interface AuthErrorPayload {
+	code: AuthErrorCode;
+	message: string;
+	details: string | null;
+}
+
+export const isAuthError = (error: unknown): error is AuthErrorPayload =>
+	typeof error === "object" &&
+	error !== null &&
+	"code" in error &&
+	"message" in error;
+
+export type AuthAttemptOutcome =
+	| "authenticated"
+	| "already_authenticated"
+	| "failed";
+
+export interface AuthHistoryEntry {
+	id: number;
+	timestamp_ms: number;
 	ssid: string | null;
-	is_uabc: boolean;
-	network_state: NetworkSyncState;
-	status_text: string;
+	outcome: AuthAttemptOutcome;
+	error_code: AuthErrorCode | null;
+	error_message: string | null;
+	detect_ms: number | null;
+	submit_ms: number | null;
+	verify_ms: number | null;
+	total_ms: number;
+	backoff_secs: number | null;
+}
+
+export interface AuthHistoryPage {
+	entries: AuthHistoryEntry[];
+	page: number;
+	page_size: number;
+	total: number;
+	stats: {
+		attempts: number;
+		successes: number;
+		success_rate: number | null;
+		mean_time_to_auth_ms: number | null;
+	};
+}
+
+export interface ProfileSummary {
+	id: string;
+	label: string;
+	email: string;
+	active: boolean;
+	created_at_ms: number;
+}
+
+export interface NetworkProfile {
+	id: string;
+	name: string;
+	ssid_patterns: string[];
+	bssids: string[];
+	gateway_macs: string[];
+	credential_profile_id: string | null;
+	portal_url: string | null;
+	cert_sha256: string | null;
+	trust_on_first_use: boolean;
 }